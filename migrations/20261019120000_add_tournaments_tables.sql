@@ -0,0 +1,35 @@
+CREATE TABLE tournaments
+(
+    id             INTEGER PRIMARY KEY AUTOINCREMENT,
+    name           TEXT    NOT NULL,
+    format         TEXT    NOT NULL,
+    rounds         INTEGER NOT NULL,
+    games_per_pair INTEGER NOT NULL,
+    finished       INTEGER NOT NULL,
+    created_at     INTEGER NOT NULL
+);
+
+CREATE TABLE tournament_participants
+(
+    tournament_id INTEGER NOT NULL,
+    bot_id        INTEGER NOT NULL,
+    `index`       INTEGER NOT NULL,
+    PRIMARY KEY (tournament_id, bot_id),
+    FOREIGN KEY (tournament_id) REFERENCES tournaments (id) ON DELETE CASCADE
+);
+
+-- bot_id_2 is NULL for a bye, ranks are NULL until the game is finished
+CREATE TABLE tournament_games
+(
+    id            INTEGER PRIMARY KEY AUTOINCREMENT,
+    tournament_id INTEGER NOT NULL,
+    round         INTEGER NOT NULL,
+    seed          INTEGER NOT NULL,
+    bot_id_1      INTEGER NOT NULL,
+    bot_id_2      INTEGER,
+    rank_1        INTEGER,
+    rank_2        INTEGER,
+    FOREIGN KEY (tournament_id) REFERENCES tournaments (id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_tournament_games_tournament_id ON tournament_games(tournament_id);
@@ -0,0 +1,7 @@
+CREATE TABLE bot_tags
+(
+    bot_id INTEGER NOT NULL,
+    tag    TEXT    NOT NULL,
+    PRIMARY KEY (bot_id, tag),
+    FOREIGN KEY (bot_id) REFERENCES bots (id) ON DELETE CASCADE
+);
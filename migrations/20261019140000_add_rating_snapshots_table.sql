@@ -0,0 +1,14 @@
+-- leaderboard_id is 0 for the global leaderboard
+CREATE TABLE rating_snapshots
+(
+    leaderboard_id INTEGER NOT NULL,
+    bot_id         INTEGER NOT NULL,
+    rating_mu      REAL    NOT NULL,
+    rating_sigma   REAL    NOT NULL,
+    rank           INTEGER NOT NULL,
+    total_matches  INTEGER NOT NULL,
+    created_at     INTEGER NOT NULL,
+    FOREIGN KEY (bot_id) REFERENCES bots (id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_rating_snapshots_leaderboard_id ON rating_snapshots(leaderboard_id);
@@ -0,0 +1,3 @@
+-- NULL means the arena-wide [ranking] and [leaderboards] config is used
+ALTER TABLE leaderboards ADD COLUMN ranking TEXT;
+ALTER TABLE leaderboards ADD COLUMN uncertainty_coefficient REAL;
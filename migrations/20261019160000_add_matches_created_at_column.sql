@@ -0,0 +1,4 @@
+ALTER TABLE matches ADD COLUMN created_at INTEGER NOT NULL DEFAULT 0;
+
+-- the real time of the existing matches is unknown, treat them as played now
+UPDATE matches SET created_at = CAST(strftime('%s', 'now') AS INTEGER);
@@ -0,0 +1,2 @@
+-- JSON list of attribute aggregations, NULL means no extra columns
+ALTER TABLE leaderboards ADD COLUMN columns TEXT;
@@ -0,0 +1,8 @@
+-- arena-wide [ranking] and [leaderboards] config the global rating history was recorded with,
+-- a single row
+CREATE TABLE global_rating_settings
+(
+    id                      INTEGER PRIMARY KEY CHECK (id = 0),
+    ranking                 TEXT NOT NULL,
+    uncertainty_coefficient REAL NOT NULL
+);
@@ -3,7 +3,9 @@ mod models;
 mod routes;
 mod web_router;
 
-use crate::api::routes::{bots, charts, enable_matchmaking, fetch_status, leaderboards};
+use crate::api::routes::{
+    bots, charts, enable_matchmaking, fetch_status, leaderboards, matches, tournaments,
+};
 use crate::api::web_router::create_web_router;
 use crate::arena_handle::ArenaHandle;
 use axum::routing::{delete, get, patch, post, put};
@@ -35,15 +37,49 @@ async fn create_router(app_state: AppState) -> Router {
         .route("/bots/{id}", delete(bots::delete_bot))
         .route("/bots/{id}", patch(bots::rename_bot))
         .route("/bots/{id}/source", get(bots::fetch_source_code))
+        .route("/bots/{id}/tags", put(bots::set_bot_tags))
         .route("/leaderboards", post(leaderboards::create_leaderboard))
         .route("/leaderboards/{id}", patch(leaderboards::patch_leaderboard))
         .route(
             "/leaderboards/{id}",
             delete(leaderboards::delete_leaderboard),
         )
+        .route(
+            "/leaderboards/{id}/history",
+            get(leaderboards::fetch_leaderboard_history),
+        )
+        .route(
+            "/leaderboards/{id}/bootstrap",
+            get(leaderboards::fetch_leaderboard_bootstrap),
+        )
+        .route(
+            "/leaderboards/{id}/predict",
+            get(leaderboards::fetch_leaderboard_prediction),
+        )
+        .route(
+            "/leaderboards/{id}/h2h",
+            get(leaderboards::fetch_leaderboard_head_to_head),
+        )
+        .route(
+            "/leaderboards/{id}/calibration",
+            get(leaderboards::fetch_leaderboard_calibration),
+        )
+        .route("/matches", get(matches::fetch_matches))
+        .route("/matches/{id}", get(matches::fetch_match))
         .route("/status", get(fetch_status::fetch_status))
         .route("/chart", post(charts::chart))
+        .route("/chart/winrate", post(charts::winrate_buckets))
+        .route("/chart/histogram", post(charts::histogram))
+        .route("/chart/scatter", post(charts::scatter))
+        .route("/chart/correlation", post(charts::outcome_correlation))
         .route("/matchmaking", put(enable_matchmaking::enable_matchmaking))
+        .route(
+            "/matchmaking/group",
+            put(enable_matchmaking::set_matchmaking_group),
+        )
+        .route("/tournaments", post(tournaments::create_tournament))
+        .route("/tournaments", get(tournaments::fetch_tournaments))
+        .route("/tournaments/{id}", delete(tournaments::delete_tournament))
         .with_state(app_state);
 
     create_web_router()
@@ -4,6 +4,8 @@ use crate::arena_commands::FetchStatusResult;
 use crate::arena_commands::LeaderboardItem;
 use crate::arena_commands::LeaderboardOverview;
 use crate::arena_commands::LeaderboardStatus;
+use crate::config::RankingConfig;
+use crate::domain::AttributeAggregation;
 use crate::domain::BotId;
 use crate::domain::WinrateStats;
 use chrono::DateTime;
@@ -15,6 +17,7 @@ pub struct FetchStatusResponse {
     pub bots: Vec<BotOverviewResponse>,
     pub leaderboards: Vec<LeaderboardOverviewResponse>,
     pub matchmaking_enabled: bool,
+    pub matchmaking_group: Option<String>,
 }
 
 impl From<FetchStatusResult> for FetchStatusResponse {
@@ -23,6 +26,7 @@ impl From<FetchStatusResult> for FetchStatusResponse {
             bots: value.bots.into_iter().map(Into::into).collect(),
             leaderboards: value.leaderboards.into_iter().map(Into::into).collect(),
             matchmaking_enabled: value.matchmaking_enabled,
+            matchmaking_group: value.matchmaking_group.map(Into::into),
         }
     }
 }
@@ -34,8 +38,13 @@ pub struct LeaderboardOverviewResponse {
     pub filter: String,
     pub status: &'static str,
     pub error: Option<String>,
+    pub ranking: Option<RankingConfig>,
+    pub uncertainty_coefficient: f64,
+    pub columns: Vec<AttributeAggregation>,
     pub items: Vec<LeaderboardItemResponse>,
     pub winrate_stats: Vec<WinrateStatsResponse>,
+    pub seat_stats: Vec<SeatStatsResponse>,
+    pub seat_advantage: Option<f64>,
     pub total_matches: u64,
     pub example_seeds: Vec<i64>,
 }
@@ -55,8 +64,13 @@ impl From<LeaderboardOverview> for LeaderboardOverviewResponse {
                 LeaderboardStatus::Error(e) => Some(e),
                 _ => None,
             },
+            ranking: value.ranking,
+            uncertainty_coefficient: value.uncertainty_coefficient,
+            columns: value.columns,
             items: value.items.into_iter().map(Into::into).collect(),
             winrate_stats: value.winrate_stats.into_iter().map(Into::into).collect(),
+            seat_stats: value.seat_stats.into_iter().map(Into::into).collect(),
+            seat_advantage: value.seat_advantage,
             total_matches: value.total_matches,
             example_seeds: value.example_seeds,
         }
@@ -84,6 +98,27 @@ impl From<((BotId, BotId), WinrateStats)> for WinrateStatsResponse {
     }
 }
 
+#[derive(Serialize)]
+pub struct SeatStatsResponse {
+    pub seat: u8,
+    pub wins: u64,
+    pub draws: u64,
+    pub loses: u64,
+    pub winrate: Option<f64>,
+}
+
+impl From<(u8, WinrateStats)> for SeatStatsResponse {
+    fn from((seat, value): (u8, WinrateStats)) -> Self {
+        SeatStatsResponse {
+            seat,
+            wins: value.wins,
+            draws: value.draws,
+            loses: value.loses,
+            winrate: value.winrate(),
+        }
+    }
+}
+
 #[derive(Serialize)]
 pub struct LeaderboardItemResponse {
     pub id: i64,
@@ -91,6 +126,17 @@ pub struct LeaderboardItemResponse {
     pub rating: f64,
     pub rating_mu: f64,
     pub rating_sigma: f64,
+    pub rating_volatility: Option<f64>,
+    pub ratings_by_player_count: Vec<PlayerCountRatingResponse>,
+    pub seat_stats: Vec<SeatStatsResponse>,
+    pub columns: Vec<Option<f64>>,
+}
+
+#[derive(Serialize)]
+pub struct PlayerCountRatingResponse {
+    pub player_count: u8,
+    pub rating_mu: f64,
+    pub rating_sigma: f64,
 }
 
 impl From<LeaderboardItem> for LeaderboardItemResponse {
@@ -101,6 +147,18 @@ impl From<LeaderboardItem> for LeaderboardItemResponse {
             rating: item.rating_ordinal,
             rating_mu: item.rating.mu,
             rating_sigma: item.rating.sigma,
+            rating_volatility: item.rating.volatility,
+            ratings_by_player_count: item
+                .ratings_by_player_count
+                .into_iter()
+                .map(|(player_count, rating)| PlayerCountRatingResponse {
+                    player_count,
+                    rating_mu: rating.mu,
+                    rating_sigma: rating.sigma,
+                })
+                .collect(),
+            seat_stats: item.seat_stats.into_iter().map(Into::into).collect(),
+            columns: item.columns,
         }
     }
 }
@@ -113,6 +171,7 @@ pub struct BotOverviewResponse {
     pub matches_played: u64,
     pub matches_with_error: u64,
     pub builds: Vec<BuildResponse>,
+    pub tags: Vec<String>,
     pub created_at: String,
 }
 
@@ -125,6 +184,7 @@ impl From<BotOverview> for BotOverviewResponse {
             matches_played: v.matches_played,
             matches_with_error: v.matches_with_error,
             builds: v.builds.into_iter().map(|b| b.into()).collect(),
+            tags: v.tags.into_iter().map(Into::into).collect(),
             created_at: DateTime::<Local>::from(v.created_at)
                 .format("%d/%m/%Y %H:%M")
                 .to_string(),
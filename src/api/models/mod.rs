@@ -2,8 +2,10 @@ mod build_response;
 mod create_bot_request;
 mod fetch_status_response;
 mod rename_bot_request;
+mod set_bot_tags_request;
 
 pub use build_response::*;
 pub use create_bot_request::*;
 pub use fetch_status_response::*;
 pub use rename_bot_request::*;
+pub use set_bot_tags_request::*;
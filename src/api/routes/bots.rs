@@ -10,11 +10,11 @@ use serde::Serialize;
 use crate::{
     api::{
         errors::ApiError,
-        models::{BotOverviewResponse, CreateBotRequest, RenameBotRequest},
+        models::{BotOverviewResponse, CreateBotRequest, RenameBotRequest, SetBotTagsRequest},
         AppState,
     },
-    arena_commands::{BotSourceCode, CreateBotResult, RenameBotResult},
-    domain::{BotId, BotName, Language, SourceCode},
+    arena_commands::{BotSourceCode, CreateBotResult, RenameBotResult, SetBotTagsResult},
+    domain::{BotId, BotName, BotTag, Language, SourceCode},
 };
 
 pub async fn create_bot(
@@ -80,6 +80,27 @@ pub async fn rename_bot(
     }
 }
 
+pub async fn set_bot_tags(
+    State(app_state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(payload): Json<SetBotTagsRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let id: BotId = id.into();
+    let tags: Vec<BotTag> = payload
+        .tags
+        .into_iter()
+        .map(BotTag::try_from)
+        .collect::<Result<_, _>>()
+        .map_err(ApiError::ValidationFailed)?;
+
+    let res = app_state.arena_handle.set_bot_tags(id, tags).await?;
+
+    match res {
+        SetBotTagsResult::Updated => Ok(()),
+        SetBotTagsResult::NotFound => Err(ApiError::NotFound),
+    }
+}
+
 pub async fn fetch_source_code(
     State(app_state): State<AppState>,
     Path(id): Path<i64>,
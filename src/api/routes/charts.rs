@@ -4,14 +4,32 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     api::{errors::ApiError, AppState},
-    arena_commands::{ChartItem, ChartOverview, ChartTurnData},
+    arena_commands::{
+        AttributeLevel, Buckets, ChartItem, ChartOptions, ChartOverview, ChartSeries, ChartSplit,
+        ChartTurnData, HistogramBin, HistogramItem, HistogramOverview, OutcomeCorrelationItem,
+        OutcomeCorrelationOverview, OutcomeCorrelationResult, ScatterAxis, ScatterItem,
+        ScatterOptions, ScatterOverview, ScatterPoint, WinrateBucket, WinrateBucketItem,
+        WinrateBucketsOverview,
+    },
     domain::MatchFilter,
 };
 
+const DEFAULT_CHART_MATCHES: usize = 1000;
+const MAX_CHART_MATCHES: usize = 10000;
+const MAX_PERCENTILES: usize = 10;
+
 #[derive(Deserialize)]
 pub struct ChartRequest {
     pub filter: String,
     pub attribute_name: String,
+    /// "bot" (default) or "match"
+    pub level: Option<String>,
+    /// "bot" (default) or "outcome"
+    pub split_by: Option<String>,
+    pub bot: Option<i64>,
+    #[serde(default)]
+    pub percentiles: Vec<f64>,
+    pub matches: Option<usize>,
 }
 
 pub async fn chart(
@@ -25,14 +43,250 @@ pub async fn chart(
         )));
     }
 
+    let level = parse_level(payload.level.as_deref())?;
+    let split_by = parse_split(payload.split_by.as_deref())?;
+    if level == AttributeLevel::Match && split_by == ChartSplit::Outcome && payload.bot.is_none() {
+        return Err(ApiError::ValidationFailed(anyhow!(
+            "Splitting match attributes by outcome requires a bot"
+        )));
+    }
+    if payload.percentiles.len() > MAX_PERCENTILES
+        || payload
+            .percentiles
+            .iter()
+            .any(|p| !(0.0..=100.0).contains(p))
+    {
+        return Err(ApiError::ValidationFailed(anyhow!(
+            "There should be up to {} percentiles from 0 to 100",
+            MAX_PERCENTILES
+        )));
+    }
+    let max_matches = parse_max_matches(payload.matches)?;
+
+    let options = ChartOptions {
+        level,
+        split_by,
+        bot_id: payload.bot.map(Into::into),
+        percentiles: payload.percentiles,
+        max_matches,
+    };
+
     let res = app_state
         .arena_handle
-        .chart(filter, payload.attribute_name)
+        .chart(filter, payload.attribute_name, options)
         .await?;
 
     Ok(Json(ChartOverviewResponse::from(res)))
 }
 
+const DEFAULT_HISTOGRAM_BINS: usize = 20;
+const MAX_HISTOGRAM_BINS: usize = 100;
+
+#[derive(Deserialize)]
+pub struct HistogramRequest {
+    pub filter: String,
+    pub attribute_name: String,
+    pub bins: Option<usize>,
+}
+
+pub async fn histogram(
+    State(app_state): State<AppState>,
+    Json(payload): Json<HistogramRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let filter: MatchFilter = payload.filter.parse().map_err(ApiError::ValidationFailed)?;
+    if payload.attribute_name.is_empty() {
+        return Err(ApiError::ValidationFailed(anyhow!(
+            "Attribute name cannot be empty"
+        )));
+    }
+    let bins = payload.bins.unwrap_or(DEFAULT_HISTOGRAM_BINS);
+    if !(1..=MAX_HISTOGRAM_BINS).contains(&bins) {
+        return Err(ApiError::ValidationFailed(anyhow!(
+            "bins should be from 1 to {}",
+            MAX_HISTOGRAM_BINS
+        )));
+    }
+
+    let res = app_state
+        .arena_handle
+        .histogram(filter, payload.attribute_name, bins)
+        .await?;
+
+    Ok(Json(HistogramOverviewResponse::from(res)))
+}
+
+#[derive(Deserialize)]
+pub struct ScatterRequest {
+    pub filter: String,
+    pub x: String,
+    /// "bot" (default) or "match"
+    pub x_level: Option<String>,
+    pub y: String,
+    /// "bot" (default) or "match"
+    pub y_level: Option<String>,
+    /// "bot" (default) or "outcome"
+    pub color_by: Option<String>,
+    pub bot: Option<i64>,
+    pub matches: Option<usize>,
+}
+
+pub async fn scatter(
+    State(app_state): State<AppState>,
+    Json(payload): Json<ScatterRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let filter: MatchFilter = payload.filter.parse().map_err(ApiError::ValidationFailed)?;
+    if payload.x.is_empty() || payload.y.is_empty() {
+        return Err(ApiError::ValidationFailed(anyhow!(
+            "Attribute names cannot be empty"
+        )));
+    }
+    let x = ScatterAxis {
+        attribute_name: payload.x,
+        level: parse_level(payload.x_level.as_deref())?,
+    };
+    let y = ScatterAxis {
+        attribute_name: payload.y,
+        level: parse_level(payload.y_level.as_deref())?,
+    };
+
+    let options = ScatterOptions {
+        x,
+        y,
+        color_by: parse_split(payload.color_by.as_deref())?,
+        bot_id: payload.bot.map(Into::into),
+        max_matches: parse_max_matches(payload.matches)?,
+    };
+
+    let res = app_state.arena_handle.scatter(filter, options).await?;
+
+    Ok(Json(ScatterOverviewResponse::from(res)))
+}
+
+#[derive(Deserialize)]
+pub struct OutcomeCorrelationRequest {
+    pub filter: String,
+    pub bot: i64,
+}
+
+pub async fn outcome_correlation(
+    State(app_state): State<AppState>,
+    Json(payload): Json<OutcomeCorrelationRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let filter: MatchFilter = payload.filter.parse().map_err(ApiError::ValidationFailed)?;
+
+    let res = app_state
+        .arena_handle
+        .outcome_correlation(filter, payload.bot.into())
+        .await?;
+
+    match res {
+        OutcomeCorrelationResult::OK(res) => {
+            Ok(Json(OutcomeCorrelationOverviewResponse::from(res)))
+        }
+        OutcomeCorrelationResult::BotNotFound => Err(ApiError::ValidationFailed(anyhow!(
+            "Bot with id {} does not exist",
+            payload.bot
+        ))),
+    }
+}
+
+fn parse_level(level: Option<&str>) -> Result<AttributeLevel, ApiError> {
+    match level {
+        None | Some("bot") => Ok(AttributeLevel::Bot),
+        Some("match") => Ok(AttributeLevel::Match),
+        Some(other) => Err(ApiError::ValidationFailed(anyhow!(
+            "Unknown attribute level '{}'",
+            other
+        ))),
+    }
+}
+
+fn parse_split(split: Option<&str>) -> Result<ChartSplit, ApiError> {
+    match split {
+        None | Some("bot") => Ok(ChartSplit::Bot),
+        Some("outcome") => Ok(ChartSplit::Outcome),
+        Some(other) => Err(ApiError::ValidationFailed(anyhow!(
+            "Unknown chart split '{}'",
+            other
+        ))),
+    }
+}
+
+fn parse_max_matches(matches: Option<usize>) -> Result<usize, ApiError> {
+    let max_matches = matches.unwrap_or(DEFAULT_CHART_MATCHES);
+    if !(1..=MAX_CHART_MATCHES).contains(&max_matches) {
+        return Err(ApiError::ValidationFailed(anyhow!(
+            "matches should be from 1 to {}",
+            MAX_CHART_MATCHES
+        )));
+    }
+    Ok(max_matches)
+}
+
+const DEFAULT_QUANTILES: usize = 4;
+const MAX_BUCKETS: usize = 20;
+
+#[derive(Deserialize)]
+pub struct WinrateBucketsRequest {
+    pub filter: String,
+    pub attribute_name: String,
+    /// explicit bucket boundaries, strictly increasing
+    pub edges: Option<Vec<f64>>,
+    /// amount of buckets with roughly the same amount of matches
+    pub quantiles: Option<usize>,
+}
+
+pub async fn winrate_buckets(
+    State(app_state): State<AppState>,
+    Json(payload): Json<WinrateBucketsRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let filter: MatchFilter = payload.filter.parse().map_err(ApiError::ValidationFailed)?;
+    if payload.attribute_name.is_empty() {
+        return Err(ApiError::ValidationFailed(anyhow!(
+            "Attribute name cannot be empty"
+        )));
+    }
+
+    let buckets = match (payload.edges, payload.quantiles) {
+        (Some(_), Some(_)) => {
+            return Err(ApiError::ValidationFailed(anyhow!(
+                "Either edges or quantiles should be specified, not both"
+            )));
+        }
+        (Some(edges), None) => {
+            if edges.is_empty() || edges.len() >= MAX_BUCKETS {
+                return Err(ApiError::ValidationFailed(anyhow!(
+                    "There should be from 1 to {} edges",
+                    MAX_BUCKETS - 1
+                )));
+            }
+            if edges.iter().any(|e| !e.is_finite()) || edges.windows(2).any(|w| w[0] >= w[1]) {
+                return Err(ApiError::ValidationFailed(anyhow!(
+                    "Edges should be finite and strictly increasing"
+                )));
+            }
+            Buckets::Edges(edges)
+        }
+        (None, quantiles) => {
+            let quantiles = quantiles.unwrap_or(DEFAULT_QUANTILES);
+            if !(2..=MAX_BUCKETS).contains(&quantiles) {
+                return Err(ApiError::ValidationFailed(anyhow!(
+                    "Quantiles should be from 2 to {}",
+                    MAX_BUCKETS
+                )));
+            }
+            Buckets::Quantiles(quantiles)
+        }
+    };
+
+    let res = app_state
+        .arena_handle
+        .winrate_buckets(filter, payload.attribute_name, buckets)
+        .await?;
+
+    Ok(Json(WinrateBucketsOverviewResponse::from(res)))
+}
+
 #[derive(Serialize)]
 pub struct ChartOverviewResponse {
     pub items: Vec<ChartItemResponse>,
@@ -41,16 +295,108 @@ pub struct ChartOverviewResponse {
 
 #[derive(Serialize)]
 pub struct ChartItemResponse {
-    pub bot_id: i64,
+    /// `None` for the outcome series
+    pub bot_id: Option<i64>,
+    /// "won" or "lost" for the outcome series
+    pub outcome: Option<&'static str>,
     pub data: Vec<ChartTurnDataResponse>,
 }
 
 #[derive(Serialize)]
 pub struct ChartTurnDataResponse {
     pub turn: u16,
+    pub count: u64,
     pub avg: f64,
     pub min: f64,
     pub max: f64,
+    pub median: f64,
+    pub stddev: f64,
+    pub percentiles: Vec<f64>,
+}
+
+#[derive(Serialize)]
+pub struct HistogramOverviewResponse {
+    pub bins: Vec<HistogramBinResponse>,
+    pub items: Vec<HistogramItemResponse>,
+    pub total_matches: u64,
+}
+
+#[derive(Serialize)]
+pub struct HistogramBinResponse {
+    pub lower: f64,
+    pub upper: f64,
+}
+
+#[derive(Serialize)]
+pub struct HistogramItemResponse {
+    pub bot_id: i64,
+    pub counts: Vec<u64>,
+    pub avg: f64,
+}
+
+#[derive(Serialize)]
+pub struct ScatterOverviewResponse {
+    pub items: Vec<ScatterItemResponse>,
+    pub total_matches: u64,
+}
+
+#[derive(Serialize)]
+pub struct ScatterItemResponse {
+    /// `None` for the outcome series
+    pub bot_id: Option<i64>,
+    /// "won" or "lost" for the outcome series
+    pub outcome: Option<&'static str>,
+    pub points: Vec<ScatterPointResponse>,
+}
+
+#[derive(Serialize)]
+pub struct ScatterPointResponse {
+    pub match_id: i64,
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Serialize)]
+pub struct OutcomeCorrelationOverviewResponse {
+    pub items: Vec<OutcomeCorrelationItemResponse>,
+    pub total_matches: u64,
+    pub wins: u64,
+}
+
+#[derive(Serialize)]
+pub struct OutcomeCorrelationItemResponse {
+    pub attribute_name: String,
+    pub level: &'static str,
+    pub wins: u64,
+    pub loses: u64,
+    pub win_avg: f64,
+    pub lose_avg: f64,
+    pub correlation: f64,
+    pub effect_size: f64,
+}
+
+#[derive(Serialize)]
+pub struct WinrateBucketsOverviewResponse {
+    pub buckets: Vec<WinrateBucketResponse>,
+    pub total_matches: u64,
+}
+
+#[derive(Serialize)]
+pub struct WinrateBucketResponse {
+    pub lower: Option<f64>,
+    pub upper: Option<f64>,
+    pub total_matches: u64,
+    pub items: Vec<WinrateBucketItemResponse>,
+}
+
+#[derive(Serialize)]
+pub struct WinrateBucketItemResponse {
+    pub bot_id: i64,
+    pub matches: u64,
+    pub wins: u64,
+    pub draws: u64,
+    pub loses: u64,
+    pub winrate: Option<f64>,
 }
 
 impl From<ChartOverview> for ChartOverviewResponse {
@@ -64,8 +410,10 @@ impl From<ChartOverview> for ChartOverviewResponse {
 
 impl From<ChartItem> for ChartItemResponse {
     fn from(value: ChartItem) -> Self {
+        let (bot_id, outcome) = series_fields(value.series);
         ChartItemResponse {
-            bot_id: value.bot_id.into(),
+            bot_id,
+            outcome,
             data: value.data.into_iter().map(Into::into).collect(),
         }
     }
@@ -75,9 +423,141 @@ impl From<ChartTurnData> for ChartTurnDataResponse {
     fn from(value: ChartTurnData) -> Self {
         ChartTurnDataResponse {
             turn: value.turn,
+            count: value.count,
             avg: value.avg,
             min: value.min,
             max: value.max,
+            median: value.median,
+            stddev: value.stddev,
+            percentiles: value.percentiles,
+        }
+    }
+}
+
+impl From<WinrateBucketsOverview> for WinrateBucketsOverviewResponse {
+    fn from(value: WinrateBucketsOverview) -> Self {
+        WinrateBucketsOverviewResponse {
+            buckets: value.buckets.into_iter().map(Into::into).collect(),
+            total_matches: value.total_matches,
         }
     }
 }
+
+impl From<WinrateBucket> for WinrateBucketResponse {
+    fn from(value: WinrateBucket) -> Self {
+        WinrateBucketResponse {
+            lower: value.lower,
+            upper: value.upper,
+            total_matches: value.total_matches,
+            items: value.items.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<WinrateBucketItem> for WinrateBucketItemResponse {
+    fn from(value: WinrateBucketItem) -> Self {
+        WinrateBucketItemResponse {
+            bot_id: value.bot_id.into(),
+            matches: value.matches,
+            winrate: value.winrate_stats.winrate(),
+            wins: value.winrate_stats.wins,
+            draws: value.winrate_stats.draws,
+            loses: value.winrate_stats.loses,
+        }
+    }
+}
+
+impl From<HistogramOverview> for HistogramOverviewResponse {
+    fn from(value: HistogramOverview) -> Self {
+        HistogramOverviewResponse {
+            bins: value.bins.into_iter().map(Into::into).collect(),
+            items: value.items.into_iter().map(Into::into).collect(),
+            total_matches: value.total_matches,
+        }
+    }
+}
+
+impl From<HistogramBin> for HistogramBinResponse {
+    fn from(value: HistogramBin) -> Self {
+        HistogramBinResponse {
+            lower: value.lower,
+            upper: value.upper,
+        }
+    }
+}
+
+impl From<HistogramItem> for HistogramItemResponse {
+    fn from(value: HistogramItem) -> Self {
+        HistogramItemResponse {
+            bot_id: value.bot_id.into(),
+            counts: value.counts,
+            avg: value.avg,
+        }
+    }
+}
+
+impl From<ScatterOverview> for ScatterOverviewResponse {
+    fn from(value: ScatterOverview) -> Self {
+        ScatterOverviewResponse {
+            items: value.items.into_iter().map(Into::into).collect(),
+            total_matches: value.total_matches,
+        }
+    }
+}
+
+impl From<ScatterItem> for ScatterItemResponse {
+    fn from(value: ScatterItem) -> Self {
+        let (bot_id, outcome) = series_fields(value.series);
+        ScatterItemResponse {
+            bot_id,
+            outcome,
+            points: value.points.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<ScatterPoint> for ScatterPointResponse {
+    fn from(value: ScatterPoint) -> Self {
+        ScatterPointResponse {
+            match_id: value.match_id.into(),
+            x: value.x,
+            y: value.y,
+        }
+    }
+}
+
+impl From<OutcomeCorrelationOverview> for OutcomeCorrelationOverviewResponse {
+    fn from(value: OutcomeCorrelationOverview) -> Self {
+        OutcomeCorrelationOverviewResponse {
+            items: value.items.into_iter().map(Into::into).collect(),
+            total_matches: value.total_matches,
+            wins: value.wins,
+        }
+    }
+}
+
+impl From<OutcomeCorrelationItem> for OutcomeCorrelationItemResponse {
+    fn from(value: OutcomeCorrelationItem) -> Self {
+        OutcomeCorrelationItemResponse {
+            attribute_name: value.attribute_name,
+            level: match value.level {
+                AttributeLevel::Bot => "bot",
+                AttributeLevel::Match => "match",
+            },
+            wins: value.correlation.wins,
+            loses: value.correlation.loses,
+            win_avg: value.correlation.win_avg,
+            lose_avg: value.correlation.lose_avg,
+            correlation: value.correlation.correlation,
+            effect_size: value.correlation.effect_size,
+        }
+    }
+}
+
+fn series_fields(series: ChartSeries) -> (Option<i64>, Option<&'static str>) {
+    match series {
+        ChartSeries::Bot(bot_id) => (Some(bot_id.into()), None),
+        ChartSeries::Won => (None, Some("won")),
+        ChartSeries::Lost => (None, Some("lost")),
+    }
+}
@@ -2,12 +2,18 @@ use axum::{extract::State, response::IntoResponse, Json};
 use serde::Deserialize;
 
 use crate::api::{errors::ApiError, AppState};
+use crate::domain::BotTag;
 
 #[derive(Deserialize)]
 pub struct EnableMatchmakingRequest {
     pub enabled: bool,
 }
 
+#[derive(Deserialize)]
+pub struct SetMatchmakingGroupRequest {
+    pub group: Option<String>,
+}
+
 pub async fn enable_matchmaking(
     State(app_state): State<AppState>,
     Json(payload): Json<EnableMatchmakingRequest>,
@@ -18,3 +24,18 @@ pub async fn enable_matchmaking(
 
     Ok(())
 }
+
+pub async fn set_matchmaking_group(
+    State(app_state): State<AppState>,
+    Json(payload): Json<SetMatchmakingGroupRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let group: Option<BotTag> = payload
+        .group
+        .map(BotTag::try_from)
+        .transpose()
+        .map_err(ApiError::ValidationFailed)?;
+
+    app_state.arena_handle.set_matchmaking_group(group).await?;
+
+    Ok(())
+}
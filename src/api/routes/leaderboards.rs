@@ -1,26 +1,405 @@
+use std::collections::BTreeMap;
+
+use anyhow::anyhow;
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     response::IntoResponse,
     Json,
 };
-use serde::Deserialize;
+use chrono::{DateTime, Local};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     api::{errors::ApiError, models::LeaderboardOverviewResponse, AppState},
-    arena_commands::PatchLeaderboardResult,
-    domain::{LeaderboardId, LeaderboardName, MatchFilter},
+    arena_commands::{
+        BootstrapItem, BootstrapOverview, CalibrationOverview, FetchHeadToHeadResult,
+        FetchPredictionResult, LeaderboardStatus, PatchLeaderboardResult, PredictionItem,
+        PredictionOverview,
+    },
+    config::RankingConfig,
+    domain::{
+        AttributeAggregation, AttributeComparison, BotId, CalibrationBucket, HeadToHead,
+        HeadToHeadSplit, LeaderboardId, LeaderboardName, MatchFilter, RatingSnapshot,
+        WinrateInterval,
+    },
 };
 
 #[derive(Deserialize)]
 pub struct CreateLeaderboardRequest {
     pub name: String,
     pub filter: String,
+    pub ranking: Option<RankingConfig>,
+    pub uncertainty_coefficient: Option<f64>,
+    #[serde(default)]
+    pub columns: Vec<AttributeAggregation>,
 }
 
 #[derive(Deserialize)]
 pub struct PatchLeaderboardRequest {
     pub name: String,
     pub filter: String,
+    pub ranking: Option<RankingConfig>,
+    pub uncertainty_coefficient: Option<f64>,
+    #[serde(default)]
+    pub columns: Vec<AttributeAggregation>,
+}
+
+fn validate_uncertainty_coefficient(value: Option<f64>) -> Result<(), ApiError> {
+    if value.is_some_and(|v| !v.is_finite()) {
+        return Err(ApiError::ValidationFailed(anyhow!(
+            "uncertainty_coefficient should be a finite number"
+        )));
+    }
+    Ok(())
+}
+
+fn validate_columns(value: &[AttributeAggregation]) -> Result<(), ApiError> {
+    for column in value {
+        column.validate().map_err(ApiError::ValidationFailed)?;
+    }
+    Ok(())
+}
+
+fn validate_ranking(value: Option<&RankingConfig>) -> Result<(), ApiError> {
+    if let Some(ranking) = value {
+        ranking.validate().map_err(ApiError::ValidationFailed)?;
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+pub struct BotRatingHistoryResponse {
+    pub bot_id: i64,
+    pub points: Vec<RatingHistoryPointResponse>,
+}
+
+#[derive(Serialize)]
+pub struct RatingHistoryPointResponse {
+    pub total_matches: u64,
+    pub rank: usize,
+    pub rating_mu: f64,
+    pub rating_sigma: f64,
+    pub created_at: String,
+}
+
+impl From<RatingSnapshot> for RatingHistoryPointResponse {
+    fn from(value: RatingSnapshot) -> Self {
+        RatingHistoryPointResponse {
+            total_matches: value.total_matches,
+            rank: value.rank,
+            rating_mu: value.rating.mu,
+            rating_sigma: value.rating.sigma,
+            created_at: DateTime::<Local>::from(value.created_at)
+                .format("%d/%m/%Y %H:%M")
+                .to_string(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct BootstrapResponse {
+    pub status: &'static str,
+    pub error: Option<String>,
+    pub samples: usize,
+    pub total_matches: u64,
+    pub items: Vec<BootstrapItemResponse>,
+    pub stronger_probabilities: Vec<StrongerProbabilityResponse>,
+}
+
+#[derive(Serialize)]
+pub struct BootstrapItemResponse {
+    pub bot_id: i64,
+    pub rank: usize,
+    pub rank_probability: f64,
+    pub rating_mu_lower: f64,
+    pub rating_mu_upper: f64,
+}
+
+#[derive(Serialize)]
+pub struct StrongerProbabilityResponse {
+    pub bot_id: i64,
+    pub opponent_bot_id: i64,
+    pub probability: f64,
+}
+
+impl From<BootstrapOverview> for BootstrapResponse {
+    fn from(value: BootstrapOverview) -> Self {
+        BootstrapResponse {
+            status: match value.status {
+                LeaderboardStatus::Live => "live",
+                LeaderboardStatus::Computing => "computing",
+                LeaderboardStatus::Error(_) => "error",
+            },
+            error: match value.status {
+                LeaderboardStatus::Error(e) => Some(e),
+                _ => None,
+            },
+            samples: value.samples,
+            total_matches: value.total_matches,
+            items: value.items.into_iter().map(Into::into).collect(),
+            stronger_probabilities: value
+                .stronger_probabilities
+                .into_iter()
+                .map(
+                    |((bot_id, opponent_bot_id), probability)| StrongerProbabilityResponse {
+                        bot_id: bot_id.into(),
+                        opponent_bot_id: opponent_bot_id.into(),
+                        probability,
+                    },
+                )
+                .sorted_by_key(|p| (p.bot_id, p.opponent_bot_id))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct PredictionQuery {
+    /// comma separated bot ids
+    pub bots: String,
+}
+
+#[derive(Serialize)]
+pub struct PredictionResponse {
+    pub status: &'static str,
+    pub items: Vec<PredictionItemResponse>,
+    pub win_probabilities: Vec<WinProbabilityResponse>,
+}
+
+#[derive(Serialize)]
+pub struct PredictionItemResponse {
+    pub bot_id: i64,
+    pub rating_mu: f64,
+    pub rating_sigma: f64,
+    pub placement_probabilities: Vec<f64>,
+    pub expected_place: f64,
+}
+
+#[derive(Serialize)]
+pub struct WinProbabilityResponse {
+    pub bot_id: i64,
+    pub opponent_bot_id: i64,
+    pub probability: f64,
+}
+
+impl From<PredictionOverview> for PredictionResponse {
+    fn from(value: PredictionOverview) -> Self {
+        PredictionResponse {
+            status: match value.status {
+                LeaderboardStatus::Live => "live",
+                LeaderboardStatus::Computing => "computing",
+                LeaderboardStatus::Error(_) => "error",
+            },
+            items: value.items.into_iter().map(Into::into).collect(),
+            win_probabilities: value
+                .win_probabilities
+                .into_iter()
+                .map(
+                    |((bot_id, opponent_bot_id), probability)| WinProbabilityResponse {
+                        bot_id: bot_id.into(),
+                        opponent_bot_id: opponent_bot_id.into(),
+                        probability,
+                    },
+                )
+                .sorted_by_key(|p| (p.bot_id, p.opponent_bot_id))
+                .collect(),
+        }
+    }
+}
+
+impl From<PredictionItem> for PredictionItemResponse {
+    fn from(value: PredictionItem) -> Self {
+        PredictionItemResponse {
+            bot_id: value.id.into(),
+            rating_mu: value.rating.mu,
+            rating_sigma: value.rating.sigma,
+            placement_probabilities: value.placement_probabilities,
+            expected_place: value.expected_place,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct HeadToHeadQuery {
+    pub a: i64,
+    pub b: i64,
+}
+
+#[derive(Serialize)]
+pub struct HeadToHeadResponse {
+    pub total_matches: u64,
+    pub wins: u64,
+    pub draws: u64,
+    pub loses: u64,
+    #[serde(flatten)]
+    pub winrate: WinrateIntervalResponse,
+    pub by_seat: Vec<HeadToHeadSplitResponse>,
+    pub by_player_count: Vec<HeadToHeadSplitResponse>,
+    pub lost_seeds: Vec<i64>,
+    pub attributes: Vec<AttributeComparisonResponse>,
+}
+
+#[derive(Serialize)]
+pub struct WinrateIntervalResponse {
+    pub winrate: f64,
+    pub winrate_lower: f64,
+    pub winrate_upper: f64,
+}
+
+#[derive(Serialize)]
+pub struct HeadToHeadSplitResponse {
+    /// seat index or player count
+    pub key: u8,
+    pub wins: u64,
+    pub draws: u64,
+    pub loses: u64,
+    #[serde(flatten)]
+    pub winrate: WinrateIntervalResponse,
+}
+
+#[derive(Serialize)]
+pub struct AttributeComparisonResponse {
+    pub name: String,
+    pub matches: u64,
+    pub a_avg: f64,
+    pub b_avg: f64,
+    pub a_higher: u64,
+}
+
+impl From<HeadToHead> for HeadToHeadResponse {
+    fn from(value: HeadToHead) -> Self {
+        let splits = |splits: BTreeMap<u8, HeadToHeadSplit>| {
+            splits
+                .into_iter()
+                .map(|(key, split)| HeadToHeadSplitResponse {
+                    key,
+                    wins: split.winrate_stats.wins,
+                    draws: split.winrate_stats.draws,
+                    loses: split.winrate_stats.loses,
+                    winrate: split.winrate.into(),
+                })
+                .collect()
+        };
+        HeadToHeadResponse {
+            total_matches: value.total_matches,
+            wins: value.winrate_stats.wins,
+            draws: value.winrate_stats.draws,
+            loses: value.winrate_stats.loses,
+            winrate: value.winrate.into(),
+            by_seat: splits(value.by_seat),
+            by_player_count: splits(value.by_player_count),
+            lost_seeds: value.lost_seeds,
+            attributes: value.attributes.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<WinrateInterval> for WinrateIntervalResponse {
+    fn from(value: WinrateInterval) -> Self {
+        WinrateIntervalResponse {
+            winrate: value.winrate,
+            winrate_lower: value.lower,
+            winrate_upper: value.upper,
+        }
+    }
+}
+
+impl From<AttributeComparison> for AttributeComparisonResponse {
+    fn from(value: AttributeComparison) -> Self {
+        AttributeComparisonResponse {
+            name: value.name,
+            matches: value.matches,
+            a_avg: value.a_avg,
+            b_avg: value.b_avg,
+            a_higher: value.a_higher,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct CalibrationResponse {
+    pub status: &'static str,
+    pub error: Option<String>,
+    pub total_matches: u64,
+    pub pairs: u64,
+    pub brier_score: Option<f64>,
+    pub log_loss: Option<f64>,
+    pub buckets: Vec<CalibrationBucketResponse>,
+}
+
+#[derive(Serialize)]
+pub struct CalibrationBucketResponse {
+    pub predicted: f64,
+    pub actual: f64,
+    pub pairs: u64,
+}
+
+impl From<CalibrationOverview> for CalibrationResponse {
+    fn from(value: CalibrationOverview) -> Self {
+        let calibration = value.calibration.as_deref();
+        // scores are meaningless until something was predicted
+        let predicted = calibration.filter(|c| c.pairs > 0);
+        CalibrationResponse {
+            status: match value.status {
+                LeaderboardStatus::Live => "live",
+                LeaderboardStatus::Computing => "computing",
+                LeaderboardStatus::Error(_) => "error",
+            },
+            error: match value.status {
+                LeaderboardStatus::Error(e) => Some(e),
+                _ => None,
+            },
+            total_matches: calibration.map_or(0, |c| c.total_matches),
+            pairs: calibration.map_or(0, |c| c.pairs),
+            brier_score: predicted.map(|c| c.brier_score),
+            log_loss: predicted.map(|c| c.log_loss),
+            buckets: calibration
+                .map(|c| c.buckets.iter().map(Into::into).collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl From<&CalibrationBucket> for CalibrationBucketResponse {
+    fn from(value: &CalibrationBucket) -> Self {
+        CalibrationBucketResponse {
+            predicted: value.predicted,
+            actual: value.actual,
+            pairs: value.pairs,
+        }
+    }
+}
+
+fn parse_bot_ids(value: &str) -> Result<Vec<BotId>, ApiError> {
+    let bot_ids = value
+        .split(',')
+        .map(|id| id.trim().parse::<i64>().map(BotId::from))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| ApiError::ValidationFailed(anyhow!("bots should be comma separated ids")))?;
+    if !(2..=8).contains(&bot_ids.len()) {
+        return Err(ApiError::ValidationFailed(anyhow!(
+            "A match should have from 2 to 8 bots"
+        )));
+    }
+    if !bot_ids.iter().all_unique() {
+        return Err(ApiError::ValidationFailed(anyhow!(
+            "bots should be distinct"
+        )));
+    }
+    Ok(bot_ids)
+}
+
+impl From<BootstrapItem> for BootstrapItemResponse {
+    fn from(value: BootstrapItem) -> Self {
+        BootstrapItemResponse {
+            bot_id: value.id.into(),
+            rank: value.rank,
+            rank_probability: value.rank_probability,
+            rating_mu_lower: value.mu_lower,
+            rating_mu_upper: value.mu_upper,
+        }
+    }
 }
 
 pub async fn create_leaderboard(
@@ -32,10 +411,19 @@ pub async fn create_leaderboard(
         .try_into()
         .map_err(ApiError::ValidationFailed)?;
     let filter: MatchFilter = payload.filter.parse().map_err(ApiError::ValidationFailed)?;
+    validate_uncertainty_coefficient(payload.uncertainty_coefficient)?;
+    validate_ranking(payload.ranking.as_ref())?;
+    validate_columns(&payload.columns)?;
 
     let res = app_state
         .arena_handle
-        .create_leaderboard(name, filter)
+        .create_leaderboard(
+            name,
+            filter,
+            payload.ranking,
+            payload.uncertainty_coefficient,
+            payload.columns,
+        )
         .await?;
 
     Ok(Json(LeaderboardOverviewResponse::from(res)))
@@ -52,10 +440,20 @@ pub async fn patch_leaderboard(
         .try_into()
         .map_err(ApiError::ValidationFailed)?;
     let filter: MatchFilter = payload.filter.parse().map_err(ApiError::ValidationFailed)?;
+    validate_uncertainty_coefficient(payload.uncertainty_coefficient)?;
+    validate_ranking(payload.ranking.as_ref())?;
+    validate_columns(&payload.columns)?;
 
     let res = app_state
         .arena_handle
-        .patch_leaderboard(id, name, filter)
+        .patch_leaderboard(
+            id,
+            name,
+            filter,
+            payload.ranking,
+            payload.uncertainty_coefficient,
+            payload.columns,
+        )
         .await?;
 
     match res {
@@ -72,3 +470,110 @@ pub async fn delete_leaderboard(
     app_state.arena_handle.delete_leaderboard(id).await?;
     Ok(())
 }
+
+/// id 0 stands for the global leaderboard
+fn parse_leaderboard_id(id: i64) -> LeaderboardId {
+    match id {
+        0 => LeaderboardId::UNINITIALIZED,
+        id => id.into(),
+    }
+}
+
+pub async fn fetch_leaderboard_history(
+    State(app_state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, ApiError> {
+    let id = parse_leaderboard_id(id);
+
+    let Some(snapshots) = app_state.arena_handle.fetch_rating_history(id).await? else {
+        return Err(ApiError::NotFound);
+    };
+
+    let res = snapshots
+        .into_iter()
+        .into_group_map_by(|s| s.bot_id)
+        .into_iter()
+        .map(|(bot_id, snapshots)| BotRatingHistoryResponse {
+            bot_id: bot_id.into(),
+            points: snapshots.into_iter().map(Into::into).collect(),
+        })
+        .sorted_by_key(|h| h.bot_id)
+        .collect_vec();
+
+    Ok(Json(res))
+}
+
+/// Starts the bootstrap computation in background if results are missing or outdated
+pub async fn fetch_leaderboard_bootstrap(
+    State(app_state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, ApiError> {
+    let id = parse_leaderboard_id(id);
+
+    let Some(res) = app_state.arena_handle.fetch_bootstrap(id).await? else {
+        return Err(ApiError::NotFound);
+    };
+
+    Ok(Json(BootstrapResponse::from(res)))
+}
+
+/// Predicts the outcome of a hypothetical match using the leaderboard ratings
+pub async fn fetch_leaderboard_prediction(
+    State(app_state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(query): Query<PredictionQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let id = parse_leaderboard_id(id);
+    let bot_ids = parse_bot_ids(&query.bots)?;
+
+    match app_state.arena_handle.fetch_prediction(id, bot_ids).await? {
+        FetchPredictionResult::OK(res) => Ok(Json(PredictionResponse::from(res))),
+        FetchPredictionResult::LeaderboardNotFound => Err(ApiError::NotFound),
+        FetchPredictionResult::BotNotFound(id) => Err(ApiError::ValidationFailed(anyhow!(
+            "Bot with id {} does not exist",
+            i64::from(id)
+        ))),
+    }
+}
+
+/// Compares two bots over the leaderboard matches both of them played in
+pub async fn fetch_leaderboard_head_to_head(
+    State(app_state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(query): Query<HeadToHeadQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let id = parse_leaderboard_id(id);
+    if query.a == query.b {
+        return Err(ApiError::ValidationFailed(anyhow!(
+            "bots should be distinct"
+        )));
+    }
+
+    let res = app_state
+        .arena_handle
+        .fetch_head_to_head(id, query.a.into(), query.b.into())
+        .await?;
+
+    match res {
+        FetchHeadToHeadResult::OK(res) => Ok(Json(HeadToHeadResponse::from(res))),
+        FetchHeadToHeadResult::LeaderboardNotFound => Err(ApiError::NotFound),
+        FetchHeadToHeadResult::BotNotFound(id) => Err(ApiError::ValidationFailed(anyhow!(
+            "Bot with id {} does not exist",
+            i64::from(id)
+        ))),
+    }
+}
+
+/// Starts the calibration computation in background if results are missing or outdated
+pub async fn fetch_leaderboard_calibration(
+    State(app_state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, ApiError> {
+    let id = parse_leaderboard_id(id);
+
+    let Some(res) = app_state.arena_handle.fetch_calibration(id).await? else {
+        return Err(ApiError::NotFound);
+    };
+
+    Ok(Json(CalibrationResponse::from(res)))
+}
@@ -0,0 +1,271 @@
+use anyhow::anyhow;
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Local};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::{errors::ApiError, AppState},
+    arena_commands::{MatchDetails, MatchesPage},
+    domain::{Match, MatchAttribute, MatchAttributeValue, MatchFilter, Participant},
+};
+
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 500;
+
+#[derive(Deserialize)]
+pub struct MatchesQuery {
+    pub filter: Option<String>,
+    /// only the matches this bot played in
+    pub bot: Option<i64>,
+    pub limit: Option<usize>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct MatchesPageResponse {
+    pub items: Vec<MatchResponse>,
+    pub next_cursor: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct MatchResponse {
+    pub id: i64,
+    pub seed: i64,
+    pub participants: Vec<ParticipantResponse>,
+    pub attributes: Vec<MatchAttributeResponse>,
+    pub created_at: String,
+}
+
+#[derive(Serialize)]
+pub struct ParticipantResponse {
+    pub bot_id: i64,
+    pub rank: u8,
+    pub error: bool,
+}
+
+#[derive(Serialize)]
+pub struct MatchAttributeResponse {
+    pub name: String,
+    pub value: MatchAttributeValueResponse,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum MatchAttributeValueResponse {
+    Integer(i64),
+    Float(f64),
+    String(String),
+}
+
+#[derive(Serialize)]
+pub struct MatchDetailsResponse {
+    pub id: i64,
+    pub seed: i64,
+    /// in seat order
+    pub participants: Vec<ParticipantDetailsResponse>,
+    pub match_attributes: AttributeGroupResponse,
+    /// in seat order
+    pub bot_attributes: Vec<BotAttributeGroupResponse>,
+    pub created_at: String,
+}
+
+#[derive(Serialize)]
+pub struct ParticipantDetailsResponse {
+    pub index: usize,
+    pub bot_id: i64,
+    /// `None` if the bot is not known to the arena
+    pub bot_name: Option<String>,
+    pub rank: u8,
+    pub error: bool,
+}
+
+#[derive(Serialize)]
+pub struct AttributeGroupResponse {
+    /// the ones without a turn
+    pub attributes: Vec<MatchAttributeResponse>,
+    pub turns: Vec<TurnAttributesResponse>,
+}
+
+#[derive(Serialize)]
+pub struct BotAttributeGroupResponse {
+    pub bot_id: i64,
+    #[serde(flatten)]
+    pub group: AttributeGroupResponse,
+}
+
+#[derive(Serialize)]
+pub struct TurnAttributesResponse {
+    pub turn: u16,
+    pub attributes: Vec<MatchAttributeResponse>,
+}
+
+impl From<MatchesPage> for MatchesPageResponse {
+    fn from(value: MatchesPage) -> Self {
+        MatchesPageResponse {
+            items: value.matches.into_iter().map(Into::into).collect(),
+            next_cursor: value.next_cursor.map(Into::into),
+        }
+    }
+}
+
+impl From<Match> for MatchResponse {
+    fn from(value: Match) -> Self {
+        MatchResponse {
+            id: value.id.into(),
+            seed: value.seed,
+            participants: value.participants.into_iter().map(Into::into).collect(),
+            // match-level only, bot and turn attributes are too many
+            attributes: value
+                .attributes
+                .into_iter()
+                .filter(|a| a.bot_id.is_none() && a.turn.is_none())
+                .map(|a| MatchAttributeResponse {
+                    name: a.name,
+                    value: a.value.into(),
+                })
+                .collect(),
+            created_at: DateTime::<Local>::from(value.created_at)
+                .format("%d/%m/%Y %H:%M")
+                .to_string(),
+        }
+    }
+}
+
+impl From<MatchDetails> for MatchDetailsResponse {
+    fn from(value: MatchDetails) -> Self {
+        let MatchDetails { m, bot_names } = value;
+        let mut by_bot = m.attributes.into_iter().into_group_map_by(|a| a.bot_id);
+        let bot_attributes = m
+            .participants
+            .iter()
+            .map(|p| p.bot_id)
+            .unique()
+            .map(|bot_id| BotAttributeGroupResponse {
+                bot_id: bot_id.into(),
+                group: by_bot.remove(&Some(bot_id)).unwrap_or_default().into(),
+            })
+            .collect();
+
+        MatchDetailsResponse {
+            id: m.id.into(),
+            seed: m.seed,
+            participants: m
+                .participants
+                .into_iter()
+                .zip(bot_names)
+                .enumerate()
+                .map(|(index, (p, bot_name))| ParticipantDetailsResponse {
+                    index,
+                    bot_id: p.bot_id.into(),
+                    bot_name: bot_name.map(Into::into),
+                    rank: p.rank,
+                    error: p.error,
+                })
+                .collect(),
+            match_attributes: by_bot.remove(&None).unwrap_or_default().into(),
+            bot_attributes,
+            created_at: DateTime::<Local>::from(m.created_at)
+                .format("%d/%m/%Y %H:%M")
+                .to_string(),
+        }
+    }
+}
+
+impl From<Vec<MatchAttribute>> for AttributeGroupResponse {
+    fn from(value: Vec<MatchAttribute>) -> Self {
+        let mut by_turn = value.into_iter().into_group_map_by(|a| a.turn);
+        let attributes = by_turn.remove(&None).unwrap_or_default();
+        AttributeGroupResponse {
+            attributes: attributes_response(attributes),
+            turns: by_turn
+                .into_iter()
+                .filter_map(|(turn, attributes)| Some((turn?, attributes)))
+                .sorted_by_key(|(turn, _)| *turn)
+                .map(|(turn, attributes)| TurnAttributesResponse {
+                    turn,
+                    attributes: attributes_response(attributes),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Sorted by name
+fn attributes_response(attributes: Vec<MatchAttribute>) -> Vec<MatchAttributeResponse> {
+    attributes
+        .into_iter()
+        .sorted_by(|a, b| a.name.cmp(&b.name))
+        .map(|a| MatchAttributeResponse {
+            name: a.name,
+            value: a.value.into(),
+        })
+        .collect()
+}
+
+impl From<Participant> for ParticipantResponse {
+    fn from(value: Participant) -> Self {
+        ParticipantResponse {
+            bot_id: value.bot_id.into(),
+            rank: value.rank,
+            error: value.error,
+        }
+    }
+}
+
+impl From<MatchAttributeValue> for MatchAttributeValueResponse {
+    fn from(value: MatchAttributeValue) -> Self {
+        match value {
+            MatchAttributeValue::Integer(v) => MatchAttributeValueResponse::Integer(v),
+            MatchAttributeValue::Float(v) => MatchAttributeValueResponse::Float(v),
+            MatchAttributeValue::String(v) => MatchAttributeValueResponse::String(v),
+        }
+    }
+}
+
+/// Newest matches first
+pub async fn fetch_matches(
+    State(app_state): State<AppState>,
+    Query(query): Query<MatchesQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let filter: MatchFilter = query
+        .filter
+        .unwrap_or_default()
+        .parse()
+        .map_err(ApiError::ValidationFailed)?;
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(ApiError::ValidationFailed(anyhow!(
+            "limit should be from 1 to {MAX_LIMIT}"
+        )));
+    }
+
+    let res = app_state
+        .arena_handle
+        .fetch_matches(
+            filter,
+            query.bot.map(Into::into),
+            limit,
+            query.cursor.map(Into::into),
+        )
+        .await?;
+
+    Ok(Json(MatchesPageResponse::from(res)))
+}
+
+pub async fn fetch_match(
+    State(app_state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, ApiError> {
+    let res = app_state.arena_handle.fetch_match(id.into()).await?;
+
+    match res {
+        Some(details) => Ok(Json(MatchDetailsResponse::from(details))),
+        None => Err(ApiError::NotFound),
+    }
+}
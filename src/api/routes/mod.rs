@@ -3,3 +3,5 @@ pub mod charts;
 pub mod enable_matchmaking;
 pub mod fetch_status;
 pub mod leaderboards;
+pub mod matches;
+pub mod tournaments;
@@ -0,0 +1,174 @@
+use anyhow::anyhow;
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Local};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::{errors::ApiError, AppState},
+    arena_commands::{CreateTournamentResult, TournamentOverview},
+    domain::{BotId, TournamentFormat, TournamentId, TournamentName, TournamentStanding},
+};
+
+#[derive(Deserialize)]
+pub struct CreateTournamentRequest {
+    pub name: String,
+    pub format: String,
+    pub rounds: Option<u32>,
+    pub games_per_pair: Option<u32>,
+    pub bot_ids: Vec<i64>,
+}
+
+#[derive(Serialize)]
+pub struct TournamentOverviewResponse {
+    pub id: i64,
+    pub name: String,
+    pub format: &'static str,
+    pub rounds: u32,
+    pub games_per_pair: u32,
+    pub finished: bool,
+    pub current_round: u32,
+    pub total_games: usize,
+    pub finished_games: usize,
+    pub standings: Vec<TournamentStandingResponse>,
+    pub created_at: String,
+}
+
+#[derive(Serialize)]
+pub struct TournamentStandingResponse {
+    pub bot_id: i64,
+    pub points: f64,
+    pub buchholz: f64,
+    pub wins: u64,
+    pub draws: u64,
+    pub loses: u64,
+    pub byes: u64,
+}
+
+impl From<TournamentOverview> for TournamentOverviewResponse {
+    fn from(value: TournamentOverview) -> Self {
+        TournamentOverviewResponse {
+            id: value.id.into(),
+            name: value.name.into(),
+            format: match value.format {
+                TournamentFormat::RoundRobin => "round_robin",
+                TournamentFormat::Swiss { .. } => "swiss",
+            },
+            rounds: match value.format {
+                TournamentFormat::RoundRobin => 1,
+                TournamentFormat::Swiss { rounds } => rounds,
+            },
+            games_per_pair: value.games_per_pair,
+            finished: value.finished,
+            current_round: value.current_round,
+            total_games: value.total_games,
+            finished_games: value.finished_games,
+            standings: value.standings.into_iter().map(Into::into).collect(),
+            created_at: DateTime::<Local>::from(value.created_at)
+                .format("%d/%m/%Y %H:%M")
+                .to_string(),
+        }
+    }
+}
+
+impl From<TournamentStanding> for TournamentStandingResponse {
+    fn from(value: TournamentStanding) -> Self {
+        TournamentStandingResponse {
+            bot_id: value.bot_id.into(),
+            points: value.points,
+            buchholz: value.buchholz,
+            wins: value.wins,
+            draws: value.draws,
+            loses: value.loses,
+            byes: value.byes,
+        }
+    }
+}
+
+pub async fn create_tournament(
+    State(app_state): State<AppState>,
+    Json(payload): Json<CreateTournamentRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let name: TournamentName = payload
+        .name
+        .try_into()
+        .map_err(ApiError::ValidationFailed)?;
+
+    let format = match (payload.format.as_str(), payload.rounds) {
+        ("round_robin", _) => TournamentFormat::RoundRobin,
+        ("swiss", Some(rounds)) if rounds > 0 => TournamentFormat::Swiss { rounds },
+        ("swiss", _) => {
+            return Err(ApiError::ValidationFailed(anyhow!(
+                "Swiss tournament requires positive number of rounds"
+            )))
+        }
+        (other, _) => {
+            return Err(ApiError::ValidationFailed(anyhow!(
+                "Unknown tournament format '{}'",
+                other
+            )))
+        }
+    };
+
+    let games_per_pair = payload.games_per_pair.unwrap_or(1);
+    if games_per_pair == 0 {
+        return Err(ApiError::ValidationFailed(anyhow!(
+            "games_per_pair should be positive"
+        )));
+    }
+
+    let bot_ids: Vec<BotId> = payload
+        .bot_ids
+        .into_iter()
+        .unique()
+        .map(BotId::from)
+        .collect();
+    if bot_ids.len() < 2 {
+        return Err(ApiError::ValidationFailed(anyhow!(
+            "Tournament requires at least 2 distinct bots"
+        )));
+    }
+
+    let res = app_state
+        .arena_handle
+        .create_tournament(name, format, games_per_pair, bot_ids)
+        .await?;
+
+    match res {
+        CreateTournamentResult::Created(overview) => {
+            Ok(Json(TournamentOverviewResponse::from(overview)))
+        }
+        CreateTournamentResult::BotNotFound(id) => Err(ApiError::ValidationFailed(anyhow!(
+            "Bot with id {} does not exist",
+            i64::from(id)
+        ))),
+        CreateTournamentResult::UnsupportedPlayerCount => Err(ApiError::ValidationFailed(anyhow!(
+            "Tournaments are only supported for games allowing 2 players"
+        ))),
+    }
+}
+
+pub async fn fetch_tournaments(
+    State(app_state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let res = app_state.arena_handle.fetch_tournaments().await?;
+    let res: Vec<TournamentOverviewResponse> = res.into_iter().map(Into::into).collect();
+    Ok(Json(res))
+}
+
+pub async fn delete_tournament(
+    State(app_state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, ApiError> {
+    // ids start from 1, while 0 would hit the uninitialized id assertion
+    if id == i64::from(TournamentId::UNINITIALIZED) {
+        return Err(ApiError::NotFound);
+    }
+    let id: TournamentId = id.into();
+    app_state.arena_handle.delete_tournament(id).await?;
+    Ok(())
+}
@@ -5,11 +5,12 @@ use crate::domain::*;
 use crate::matchmaking;
 use crate::ranking::Ranker;
 use crate::worker::{BuildBotInput, PlayMatchBot, PlayMatchInput, PlayMatchOutput, WorkerHandle};
-use crate::{chart, db};
+use crate::{chart, db, head_to_head_query, match_browser};
 use anyhow::{bail, Context};
+use chrono::Utc;
 use itertools::Itertools;
 use sqlx::SqlitePool;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc::error::{TryRecvError, TrySendError};
@@ -18,6 +19,7 @@ use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
 use tracing::{error, instrument, warn};
 
+#[allow(clippy::too_many_arguments)]
 pub async fn run(
     game_config: GameConfig,
     matchmaking_config: MatchmakingConfig,
@@ -33,6 +35,8 @@ pub async fn run(
         .await
         .context("Cannot run db migrations")?;
 
+    let ranking_settings =
+        serde_json::to_value(&ranking_config).context("Cannot serialize ranking config")?;
     let ranker = Ranker::new(ranking_config);
     if game_config.max_players > 2 && !ranker.support_multi_team() {
         bail!("Configured ranking algorithm only supports 2 player games");
@@ -51,6 +55,10 @@ pub async fn run(
         .load_from_db()
         .await
         .context("Cannot load initial data from db")?;
+    arena
+        .restore_rating_history(ranking_settings)
+        .await
+        .context("Cannot restore rating history from db")?;
     arena.reset_stale_builds().await;
     arena.recalculate_computed_full();
 
@@ -92,6 +100,10 @@ struct Arena {
     game_config: GameConfig,
     matchmaking_config: MatchmakingConfig,
     uncertainty_coefficient: f64,
+    history_every_matches: Option<u64>,
+    history_interval: Option<Duration>,
+    bootstrap_samples: usize,
+    refit_interval: Duration,
     pool: SqlitePool,
     bots: Vec<Bot>,
     builds: Vec<Build>,
@@ -103,8 +115,21 @@ struct Arena {
     scheduled_matches_total: HashMap<BotId, u64>,
     scheduled_matches_vs: HashMap<(BotId, BotId), u64>,
     matchmaking_enabled: bool,
+    matchmaking_group: Option<BotTag>,
+    tournaments: Vec<Tournament>,
+    scheduled_tournament_games: HashSet<TournamentGameId>,
 }
 
+// hardcoded for now
+const MATCH_QUEUE_SIZE_THRESHOLD: usize = 20;
+
+// used when neither matches nor time based rating history is configured
+const DEFAULT_HISTORY_EVERY_MATCHES: u64 = 100;
+
+const DEFAULT_BOOTSTRAP_SAMPLES: usize = 100;
+const DEFAULT_REFIT_INTERVAL_MS: u64 = 1000;
+const PREDICTION_SAMPLES: usize = 10000;
+
 impl Arena {
     fn new(
         game_config: GameConfig,
@@ -118,18 +143,46 @@ impl Arena {
         Self {
             game_config,
             uncertainty_coefficient: leaderboards_config.uncertainty_coefficient.unwrap_or(3.0),
+            history_every_matches: leaderboards_config.history_every_matches.or(
+                leaderboards_config
+                    .history_interval_minutes
+                    .is_none()
+                    .then_some(DEFAULT_HISTORY_EVERY_MATCHES),
+            ),
+            bootstrap_samples: leaderboards_config
+                .bootstrap_samples
+                .unwrap_or(DEFAULT_BOOTSTRAP_SAMPLES),
+            refit_interval: Duration::from_millis(
+                leaderboards_config
+                    .refit_interval_ms
+                    .unwrap_or(DEFAULT_REFIT_INTERVAL_MS),
+            ),
+            history_interval: leaderboards_config
+                .history_interval_minutes
+                .map(|minutes| Duration::from_secs(minutes * 60)),
             matchmaking_enabled: matchmaking_config.enabled_on_start.unwrap_or(true),
+            matchmaking_group: matchmaking_config
+                .group
+                .clone()
+                .and_then(|group| group.try_into().ok()),
             matchmaking_config,
             pool: pool.clone(),
             worker_handle,
             ranker: Arc::clone(&ranker),
             bots: Default::default(),
             builds: Default::default(),
-            global_leaderboard: AsyncLeaderboard::new(Leaderboard::global(), ranker, pool),
+            global_leaderboard: AsyncLeaderboard::new(
+                Leaderboard::global(leaderboards_config.columns.unwrap_or_default()),
+                ranker,
+                pool,
+                Default::default(),
+            ),
             custom_leaderboards: Default::default(),
             scheduled_matches_total: Default::default(),
             scheduled_matches_vs: Default::default(),
             match_queue: Default::default(),
+            tournaments: Default::default(),
+            scheduled_tournament_games: Default::default(),
         }
     }
 
@@ -141,18 +194,40 @@ impl Arena {
         self.builds = db::fetch_builds(&self.pool)
             .await
             .context("Cannot fetch builds")?;
+        let bot_tags = self.bot_tags_snapshot();
+        self.global_leaderboard.set_bot_tags(Arc::clone(&bot_tags));
         self.custom_leaderboards = db::fetch_leaderboards(&self.pool)
             .await
             .context("Cannot fetch leaderboards")?
             .into_iter()
-            .map(|lb| AsyncLeaderboard::new(lb, Arc::clone(&self.ranker), self.pool.clone()))
+            .map(|lb| {
+                let ranker = self.ranker_for(&lb);
+                AsyncLeaderboard::new(lb, ranker, self.pool.clone(), Arc::clone(&bot_tags))
+            })
             .collect();
+        self.tournaments = db::fetch_tournaments(&self.pool)
+            .await
+            .context("Cannot fetch tournaments")?;
+        for tournament in self.tournaments.iter_mut().filter(|t| !t.finished) {
+            let deleted = tournament
+                .bot_ids
+                .iter()
+                .copied()
+                .filter(|&id| !self.bots.iter().any(|b| b.id == id))
+                .collect_vec();
+            for id in deleted {
+                Self::withdraw_from_tournament(&self.pool, tournament, id).await;
+            }
+        }
         Ok(())
     }
 
     pub async fn do_chores(&mut self) -> anyhow::Result<()> {
         self.run_builds().await;
 
+        self.advance_tournaments().await;
+        self.schedule_tournament_games();
+
         if self.matchmaking_enabled {
             self.perform_matchmaking()?;
         }
@@ -163,6 +238,47 @@ impl Arena {
 
         self.let_leaderboards_catchup_with_live_matches();
 
+        self.take_rating_snapshots().await;
+
+        Ok(())
+    }
+
+    /// Wipes the rating history recorded with another arena-wide config
+    /// (the leaderboards which override it keep theirs) and continues the rest
+    async fn restore_rating_history(&mut self, ranking: serde_json::Value) -> anyhow::Result<()> {
+        // nothing to compare with on the first run, the history is kept
+        if let Some((old_ranking, old_coefficient)) =
+            db::fetch_global_rating_settings(&self.pool).await?
+        {
+            let ranking_changed = serde_json::from_str::<serde_json::Value>(&old_ranking).ok()
+                != Some(ranking.clone());
+            let coefficient_changed = old_coefficient != self.uncertainty_coefficient;
+
+            let outdated = std::iter::once(&self.global_leaderboard)
+                .chain(&self.custom_leaderboards)
+                .filter(|lb| {
+                    (ranking_changed && lb.leaderboard.ranking.is_none())
+                        || (coefficient_changed && lb.leaderboard.uncertainty_coefficient.is_none())
+                })
+                .map(|lb| lb.leaderboard.id)
+                .collect_vec();
+            for id in outdated {
+                db::delete_rating_snapshots(&self.pool, id).await?;
+            }
+        }
+        db::persist_global_rating_settings(
+            &self.pool,
+            &ranking.to_string(),
+            self.uncertainty_coefficient,
+        )
+        .await?;
+
+        for (id, total_matches, taken_at) in db::fetch_last_rating_snapshots(&self.pool).await? {
+            std::iter::once(&mut self.global_leaderboard)
+                .chain(&mut self.custom_leaderboards)
+                .filter(|lb| lb.leaderboard.id == id)
+                .for_each(|lb| lb.restore_rating_snapshot(total_matches, taken_at));
+        }
         Ok(())
     }
 
@@ -262,6 +378,37 @@ impl Arena {
         self.matchmaking_enabled = enabled;
     }
 
+    fn cmd_set_matchmaking_group(&mut self, group: Option<BotTag>) {
+        self.matchmaking_group = group;
+    }
+
+    async fn cmd_set_bot_tags(&mut self, id: BotId, tags: Vec<BotTag>) -> SetBotTagsResult {
+        let Some(bot) = self.bots.iter_mut().find(|b| b.id == id) else {
+            return SetBotTagsResult::NotFound;
+        };
+
+        bot.tags = tags.into_iter().sorted().dedup().collect();
+        db::persist_bot_tags(&self.pool, bot)
+            .await
+            .expect("Cannot persist bot tags to DB");
+
+        let bot_tags = self.bot_tags_snapshot();
+        self.global_leaderboard.set_bot_tags(Arc::clone(&bot_tags));
+        for lb in &mut self.custom_leaderboards {
+            lb.set_bot_tags(Arc::clone(&bot_tags));
+        }
+        SetBotTagsResult::Updated
+    }
+
+    fn bot_tags_snapshot(&self) -> Arc<BotTags> {
+        let bot_tags = self
+            .bots
+            .iter()
+            .map(|bot| (bot.id, bot.tags.clone()))
+            .collect();
+        Arc::new(bot_tags)
+    }
+
     async fn cmd_create_bot(
         &mut self,
         name: BotName,
@@ -275,7 +422,8 @@ impl Arena {
         db::persist_bot(&self.pool, &mut bot)
             .await
             .expect("Cannot persist bot to DB");
-        let bot_overview = self.render_bot_overview(&bot);
+        let stats = self.global_leaderboard.stats();
+        let bot_overview = self.render_bot_overview(&bot, stats.as_ref());
         self.bots.push(bot);
         CreateBotResult::Created(bot_overview)
     }
@@ -305,14 +453,31 @@ impl Arena {
             .expect("Cannot delete bot from DB");
         self.bots.retain(|bot| bot.id != id);
         self.builds.retain(|b| b.bot_id != id);
+        for tournament in self.tournaments.iter_mut().filter(|t| !t.finished) {
+            Self::withdraw_from_tournament(&self.pool, tournament, id).await;
+        }
         self.recalculate_computed_full();
     }
 
+    async fn withdraw_from_tournament(pool: &SqlitePool, tournament: &mut Tournament, id: BotId) {
+        for game_id in tournament.withdraw(id) {
+            let game = tournament
+                .games
+                .iter_mut()
+                .find(|g| g.id == game_id)
+                .unwrap();
+            db::persist_tournament_game(pool, tournament.id, game)
+                .await
+                .expect("Cannot persist tournament game to DB");
+        }
+    }
+
     async fn cmd_fetch_status(&mut self) -> FetchStatusResult {
+        let stats = self.global_leaderboard.stats();
         let bots = self
             .bots
             .iter()
-            .map(|bot| self.render_bot_overview(bot))
+            .map(|bot| self.render_bot_overview(bot, stats.as_ref()))
             .collect_vec();
 
         let leaderboards =
@@ -325,27 +490,23 @@ impl Arena {
                 .collect_vec();
 
         let matchmaking_enabled = self.matchmaking_enabled;
+        let matchmaking_group = self.matchmaking_group.clone();
 
         FetchStatusResult {
             bots,
             leaderboards,
             matchmaking_enabled,
+            matchmaking_group,
         }
     }
 
-    fn render_bot_overview(&self, bot: &Bot) -> BotOverview {
+    fn render_bot_overview(&self, bot: &Bot, stats: Option<&ComputedStats>) -> BotOverview {
         BotOverview {
             id: bot.id,
             name: bot.name.clone(),
             language: bot.language.clone(),
-            matches_played: self
-                .global_leaderboard
-                .stats()
-                .map(|s| s.matches_played(bot.id))
-                .unwrap_or_default(),
-            matches_with_error: self
-                .global_leaderboard
-                .stats()
+            matches_played: stats.map(|s| s.matches_played(bot.id)).unwrap_or_default(),
+            matches_with_error: stats
                 .map(|s| s.matches_with_error(bot.id))
                 .unwrap_or_default(),
             builds: self
@@ -354,6 +515,7 @@ impl Arena {
                 .filter(|b| b.bot_id == bot.id)
                 .cloned()
                 .collect(),
+            tags: bot.tags.clone(),
             created_at: bot.created_at,
         }
     }
@@ -370,8 +532,13 @@ impl Arena {
                     .error()
                     .map(LeaderboardStatus::Error)
                     .unwrap_or(LeaderboardStatus::Computing),
+                ranking: leaderboard.ranking.clone(),
+                uncertainty_coefficient: self.uncertainty_coefficient(async_lb),
+                columns: leaderboard.columns.clone(),
                 items: Default::default(),
                 winrate_stats: Default::default(),
+                seat_stats: Default::default(),
+                seat_advantage: None,
                 total_matches: 0,
                 example_seeds: vec![],
             };
@@ -381,12 +548,19 @@ impl Arena {
             .bots
             .iter()
             .map(|bot| {
-                let rating = self.rating(&stats, bot.id);
+                let rating = self.rating(async_lb, &stats, bot.id);
                 LeaderboardItem {
                     id: bot.id,
-                    rank: self.rank(&stats, bot.id),
+                    rank: self.rank(async_lb, &stats, bot.id),
                     rating,
-                    rating_ordinal: rating.score(self.uncertainty_coefficient),
+                    rating_ordinal: rating.score(self.uncertainty_coefficient(async_lb)),
+                    ratings_by_player_count: stats.ratings_by_player_count(bot.id),
+                    seat_stats: stats.seat_stats(bot.id),
+                    columns: leaderboard
+                        .columns
+                        .iter()
+                        .map(|column| stats.aggregate_attribute(bot.id, column))
+                        .collect(),
                 }
             })
             .sorted_by_key(|item| item.rank)
@@ -399,8 +573,13 @@ impl Arena {
             name: leaderboard.name.clone(),
             filter: leaderboard.filter.to_string(),
             status: LeaderboardStatus::Live,
+            ranking: leaderboard.ranking.clone(),
+            uncertainty_coefficient: self.uncertainty_coefficient(async_lb),
+            columns: leaderboard.columns.clone(),
             items,
             winrate_stats,
+            seat_stats: stats.seat_totals(),
+            seat_advantage: stats.seat_advantage(),
             total_matches: stats.total_matches(),
             example_seeds: stats.example_seeds().to_vec(),
         }
@@ -410,13 +589,23 @@ impl Arena {
         &mut self,
         name: LeaderboardName,
         filter: MatchFilter,
+        ranking: Option<RankingConfig>,
+        uncertainty_coefficient: Option<f64>,
+        columns: Vec<AttributeAggregation>,
     ) -> LeaderboardOverview {
-        let mut leaderboard = Leaderboard::new(name, filter);
+        let mut leaderboard =
+            Leaderboard::new(name, filter, ranking, uncertainty_coefficient, columns);
         db::persist_leaderboard(&self.pool, &mut leaderboard)
             .await
             .expect("Cannot persist leaderboard to DB");
 
-        let lb = AsyncLeaderboard::new(leaderboard, Arc::clone(&self.ranker), self.pool.clone());
+        let ranker = self.ranker_for(&leaderboard);
+        let lb = AsyncLeaderboard::new(
+            leaderboard,
+            ranker,
+            self.pool.clone(),
+            self.bot_tags_snapshot(),
+        );
         lb.recalculate();
         let overview = self.render_leaderboard_overview(&lb);
         self.custom_leaderboards.push(lb);
@@ -428,29 +617,50 @@ impl Arena {
         id: LeaderboardId,
         name: LeaderboardName,
         filter: MatchFilter,
+        ranking: Option<RankingConfig>,
+        uncertainty_coefficient: Option<f64>,
+        columns: Vec<AttributeAggregation>,
     ) -> PatchLeaderboardResult {
-        let Some(async_lb) = self
+        let Some(idx) = self
             .custom_leaderboards
-            .iter_mut()
-            .find(|w| w.leaderboard.id == id)
+            .iter()
+            .position(|w| w.leaderboard.id == id)
         else {
             return PatchLeaderboardResult::NotFound;
         };
 
-        let leaderboard = &mut async_lb.leaderboard;
-
-        let old_filter_str = leaderboard.filter.to_string();
-        let new_filter_str = filter.to_string();
+        let old = &self.custom_leaderboards[idx].leaderboard;
+        let filter_changed = old.filter.to_string() != filter.to_string();
+        let ranking_changed =
+            serde_json::to_value(&old.ranking).ok() != serde_json::to_value(&ranking).ok();
+        let coefficient_changed = old.uncertainty_coefficient != uncertainty_coefficient;
+        let columns_changed = old.columns != columns;
 
+        let async_lb = &mut self.custom_leaderboards[idx];
+        let leaderboard = &mut async_lb.leaderboard;
         leaderboard.name = name;
-        leaderboard.filter = filter.clone();
+        leaderboard.filter = filter;
+        leaderboard.ranking = ranking;
+        leaderboard.uncertainty_coefficient = uncertainty_coefficient;
+        leaderboard.columns = columns;
 
         db::persist_leaderboard(&self.pool, leaderboard)
             .await
             .expect("Cannot persist leaderboard to DB");
 
-        if old_filter_str != new_filter_str {
-            async_lb.recalculate();
+        if filter_changed || ranking_changed || coefficient_changed {
+            // rating history of the old settings is meaningless
+            db::delete_rating_snapshots(&self.pool, id)
+                .await
+                .expect("Cannot delete rating snapshots from DB");
+            async_lb.forget_rating_snapshots();
+        }
+
+        if ranking_changed {
+            let ranker = self.ranker_for(&self.custom_leaderboards[idx].leaderboard);
+            self.custom_leaderboards[idx].set_ranker(ranker);
+        } else if filter_changed || columns_changed {
+            self.custom_leaderboards[idx].recalculate();
         }
 
         PatchLeaderboardResult::OK
@@ -463,20 +673,324 @@ impl Arena {
         self.custom_leaderboards.retain(|w| w.leaderboard.id != id);
     }
 
-    fn rating(&self, stats: &ComputedStats, id: BotId) -> Rating {
+    async fn cmd_fetch_rating_history(&self, id: LeaderboardId) -> Option<Vec<RatingSnapshot>> {
+        let exists = std::iter::once(&self.global_leaderboard)
+            .chain(&self.custom_leaderboards)
+            .any(|lb| lb.leaderboard.id == id);
+        if !exists {
+            return None;
+        }
+
+        let snapshots = db::fetch_rating_snapshots(&self.pool, id)
+            .await
+            .expect("Cannot fetch rating snapshots from DB");
+        Some(snapshots)
+    }
+
+    async fn cmd_fetch_match(&self, id: MatchId) -> Option<MatchDetails> {
+        let m = db::fetch_match(&self.pool, id)
+            .await
+            .expect("Cannot fetch match from DB")?;
+        let bot_names = m
+            .participants
+            .iter()
+            .map(|p| {
+                self.bots
+                    .iter()
+                    .find(|b| b.id == p.bot_id)
+                    .map(|b| b.name.clone())
+            })
+            .collect();
+        Some(MatchDetails { m, bot_names })
+    }
+
+    fn cmd_fetch_bootstrap(&self, id: LeaderboardId) -> Option<BootstrapOverview> {
+        let async_lb = std::iter::once(&self.global_leaderboard)
+            .chain(&self.custom_leaderboards)
+            .find(|lb| lb.leaderboard.id == id)?;
+
+        let empty = |status| BootstrapOverview {
+            status,
+            samples: 0,
+            total_matches: 0,
+            items: vec![],
+            stronger_probabilities: Default::default(),
+        };
+
+        let Some(stats) = async_lb.stats() else {
+            return Some(empty(LeaderboardStatus::Computing));
+        };
+
+        let progress = async_lb.bootstrap(
+            self.bootstrap_samples,
+            self.uncertainty_coefficient(async_lb),
+        );
+        let status = match (progress.error, progress.computing) {
+            (Some(e), _) => LeaderboardStatus::Error(e),
+            (None, true) => LeaderboardStatus::Computing,
+            (None, false) => LeaderboardStatus::Live,
+        };
+
+        let Some(bootstrap) = progress.stats else {
+            return Some(empty(status));
+        };
+
+        // ranked among the resampled bots only, so that the rank matches `rank_probabilities`
+        let uncertainty_coefficient = self.uncertainty_coefficient(async_lb);
+        let scores = self
+            .bots
+            .iter()
+            .filter(|bot| bootstrap.bots.contains_key(&bot.id))
+            .map(|bot| {
+                let score = self
+                    .rating(async_lb, &stats, bot.id)
+                    .score(uncertainty_coefficient);
+                (bot.id, score)
+            })
+            .collect_vec();
+        let items = scores
+            .iter()
+            .filter_map(|&(id, score)| {
+                let bot_stats = bootstrap.bots.get(&id)?;
+                let rank = scores.iter().filter(|(_, s)| *s > score).count();
+                Some(BootstrapItem {
+                    id,
+                    rank,
+                    rank_probability: bot_stats
+                        .rank_probabilities
+                        .get(rank)
+                        .copied()
+                        .unwrap_or(0.0),
+                    mu_lower: bot_stats.mu_lower,
+                    mu_upper: bot_stats.mu_upper,
+                })
+            })
+            .sorted_by_key(|item| item.rank)
+            .collect_vec();
+
+        Some(BootstrapOverview {
+            status,
+            samples: bootstrap.samples,
+            total_matches: bootstrap.total_matches,
+            items,
+            stronger_probabilities: bootstrap.stronger_probabilities.clone(),
+        })
+    }
+
+    fn cmd_fetch_prediction(&self, cmd: FetchPredictionCommand) {
+        let FetchPredictionCommand {
+            id,
+            bot_ids,
+            response,
+        } = cmd;
+        let Some(async_lb) = std::iter::once(&self.global_leaderboard)
+            .chain(&self.custom_leaderboards)
+            .find(|lb| lb.leaderboard.id == id)
+        else {
+            let _ = response.send(FetchPredictionResult::LeaderboardNotFound);
+            return;
+        };
+        if let Some(&missing) = bot_ids
+            .iter()
+            .find(|&&bot_id| !self.bots.iter().any(|b| b.id == bot_id))
+        {
+            let _ = response.send(FetchPredictionResult::BotNotFound(missing));
+            return;
+        }
+
+        let Some(stats) = async_lb.stats() else {
+            let _ = response.send(FetchPredictionResult::OK(PredictionOverview {
+                status: LeaderboardStatus::Computing,
+                items: vec![],
+                win_probabilities: HashMap::new(),
+            }));
+            return;
+        };
+
+        let ranker = async_lb.ranker();
+        let player_count = bot_ids.len() as u8;
+        let ratings = bot_ids
+            .iter()
+            .map(|&bot_id| {
+                // ratings specific to the player count are more accurate when kept
+                stats
+                    .ratings_by_player_count(bot_id)
+                    .get(&player_count)
+                    .copied()
+                    .unwrap_or_else(|| self.rating(async_lb, &stats, bot_id))
+            })
+            .collect_vec();
+
+        let noise = ranker.performance_noise();
+
+        // placement probabilities are sampled, too slow for the arena loop
+        tokio::task::spawn_blocking(move || {
+            let placement_probabilities =
+                noise.placement_probabilities(&ratings, PREDICTION_SAMPLES, &mut rand::rng());
+
+            let items = bot_ids
+                .iter()
+                .zip(&ratings)
+                .zip(placement_probabilities)
+                .map(
+                    |((&bot_id, &rating), placement_probabilities)| PredictionItem {
+                        id: bot_id,
+                        rating,
+                        expected_place: placement_probabilities
+                            .iter()
+                            .enumerate()
+                            // places are 1-based like everywhere else
+                            .map(|(place, p)| (place + 1) as f64 * p)
+                            .sum(),
+                        placement_probabilities,
+                    },
+                )
+                .collect_vec();
+
+            let win_probabilities = bot_ids
+                .iter()
+                .zip(&ratings)
+                .cartesian_product(bot_ids.iter().zip(&ratings))
+                .filter(|((a, _), (b, _))| a != b)
+                .map(|((&a, ra), (&b, rb))| ((a, b), noise.win_probability(ra, rb)))
+                .collect();
+
+            let _ = response.send(FetchPredictionResult::OK(PredictionOverview {
+                status: LeaderboardStatus::Live,
+                items,
+                win_probabilities,
+            }));
+        });
+    }
+
+    fn cmd_fetch_calibration(&self, id: LeaderboardId) -> Option<CalibrationOverview> {
+        let async_lb = std::iter::once(&self.global_leaderboard)
+            .chain(&self.custom_leaderboards)
+            .find(|lb| lb.leaderboard.id == id)?;
+
+        if async_lb.stats().is_none() {
+            return Some(CalibrationOverview {
+                status: LeaderboardStatus::Computing,
+                calibration: None,
+            });
+        }
+
+        let progress = async_lb.calibration();
+        let status = match (progress.error, progress.computing) {
+            (Some(e), _) => LeaderboardStatus::Error(e),
+            (None, true) => LeaderboardStatus::Computing,
+            (None, false) => LeaderboardStatus::Live,
+        };
+
+        Some(CalibrationOverview {
+            status,
+            calibration: progress.stats,
+        })
+    }
+
+    fn cmd_fetch_head_to_head(&self, cmd: FetchHeadToHeadCommand) {
+        let FetchHeadToHeadCommand { id, a, b, response } = cmd;
+        let Some(async_lb) = std::iter::once(&self.global_leaderboard)
+            .chain(&self.custom_leaderboards)
+            .find(|lb| lb.leaderboard.id == id)
+        else {
+            let _ = response.send(FetchHeadToHeadResult::LeaderboardNotFound);
+            return;
+        };
+        if let Some(&missing) = [a, b]
+            .iter()
+            .find(|&&bot_id| !self.bots.iter().any(|b| b.id == bot_id))
+        {
+            let _ = response.send(FetchHeadToHeadResult::BotNotFound(missing));
+            return;
+        }
+
+        let filter = async_lb.leaderboard.filter.clone();
+        let error_policy = async_lb.ranker().error_policy();
+        let pool = self.pool.clone();
+        let bot_tags = self.bot_tags_snapshot();
+
+        tokio::spawn(async move {
+            let res = head_to_head_query::compare(filter, a, b, error_policy, bot_tags, pool).await;
+            match res {
+                Ok(h2h) => {
+                    let _ = response.send(FetchHeadToHeadResult::OK(h2h));
+                }
+                Err(e) => {
+                    error!("Failed to compare bots: {}", e);
+                }
+            };
+        });
+    }
+
+    #[instrument(skip(self), level = "debug")]
+    async fn take_rating_snapshots(&mut self) {
+        let now = Utc::now();
+        let due = std::iter::once(&self.global_leaderboard)
+            .chain(&self.custom_leaderboards)
+            .filter(|lb| lb.rating_snapshot_due(self.history_every_matches, self.history_interval))
+            .filter_map(|lb| {
+                let stats = lb.stats()?;
+                let snapshots = self
+                    .bots
+                    .iter()
+                    .filter(|bot| stats.rating(bot.id).is_some())
+                    .map(|bot| RatingSnapshot {
+                        bot_id: bot.id,
+                        rating: self.rating(lb, &stats, bot.id),
+                        rank: self.rank(lb, &stats, bot.id),
+                        total_matches: stats.fitted_matches(),
+                        created_at: now,
+                    })
+                    .collect_vec();
+                Some((lb.leaderboard.id, stats.fitted_matches(), snapshots))
+            })
+            .collect_vec();
+
+        for (id, total_matches, snapshots) in due {
+            db::persist_rating_snapshots(&self.pool, id, &snapshots)
+                .await
+                .expect("Cannot persist rating snapshots to DB");
+
+            std::iter::once(&mut self.global_leaderboard)
+                .chain(&mut self.custom_leaderboards)
+                .filter(|lb| lb.leaderboard.id == id)
+                .for_each(|lb| lb.mark_rating_snapshot_taken(total_matches));
+        }
+    }
+
+    /// Leaderboard specific ranker or the arena-wide one
+    fn ranker_for(&self, leaderboard: &Leaderboard) -> Arc<Ranker> {
+        match &leaderboard.ranking {
+            Some(config) => Arc::new(Ranker::new(config.clone())),
+            None => Arc::clone(&self.ranker),
+        }
+    }
+
+    fn uncertainty_coefficient(&self, async_lb: &AsyncLeaderboard) -> f64 {
+        async_lb
+            .leaderboard
+            .uncertainty_coefficient
+            .unwrap_or(self.uncertainty_coefficient)
+    }
+
+    fn rating(&self, async_lb: &AsyncLeaderboard, stats: &ComputedStats, id: BotId) -> Rating {
         stats
             .rating(id)
-            .unwrap_or_else(|| self.ranker.default_rating())
+            .unwrap_or_else(|| async_lb.ranker().default_rating())
     }
 
-    fn rank(&self, stats: &ComputedStats, id: BotId) -> usize {
-        let my_rating = self.rating(stats, id);
+    fn rank(&self, async_lb: &AsyncLeaderboard, stats: &ComputedStats, id: BotId) -> usize {
+        let uncertainty_coefficient = self.uncertainty_coefficient(async_lb);
+        let my_rating = self.rating(async_lb, stats, id);
         let stronger_bots_cnt = self
             .bots
             .iter()
             .filter(|b| {
-                my_rating.score(self.uncertainty_coefficient)
-                    < self.rating(stats, b.id).score(self.uncertainty_coefficient)
+                my_rating.score(uncertainty_coefficient)
+                    < self
+                        .rating(async_lb, stats, b.id)
+                        .score(uncertainty_coefficient)
             })
             .count();
         stronger_bots_cnt
@@ -486,12 +1000,14 @@ impl Arena {
         let ChartCommand {
             filter,
             attribute_name,
+            options,
             response,
         } = cmd;
         let pool = self.pool.clone();
+        let bot_tags = self.bot_tags_snapshot();
 
         tokio::spawn(async move {
-            let res = chart::visualize(filter, attribute_name, pool).await;
+            let res = chart::visualize(filter, attribute_name, options, bot_tags, pool).await;
             match res {
                 Ok(overview) => {
                     let _ = response.send(overview);
@@ -503,6 +1019,183 @@ impl Arena {
         });
     }
 
+    fn cmd_winrate_buckets(&self, cmd: WinrateBucketsCommand) {
+        let WinrateBucketsCommand {
+            filter,
+            attribute_name,
+            buckets,
+            response,
+        } = cmd;
+        let pool = self.pool.clone();
+        let bot_tags = self.bot_tags_snapshot();
+
+        tokio::spawn(async move {
+            let res =
+                chart::winrate_by_buckets(filter, attribute_name, buckets, bot_tags, pool).await;
+            match res {
+                Ok(overview) => {
+                    let _ = response.send(overview);
+                }
+                Err(e) => {
+                    error!("Failed to compute winrate by buckets: {}", e);
+                }
+            };
+        });
+    }
+
+    fn cmd_histogram(&self, cmd: HistogramCommand) {
+        let HistogramCommand {
+            filter,
+            attribute_name,
+            bins,
+            response,
+        } = cmd;
+        let pool = self.pool.clone();
+        let bot_tags = self.bot_tags_snapshot();
+
+        tokio::spawn(async move {
+            let res = chart::histogram(filter, attribute_name, bins, bot_tags, pool).await;
+            match res {
+                Ok(overview) => {
+                    let _ = response.send(overview);
+                }
+                Err(e) => {
+                    error!("Failed to build histogram: {}", e);
+                }
+            };
+        });
+    }
+
+    fn cmd_scatter(&self, cmd: ScatterCommand) {
+        let ScatterCommand {
+            filter,
+            options,
+            response,
+        } = cmd;
+        let pool = self.pool.clone();
+        let bot_tags = self.bot_tags_snapshot();
+
+        tokio::spawn(async move {
+            let res = chart::scatter(filter, options, bot_tags, pool).await;
+            match res {
+                Ok(overview) => {
+                    let _ = response.send(overview);
+                }
+                Err(e) => {
+                    error!("Failed to build scatter: {}", e);
+                }
+            };
+        });
+    }
+
+    fn cmd_outcome_correlation(&self, cmd: OutcomeCorrelationCommand) {
+        let OutcomeCorrelationCommand {
+            filter,
+            bot_id,
+            response,
+        } = cmd;
+        if !self.bots.iter().any(|b| b.id == bot_id) {
+            let _ = response.send(OutcomeCorrelationResult::BotNotFound);
+            return;
+        }
+        let pool = self.pool.clone();
+        let bot_tags = self.bot_tags_snapshot();
+
+        tokio::spawn(async move {
+            let res = chart::outcome_correlation(filter, bot_id, bot_tags, pool).await;
+            match res {
+                Ok(overview) => {
+                    let _ = response.send(OutcomeCorrelationResult::OK(overview));
+                }
+                Err(e) => {
+                    error!("Failed to correlate attributes with outcome: {}", e);
+                }
+            };
+        });
+    }
+
+    fn cmd_fetch_matches(&self, cmd: FetchMatchesCommand) {
+        let FetchMatchesCommand {
+            filter,
+            bot_id,
+            limit,
+            cursor,
+            response,
+        } = cmd;
+        let pool = self.pool.clone();
+        let bot_tags = self.bot_tags_snapshot();
+
+        tokio::spawn(async move {
+            let res = match_browser::browse(filter, bot_id, limit, cursor, bot_tags, pool).await;
+            match res {
+                Ok(page) => {
+                    let _ = response.send(page);
+                }
+                Err(e) => {
+                    error!("Failed to fetch matches: {}", e);
+                }
+            };
+        });
+    }
+
+    async fn cmd_create_tournament(
+        &mut self,
+        name: TournamentName,
+        format: TournamentFormat,
+        games_per_pair: u32,
+        bot_ids: Vec<BotId>,
+    ) -> CreateTournamentResult {
+        // tournaments are played as a series of 1v1 games
+        if self.game_config.min_players > 2 || self.game_config.max_players < 2 {
+            return CreateTournamentResult::UnsupportedPlayerCount;
+        }
+        if let Some(&id) = bot_ids
+            .iter()
+            .find(|&&id| self.bots.iter().all(|b| b.id != id))
+        {
+            return CreateTournamentResult::BotNotFound(id);
+        }
+
+        let mut tournament = Tournament::new(name, format, games_per_pair, bot_ids);
+        db::persist_tournament(&self.pool, &mut tournament)
+            .await
+            .expect("Cannot persist tournament to DB");
+        Self::advance_tournament(&self.pool, self.game_config.symmetric, &mut tournament).await;
+
+        let overview = Self::render_tournament_overview(&tournament);
+        self.tournaments.push(tournament);
+        CreateTournamentResult::Created(overview)
+    }
+
+    fn cmd_fetch_tournaments(&self) -> Vec<TournamentOverview> {
+        self.tournaments
+            .iter()
+            .map(Self::render_tournament_overview)
+            .collect()
+    }
+
+    async fn cmd_delete_tournament(&mut self, id: TournamentId) {
+        db::delete_tournament(&self.pool, id)
+            .await
+            .expect("Cannot delete tournament from DB");
+        self.tournaments.retain(|t| t.id != id);
+    }
+
+    fn render_tournament_overview(tournament: &Tournament) -> TournamentOverview {
+        TournamentOverview {
+            id: tournament.id,
+            name: tournament.name.clone(),
+            format: tournament.format,
+            games_per_pair: tournament.games_per_pair,
+            finished: tournament.finished,
+            current_round: tournament.current_round(),
+            total_games: tournament.games.len(),
+            finished_games: tournament.finished_games(),
+            standings: tournament.standings(),
+            created_at: tournament.created_at,
+        }
+    }
+
     pub async fn handle_command(&mut self, command: ArenaCommand) {
         match command {
             ArenaCommand::CreateBot(command) => {
@@ -533,7 +1226,13 @@ impl Arena {
             }
             ArenaCommand::CreateLeaderboard(command) => {
                 let res = self
-                    .cmd_create_leaderboard(command.name, command.filter)
+                    .cmd_create_leaderboard(
+                        command.name,
+                        command.filter,
+                        command.ranking,
+                        command.uncertainty_coefficient,
+                        command.columns,
+                    )
                     .await;
                 if command.response.send(res).is_err() {
                     warn!("Failed to send response to client");
@@ -547,16 +1246,72 @@ impl Arena {
             }
             ArenaCommand::PatchLeaderboard(command) => {
                 let res = self
-                    .cmd_patch_leaderboard(command.id, command.name, command.filter)
+                    .cmd_patch_leaderboard(
+                        command.id,
+                        command.name,
+                        command.filter,
+                        command.ranking,
+                        command.uncertainty_coefficient,
+                        command.columns,
+                    )
                     .await;
                 if command.response.send(res).is_err() {
                     warn!("Failed to send response to client");
                 }
             }
+            ArenaCommand::FetchRatingHistory(command) => {
+                let res = self.cmd_fetch_rating_history(command.id).await;
+                if command.response.send(res).is_err() {
+                    warn!("Failed to send response to client");
+                }
+            }
+            ArenaCommand::FetchMatch(command) => {
+                let res = self.cmd_fetch_match(command.id).await;
+                if command.response.send(res).is_err() {
+                    warn!("Failed to send response to client");
+                }
+            }
+            ArenaCommand::FetchBootstrap(command) => {
+                let res = self.cmd_fetch_bootstrap(command.id);
+                if command.response.send(res).is_err() {
+                    warn!("Failed to send response to client");
+                }
+            }
+            ArenaCommand::FetchPrediction(command) => {
+                // runs in background like the chart
+                self.cmd_fetch_prediction(command);
+            }
+            ArenaCommand::FetchCalibration(command) => {
+                let res = self.cmd_fetch_calibration(command.id);
+                if command.response.send(res).is_err() {
+                    warn!("Failed to send response to client");
+                }
+            }
+            ArenaCommand::FetchHeadToHead(command) => {
+                // runs in background like the chart
+                self.cmd_fetch_head_to_head(command);
+            }
             ArenaCommand::Chart(chart_command) => {
                 // this one is a bit special
                 self.cmd_chart(chart_command);
             }
+            // the attribute analytics run in background like the chart
+            ArenaCommand::WinrateBuckets(command) => {
+                self.cmd_winrate_buckets(command);
+            }
+            ArenaCommand::Histogram(command) => {
+                self.cmd_histogram(command);
+            }
+            ArenaCommand::Scatter(command) => {
+                self.cmd_scatter(command);
+            }
+            ArenaCommand::OutcomeCorrelation(command) => {
+                self.cmd_outcome_correlation(command);
+            }
+            ArenaCommand::FetchMatches(command) => {
+                // runs in background like the chart
+                self.cmd_fetch_matches(command);
+            }
             ArenaCommand::FetchBotSourceCode(command) => {
                 let res = self.cmd_fetch_bot_source_code(command.id).await;
                 if command.response.send(res).is_err() {
@@ -569,15 +1324,118 @@ impl Arena {
                     warn!("Failed to send response to client");
                 }
             }
+            ArenaCommand::SetMatchmakingGroup(command) => {
+                self.cmd_set_matchmaking_group(command.group);
+                if command.response.send(()).is_err() {
+                    warn!("Failed to send response to client");
+                }
+            }
+            ArenaCommand::SetBotTags(command) => {
+                let res = self.cmd_set_bot_tags(command.id, command.tags).await;
+                if command.response.send(res).is_err() {
+                    warn!("Failed to send response to client");
+                }
+            }
+            ArenaCommand::CreateTournament(command) => {
+                let res = self
+                    .cmd_create_tournament(
+                        command.name,
+                        command.format,
+                        command.games_per_pair,
+                        command.bot_ids,
+                    )
+                    .await;
+                if command.response.send(res).is_err() {
+                    warn!("Failed to send response to client");
+                }
+            }
+            ArenaCommand::FetchTournaments(command) => {
+                let res = self.cmd_fetch_tournaments();
+                if command.response.send(res).is_err() {
+                    warn!("Failed to send response to client");
+                }
+            }
+            ArenaCommand::DeleteTournament(command) => {
+                let res = self.cmd_delete_tournament(command.id).await;
+                if command.response.send(res).is_err() {
+                    warn!("Failed to send response to client");
+                }
+            }
         }
     }
 
     #[instrument(skip(self), level = "debug")]
-    pub fn perform_matchmaking(&mut self) -> anyhow::Result<()> {
-        // hardcoded for now
-        let mm_match_queue_size_threshold: usize = 20;
+    pub async fn advance_tournaments(&mut self) {
+        for tournament in self.tournaments.iter_mut().filter(|t| !t.finished) {
+            Self::advance_tournament(&self.pool, self.game_config.symmetric, tournament).await;
+        }
+    }
 
-        while self.match_queue.len() < mm_match_queue_size_threshold {
+    /// Plans the next round once the current one is over, or finishes the tournament.
+    async fn advance_tournament(pool: &SqlitePool, symmetric: bool, tournament: &mut Tournament) {
+        if !tournament.is_current_round_finished() {
+            return;
+        }
+
+        if tournament.current_round() >= tournament.total_rounds() {
+            tournament.finished = true;
+            db::persist_tournament(pool, tournament)
+                .await
+                .expect("Cannot persist tournament to DB");
+            return;
+        }
+
+        let mut games = tournament.plan_next_round(symmetric);
+        for game in &mut games {
+            db::persist_tournament_game(pool, tournament.id, game)
+                .await
+                .expect("Cannot persist tournament game to DB");
+        }
+        tournament.games.extend(games);
+    }
+
+    /// Tournament games have priority over regular matchmaking
+    #[instrument(skip(self), level = "debug")]
+    pub fn schedule_tournament_games(&mut self) {
+        let mut new_matches = vec![];
+        for tournament in self.tournaments.iter().filter(|t| !t.finished) {
+            for game in &tournament.games {
+                if self.match_queue.len() + new_matches.len() >= MATCH_QUEUE_SIZE_THRESHOLD {
+                    break;
+                }
+                if game.is_finished() || self.scheduled_tournament_games.contains(&game.id) {
+                    continue;
+                }
+                if !game
+                    .bot_ids
+                    .iter()
+                    .all(|&id| self.is_bot_ready_for_playing(id))
+                {
+                    continue;
+                }
+                new_matches.push(PlayMatchInput {
+                    bots: game
+                        .bot_ids
+                        .iter()
+                        .map(|&id| self.play_match_bot(id))
+                        .collect_vec(),
+                    seed: game.seed,
+                    tournament_game_id: Some(game.id),
+                });
+            }
+        }
+
+        for m in &new_matches {
+            self.scheduled_tournament_games
+                .extend(m.tournament_game_id.iter().copied());
+            self.record_scheduled_match(m);
+        }
+        self.match_queue.extend(new_matches);
+    }
+
+    #[instrument(skip(self), level = "debug")]
+    pub fn perform_matchmaking(&mut self) -> anyhow::Result<()> {
+        while self.match_queue.len() < MATCH_QUEUE_SIZE_THRESHOLD {
             let new_matches = self.schedule_match();
             if new_matches.is_empty() {
                 break;
@@ -611,9 +1469,10 @@ impl Arena {
 
     #[instrument(skip(self), level = "debug")]
     pub fn let_leaderboards_catchup_with_live_matches(&mut self) {
-        self.global_leaderboard.catch_up_with_live_matches();
+        self.global_leaderboard
+            .catch_up_with_live_matches(self.refit_interval);
         for async_lb in &mut self.custom_leaderboards {
-            async_lb.catch_up_with_live_matches();
+            async_lb.catch_up_with_live_matches(self.refit_interval);
         }
     }
 
@@ -621,6 +1480,9 @@ impl Arena {
     pub async fn process_finished_matches(&mut self) {
         while let Ok(output) = self.worker_handle.match_result_rx.try_recv() {
             self.forget_scheduled_match_result(&output);
+            if let Some(game_id) = output.tournament_game_id {
+                self.scheduled_tournament_games.remove(&game_id);
+            }
 
             // validation
             if output
@@ -686,6 +1548,11 @@ impl Arena {
                 .await
                 .expect("Cannot persist match to DB");
 
+            if let Some(game_id) = output.tournament_game_id {
+                self.record_tournament_game_result(game_id, &new_match)
+                    .await;
+            }
+
             let m = Arc::new(new_match);
 
             self.global_leaderboard.record_for_later(Arc::clone(&m));
@@ -695,6 +1562,34 @@ impl Arena {
         }
     }
 
+    async fn record_tournament_game_result(&mut self, game_id: TournamentGameId, m: &Match) {
+        // the tournament could have been deleted while the game was running
+        let Some(tournament) = self
+            .tournaments
+            .iter_mut()
+            .find(|t| t.games.iter().any(|g| g.id == game_id))
+        else {
+            return;
+        };
+
+        let ranks = m.participants.iter().map(|p| p.rank).collect_vec();
+        if !tournament.record_result(game_id, ranks) {
+            warn!(
+                "Match participants differ from the tournament game, skipping. {:?}",
+                game_id
+            );
+            return;
+        }
+        let game = tournament
+            .games
+            .iter_mut()
+            .find(|g| g.id == game_id)
+            .unwrap();
+        db::persist_tournament_game(&self.pool, tournament.id, game)
+            .await
+            .expect("Cannot persist tournament game to DB");
+    }
+
     fn is_bot_ready_for_playing(&self, id: BotId) -> bool {
         for worker_name in std::iter::once(WorkerName::embedded()) {
             let ready = self
@@ -719,6 +1614,11 @@ impl Arena {
         let ready_bot_ids = self
             .bots
             .iter()
+            .filter(|b| {
+                self.matchmaking_group
+                    .as_ref()
+                    .is_none_or(|group| b.has_tag(group))
+            })
             .map(|b| b.id)
             .filter(|id| self.is_bot_ready_for_playing(*id))
             .collect_vec();
@@ -727,7 +1627,10 @@ impl Arena {
             .iter()
             .map(|&id| matchmaking::Candidate {
                 id,
-                rating: self.rating(&stats, id).score(self.uncertainty_coefficient),
+                rating: self
+                    .rating(&self.global_leaderboard, &stats, id)
+                    .score(self.uncertainty_coefficient),
+                created_at: self.bots.iter().find(|b| b.id == id).unwrap().created_at,
                 matches_total: {
                     let played = stats.matches_played(id);
                     let queued = self.scheduled_matches_total.get(&id).copied().unwrap_or(0);
@@ -758,22 +1661,27 @@ impl Arena {
                 bots: m
                     .bot_ids
                     .into_iter()
-                    .map(|id| PlayMatchBot {
-                        bot_id: id,
-                        language: self
-                            .bots
-                            .iter()
-                            .find(|b| b.id == id)
-                            .unwrap()
-                            .language
-                            .clone(),
-                    })
+                    .map(|id| self.play_match_bot(id))
                     .collect_vec(),
                 seed: m.seed,
+                tournament_game_id: None,
             })
             .collect_vec()
     }
 
+    fn play_match_bot(&self, id: BotId) -> PlayMatchBot {
+        PlayMatchBot {
+            bot_id: id,
+            language: self
+                .bots
+                .iter()
+                .find(|b| b.id == id)
+                .unwrap()
+                .language
+                .clone(),
+        }
+    }
+
     fn record_scheduled_match(&mut self, input: &PlayMatchInput) {
         for bot in &input.bots {
             *self.scheduled_matches_total.entry(bot.bot_id).or_default() += 1;
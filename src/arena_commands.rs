@@ -1,8 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
 
 use chrono::{DateTime, Utc};
 use tokio::sync::oneshot;
 
+use crate::config::RankingConfig;
 use crate::domain::*;
 
 pub enum ArenaCommand {
@@ -13,9 +15,61 @@ pub enum ArenaCommand {
     CreateLeaderboard(CreateLeaderboardCommand),
     DeleteLeaderboard(DeleteLeaderboardCommand),
     PatchLeaderboard(PatchLeaderboardCommand),
+    FetchRatingHistory(FetchRatingHistoryCommand),
+    FetchBootstrap(FetchBootstrapCommand),
+    FetchPrediction(FetchPredictionCommand),
+    FetchCalibration(FetchCalibrationCommand),
+    FetchHeadToHead(FetchHeadToHeadCommand),
     Chart(ChartCommand),
+    WinrateBuckets(WinrateBucketsCommand),
+    Histogram(HistogramCommand),
+    Scatter(ScatterCommand),
+    OutcomeCorrelation(OutcomeCorrelationCommand),
+    FetchMatches(FetchMatchesCommand),
+    FetchMatch(FetchMatchCommand),
     FetchBotSourceCode(FetchBotSourceCodeCommand),
     EnableMatchmaking(EnableMatchmakingCommand),
+    SetMatchmakingGroup(SetMatchmakingGroupCommand),
+    SetBotTags(SetBotTagsCommand),
+    CreateTournament(CreateTournamentCommand),
+    FetchTournaments(FetchTournamentsCommand),
+    DeleteTournament(DeleteTournamentCommand),
+}
+
+pub struct CreateTournamentCommand {
+    pub name: TournamentName,
+    pub format: TournamentFormat,
+    pub games_per_pair: u32,
+    pub bot_ids: Vec<BotId>,
+    pub response: oneshot::Sender<CreateTournamentResult>,
+}
+
+pub enum CreateTournamentResult {
+    Created(TournamentOverview),
+    BotNotFound(BotId),
+    UnsupportedPlayerCount,
+}
+
+pub struct FetchTournamentsCommand {
+    pub response: oneshot::Sender<Vec<TournamentOverview>>,
+}
+
+pub struct DeleteTournamentCommand {
+    pub id: TournamentId,
+    pub response: oneshot::Sender<()>,
+}
+
+pub struct TournamentOverview {
+    pub id: TournamentId,
+    pub name: TournamentName,
+    pub format: TournamentFormat,
+    pub games_per_pair: u32,
+    pub finished: bool,
+    pub current_round: u32,
+    pub total_games: usize,
+    pub finished_games: usize,
+    pub standings: Vec<TournamentStanding>,
+    pub created_at: DateTime<Utc>,
 }
 
 pub struct EnableMatchmakingCommand {
@@ -23,6 +77,22 @@ pub struct EnableMatchmakingCommand {
     pub response: oneshot::Sender<()>,
 }
 
+pub struct SetMatchmakingGroupCommand {
+    pub group: Option<BotTag>,
+    pub response: oneshot::Sender<()>,
+}
+
+pub struct SetBotTagsCommand {
+    pub id: BotId,
+    pub tags: Vec<BotTag>,
+    pub response: oneshot::Sender<SetBotTagsResult>,
+}
+
+pub enum SetBotTagsResult {
+    Updated,
+    NotFound,
+}
+
 pub struct FetchBotSourceCodeCommand {
     pub id: BotId,
     pub response: oneshot::Sender<Option<BotSourceCode>>,
@@ -33,32 +103,246 @@ pub struct BotSourceCode {
     pub source_code: SourceCode,
 }
 
+pub struct FetchHeadToHeadCommand {
+    pub id: LeaderboardId,
+    pub a: BotId,
+    pub b: BotId,
+    pub response: oneshot::Sender<FetchHeadToHeadResult>,
+}
+
+pub enum FetchHeadToHeadResult {
+    OK(HeadToHead),
+    LeaderboardNotFound,
+    BotNotFound(BotId),
+}
+
 pub struct ChartCommand {
     pub filter: MatchFilter,
     pub attribute_name: String,
+    pub options: ChartOptions,
     pub response: oneshot::Sender<ChartOverview>,
 }
 
+pub struct ChartOptions {
+    pub level: AttributeLevel,
+    pub split_by: ChartSplit,
+    /// only the matches of this bot, and only its values for bot attributes
+    pub bot_id: Option<BotId>,
+    /// from 0 to 100, calculated for every turn besides the median
+    pub percentiles: Vec<f64>,
+    /// amount of the latest matches to use
+    pub max_matches: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AttributeLevel {
+    /// `[PDATA]` attributes
+    Bot,
+    /// `[TDATA]` attributes
+    Match,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ChartSplit {
+    /// a series per bot: its own values of the bot attributes or the values
+    /// of the match attributes in the matches it played
+    Bot,
+    /// won and lost series, where won means the first place of the bot the value
+    /// belongs to or, for match attributes, of the requested bot
+    Outcome,
+}
+
 pub struct ChartOverview {
     pub items: Vec<ChartItem>,
     pub total_matches: u64,
 }
 
 pub struct ChartItem {
-    pub bot_id: BotId,
+    pub series: ChartSeries,
     pub data: Vec<ChartTurnData>,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChartSeries {
+    Bot(BotId),
+    Won,
+    Lost,
+}
+
 pub struct ChartTurnData {
     pub turn: u16,
+    pub count: u64,
     pub avg: f64,
     pub min: f64,
     pub max: f64,
+    pub median: f64,
+    pub stddev: f64,
+    /// in the order of the requested percentiles
+    pub percentiles: Vec<f64>,
+}
+
+pub struct HistogramCommand {
+    pub filter: MatchFilter,
+    /// bot attribute without turn
+    pub attribute_name: String,
+    pub bins: usize,
+    pub response: oneshot::Sender<HistogramOverview>,
+}
+
+/// Equal-width bins shared by all the bots, from the lowest value to the highest one
+pub struct HistogramOverview {
+    pub bins: Vec<HistogramBin>,
+    pub items: Vec<HistogramItem>,
+    pub total_matches: u64,
+}
+
+/// Values in `lower..upper`, the last bin includes `upper`
+pub struct HistogramBin {
+    pub lower: f64,
+    pub upper: f64,
+}
+
+pub struct HistogramItem {
+    pub bot_id: BotId,
+    /// amount of values in every bin
+    pub counts: Vec<u64>,
+    pub avg: f64,
+}
+
+pub struct ScatterCommand {
+    pub filter: MatchFilter,
+    pub options: ScatterOptions,
+    pub response: oneshot::Sender<ScatterOverview>,
+}
+
+pub struct ScatterOptions {
+    pub x: ScatterAxis,
+    pub y: ScatterAxis,
+    pub color_by: ChartSplit,
+    /// only the points of this bot
+    pub bot_id: Option<BotId>,
+    /// amount of the latest matches to use
+    pub max_matches: usize,
+}
+
+/// Attribute without turn
+pub struct ScatterAxis {
+    pub attribute_name: String,
+    pub level: AttributeLevel,
+}
+
+pub struct ScatterOverview {
+    pub items: Vec<ScatterItem>,
+    pub total_matches: u64,
+}
+
+pub struct ScatterItem {
+    pub series: ChartSeries,
+    pub points: Vec<ScatterPoint>,
+}
+
+/// A point per match participant having both the values
+pub struct ScatterPoint {
+    pub match_id: MatchId,
+    pub x: f64,
+    pub y: f64,
+}
+
+pub struct OutcomeCorrelationCommand {
+    pub filter: MatchFilter,
+    pub bot_id: BotId,
+    pub response: oneshot::Sender<OutcomeCorrelationResult>,
+}
+
+pub enum OutcomeCorrelationResult {
+    OK(OutcomeCorrelationOverview),
+    BotNotFound,
+}
+
+pub struct OutcomeCorrelationOverview {
+    /// sorted by the absolute effect size, the largest first
+    pub items: Vec<OutcomeCorrelationItem>,
+    /// amount of matches the bot played
+    pub total_matches: u64,
+    pub wins: u64,
+}
+
+pub struct OutcomeCorrelationItem {
+    pub attribute_name: String,
+    pub level: AttributeLevel,
+    pub correlation: OutcomeCorrelation,
+}
+
+pub struct WinrateBucketsCommand {
+    pub filter: MatchFilter,
+    pub attribute_name: String,
+    pub buckets: Buckets,
+    pub response: oneshot::Sender<WinrateBucketsOverview>,
+}
+
+pub enum Buckets {
+    /// sorted bucket boundaries, values below the first edge form the first bucket
+    Edges(Vec<f64>),
+    /// amount of buckets with roughly the same amount of matches
+    Quantiles(usize),
+}
+
+pub struct WinrateBucketsOverview {
+    pub buckets: Vec<WinrateBucket>,
+    /// amount of matches having the attribute
+    pub total_matches: u64,
+}
+
+/// Matches with the attribute value in `lower..upper`, unbounded if `None`
+pub struct WinrateBucket {
+    pub lower: Option<f64>,
+    pub upper: Option<f64>,
+    pub total_matches: u64,
+    pub items: Vec<WinrateBucketItem>,
+}
+
+pub struct WinrateBucketItem {
+    pub bot_id: BotId,
+    pub matches: u64,
+    /// pairwise outcomes against all the opponents
+    pub winrate_stats: WinrateStats,
+}
+
+pub struct FetchMatchesCommand {
+    pub filter: MatchFilter,
+    /// only the matches this bot played in
+    pub bot_id: Option<BotId>,
+    pub limit: usize,
+    /// only the matches older than this one
+    pub cursor: Option<MatchId>,
+    pub response: oneshot::Sender<MatchesPage>,
+}
+
+pub struct MatchesPage {
+    /// newest first
+    pub matches: Vec<Match>,
+    /// `None` if there are no more matches
+    pub next_cursor: Option<MatchId>,
+}
+
+pub struct FetchMatchCommand {
+    pub id: MatchId,
+    /// `None` if there is no match with such id
+    pub response: oneshot::Sender<Option<MatchDetails>>,
+}
+
+pub struct MatchDetails {
+    pub m: Match,
+    /// in seat order
+    pub bot_names: Vec<Option<BotName>>,
 }
 
 pub struct CreateLeaderboardCommand {
     pub name: LeaderboardName,
     pub filter: MatchFilter,
+    pub ranking: Option<RankingConfig>,
+    pub uncertainty_coefficient: Option<f64>,
+    pub columns: Vec<AttributeAggregation>,
     pub response: oneshot::Sender<LeaderboardOverview>,
 }
 
@@ -66,6 +350,9 @@ pub struct PatchLeaderboardCommand {
     pub id: LeaderboardId,
     pub name: LeaderboardName,
     pub filter: MatchFilter,
+    pub ranking: Option<RankingConfig>,
+    pub uncertainty_coefficient: Option<f64>,
+    pub columns: Vec<AttributeAggregation>,
     pub response: oneshot::Sender<PatchLeaderboardResult>,
 }
 
@@ -74,6 +361,76 @@ pub enum PatchLeaderboardResult {
     NotFound,
 }
 
+pub struct FetchRatingHistoryCommand {
+    pub id: LeaderboardId,
+    /// `None` if there is no leaderboard with such id
+    pub response: oneshot::Sender<Option<Vec<RatingSnapshot>>>,
+}
+
+pub struct FetchBootstrapCommand {
+    pub id: LeaderboardId,
+    /// `None` if there is no leaderboard with such id
+    pub response: oneshot::Sender<Option<BootstrapOverview>>,
+}
+
+pub struct BootstrapOverview {
+    pub status: LeaderboardStatus,
+    pub samples: usize,
+    pub total_matches: u64,
+    pub items: Vec<BootstrapItem>,
+    pub stronger_probabilities: HashMap<(BotId, BotId), f64>,
+}
+
+pub struct BootstrapItem {
+    pub id: BotId,
+    /// rank among the bots which played the leaderboard matches
+    pub rank: usize,
+    /// probability that the bot truly holds its displayed rank
+    pub rank_probability: f64,
+    pub mu_lower: f64,
+    pub mu_upper: f64,
+}
+
+pub struct FetchPredictionCommand {
+    pub id: LeaderboardId,
+    pub bot_ids: Vec<BotId>,
+    pub response: oneshot::Sender<FetchPredictionResult>,
+}
+
+pub enum FetchPredictionResult {
+    OK(PredictionOverview),
+    LeaderboardNotFound,
+    BotNotFound(BotId),
+}
+
+/// Outcome of a hypothetical match between the bots according to the leaderboard ratings
+pub struct PredictionOverview {
+    pub status: LeaderboardStatus,
+    pub items: Vec<PredictionItem>,
+    /// probability that the first bot finishes ahead of the second one
+    pub win_probabilities: HashMap<(BotId, BotId), f64>,
+}
+
+pub struct PredictionItem {
+    pub id: BotId,
+    pub rating: Rating,
+    /// i-th value is the probability of the bot finishing at place i
+    pub placement_probabilities: Vec<f64>,
+    /// 1 is the winner
+    pub expected_place: f64,
+}
+
+pub struct FetchCalibrationCommand {
+    pub id: LeaderboardId,
+    /// `None` if there is no leaderboard with such id
+    pub response: oneshot::Sender<Option<CalibrationOverview>>,
+}
+
+pub struct CalibrationOverview {
+    pub status: LeaderboardStatus,
+    pub calibration: Option<Arc<Calibration>>,
+}
+
 pub struct DeleteLeaderboardCommand {
     pub id: LeaderboardId,
     pub response: oneshot::Sender<()>,
@@ -116,6 +473,7 @@ pub struct FetchStatusResult {
     pub bots: Vec<BotOverview>,
     pub leaderboards: Vec<LeaderboardOverview>,
     pub matchmaking_enabled: bool,
+    pub matchmaking_group: Option<BotTag>,
 }
 
 pub struct BotOverview {
@@ -125,6 +483,7 @@ pub struct BotOverview {
     pub matches_played: u64,
     pub matches_with_error: u64,
     pub builds: Vec<Build>,
+    pub tags: Vec<BotTag>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -133,8 +492,16 @@ pub struct LeaderboardOverview {
     pub name: LeaderboardName,
     pub filter: String,
     pub status: LeaderboardStatus,
+    /// `None` if the arena-wide ranking config is used
+    pub ranking: Option<RankingConfig>,
+    pub uncertainty_coefficient: f64,
+    pub columns: Vec<AttributeAggregation>,
     pub items: Vec<LeaderboardItem>,
     pub winrate_stats: HashMap<(BotId, BotId), WinrateStats>,
+    /// outcomes of all the bots keyed by their seat index
+    pub seat_stats: BTreeMap<u8, WinrateStats>,
+    /// first seat winrate minus 0.5, `None` until a match is played
+    pub seat_advantage: Option<f64>,
     pub total_matches: u64,
     pub example_seeds: Vec<i64>,
 }
@@ -150,4 +517,8 @@ pub struct LeaderboardItem {
     pub rank: usize,
     pub rating: Rating,
     pub rating_ordinal: f64,
+    pub ratings_by_player_count: BTreeMap<u8, Rating>,
+    pub seat_stats: BTreeMap<u8, WinrateStats>,
+    /// values of the leaderboard `columns`, `None` if the bot has no attribute values
+    pub columns: Vec<Option<f64>>,
 }
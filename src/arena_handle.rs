@@ -1,11 +1,22 @@
 use crate::arena_commands::{
-    ArenaCommand, BotSourceCode, ChartCommand, ChartOverview, CreateBotCommand, CreateBotResult,
-    CreateLeaderboardCommand, DeleteBotCommand, DeleteLeaderboardCommand, EnableMatchmakingCommand,
-    FetchBotSourceCodeCommand, FetchStatusCommand, FetchStatusResult, LeaderboardOverview,
+    ArenaCommand, BootstrapOverview, BotSourceCode, Buckets, CalibrationOverview, ChartCommand,
+    ChartOptions, ChartOverview, CreateBotCommand, CreateBotResult, CreateLeaderboardCommand,
+    CreateTournamentCommand, CreateTournamentResult, DeleteBotCommand, DeleteLeaderboardCommand,
+    DeleteTournamentCommand, EnableMatchmakingCommand, FetchBootstrapCommand,
+    FetchBotSourceCodeCommand, FetchCalibrationCommand, FetchHeadToHeadCommand,
+    FetchHeadToHeadResult, FetchMatchCommand, FetchMatchesCommand, FetchPredictionCommand,
+    FetchPredictionResult, FetchRatingHistoryCommand, FetchStatusCommand, FetchStatusResult,
+    FetchTournamentsCommand, HistogramCommand, HistogramOverview, LeaderboardOverview,
+    MatchDetails, MatchesPage, OutcomeCorrelationCommand, OutcomeCorrelationResult,
     PatchLeaderboardCommand, PatchLeaderboardResult, RenameBotCommand, RenameBotResult,
+    ScatterCommand, ScatterOptions, ScatterOverview, SetBotTagsCommand, SetBotTagsResult,
+    SetMatchmakingGroupCommand, TournamentOverview, WinrateBucketsCommand, WinrateBucketsOverview,
 };
+use crate::config::RankingConfig;
 use crate::domain::{
-    BotId, BotName, Language, LeaderboardId, LeaderboardName, MatchFilter, SourceCode,
+    AttributeAggregation, BotId, BotName, BotTag, Language, LeaderboardId, LeaderboardName,
+    MatchFilter, MatchId, RatingSnapshot, SourceCode, TournamentFormat, TournamentId,
+    TournamentName,
 };
 use tokio::sync::{mpsc, oneshot};
 
@@ -76,11 +87,17 @@ impl ArenaHandle {
         &self,
         name: LeaderboardName,
         filter: MatchFilter,
+        ranking: Option<RankingConfig>,
+        uncertainty_coefficient: Option<f64>,
+        columns: Vec<AttributeAggregation>,
     ) -> anyhow::Result<LeaderboardOverview> {
         self.send_command_and_await_for_result(move |tx| {
             ArenaCommand::CreateLeaderboard(CreateLeaderboardCommand {
                 name,
                 filter,
+                ranking,
+                uncertainty_coefficient,
+                columns,
                 response: tx,
             })
         })
@@ -92,12 +109,18 @@ impl ArenaHandle {
         id: LeaderboardId,
         name: LeaderboardName,
         filter: MatchFilter,
+        ranking: Option<RankingConfig>,
+        uncertainty_coefficient: Option<f64>,
+        columns: Vec<AttributeAggregation>,
     ) -> anyhow::Result<PatchLeaderboardResult> {
         self.send_command_and_await_for_result(move |tx| {
             ArenaCommand::PatchLeaderboard(PatchLeaderboardCommand {
                 id,
                 name,
                 filter,
+                ranking,
+                uncertainty_coefficient,
+                columns,
                 response: tx,
             })
         })
@@ -111,21 +134,175 @@ impl ArenaHandle {
         .await
     }
 
+    pub async fn fetch_rating_history(
+        &self,
+        id: LeaderboardId,
+    ) -> anyhow::Result<Option<Vec<RatingSnapshot>>> {
+        self.send_command_and_await_for_result(move |tx| {
+            ArenaCommand::FetchRatingHistory(FetchRatingHistoryCommand { id, response: tx })
+        })
+        .await
+    }
+
+    pub async fn fetch_bootstrap(
+        &self,
+        id: LeaderboardId,
+    ) -> anyhow::Result<Option<BootstrapOverview>> {
+        self.send_command_and_await_for_result(move |tx| {
+            ArenaCommand::FetchBootstrap(FetchBootstrapCommand { id, response: tx })
+        })
+        .await
+    }
+
+    pub async fn fetch_prediction(
+        &self,
+        id: LeaderboardId,
+        bot_ids: Vec<BotId>,
+    ) -> anyhow::Result<FetchPredictionResult> {
+        self.send_command_and_await_for_result(move |tx| {
+            ArenaCommand::FetchPrediction(FetchPredictionCommand {
+                id,
+                bot_ids,
+                response: tx,
+            })
+        })
+        .await
+    }
+
+    pub async fn fetch_head_to_head(
+        &self,
+        id: LeaderboardId,
+        a: BotId,
+        b: BotId,
+    ) -> anyhow::Result<FetchHeadToHeadResult> {
+        self.send_command_and_await_for_result(move |tx| {
+            ArenaCommand::FetchHeadToHead(FetchHeadToHeadCommand {
+                id,
+                a,
+                b,
+                response: tx,
+            })
+        })
+        .await
+    }
+
+    pub async fn fetch_calibration(
+        &self,
+        id: LeaderboardId,
+    ) -> anyhow::Result<Option<CalibrationOverview>> {
+        self.send_command_and_await_for_result(move |tx| {
+            ArenaCommand::FetchCalibration(FetchCalibrationCommand { id, response: tx })
+        })
+        .await
+    }
+
     pub async fn chart(
         &self,
         filter: MatchFilter,
         attribute_name: String,
+        options: ChartOptions,
     ) -> anyhow::Result<ChartOverview> {
         self.send_command_and_await_for_result(move |tx| {
             ArenaCommand::Chart(ChartCommand {
                 filter,
                 attribute_name,
+                options,
+                response: tx,
+            })
+        })
+        .await
+    }
+
+    pub async fn winrate_buckets(
+        &self,
+        filter: MatchFilter,
+        attribute_name: String,
+        buckets: Buckets,
+    ) -> anyhow::Result<WinrateBucketsOverview> {
+        self.send_command_and_await_for_result(move |tx| {
+            ArenaCommand::WinrateBuckets(WinrateBucketsCommand {
+                filter,
+                attribute_name,
+                buckets,
+                response: tx,
+            })
+        })
+        .await
+    }
+
+    pub async fn histogram(
+        &self,
+        filter: MatchFilter,
+        attribute_name: String,
+        bins: usize,
+    ) -> anyhow::Result<HistogramOverview> {
+        self.send_command_and_await_for_result(move |tx| {
+            ArenaCommand::Histogram(HistogramCommand {
+                filter,
+                attribute_name,
+                bins,
+                response: tx,
+            })
+        })
+        .await
+    }
+
+    pub async fn scatter(
+        &self,
+        filter: MatchFilter,
+        options: ScatterOptions,
+    ) -> anyhow::Result<ScatterOverview> {
+        self.send_command_and_await_for_result(move |tx| {
+            ArenaCommand::Scatter(ScatterCommand {
+                filter,
+                options,
+                response: tx,
+            })
+        })
+        .await
+    }
+
+    pub async fn outcome_correlation(
+        &self,
+        filter: MatchFilter,
+        bot_id: BotId,
+    ) -> anyhow::Result<OutcomeCorrelationResult> {
+        self.send_command_and_await_for_result(move |tx| {
+            ArenaCommand::OutcomeCorrelation(OutcomeCorrelationCommand {
+                filter,
+                bot_id,
                 response: tx,
             })
         })
         .await
     }
 
+    pub async fn fetch_matches(
+        &self,
+        filter: MatchFilter,
+        bot_id: Option<BotId>,
+        limit: usize,
+        cursor: Option<MatchId>,
+    ) -> anyhow::Result<MatchesPage> {
+        self.send_command_and_await_for_result(move |tx| {
+            ArenaCommand::FetchMatches(FetchMatchesCommand {
+                filter,
+                bot_id,
+                limit,
+                cursor,
+                response: tx,
+            })
+        })
+        .await
+    }
+
+    pub async fn fetch_match(&self, id: MatchId) -> anyhow::Result<Option<MatchDetails>> {
+        self.send_command_and_await_for_result(move |tx| {
+            ArenaCommand::FetchMatch(FetchMatchCommand { id, response: tx })
+        })
+        .await
+    }
+
     pub async fn enable_matchmaking(&self, enabled: bool) -> anyhow::Result<()> {
         self.send_command_and_await_for_result(move |tx| {
             ArenaCommand::EnableMatchmaking(EnableMatchmakingCommand {
@@ -136,6 +313,64 @@ impl ArenaHandle {
         .await
     }
 
+    pub async fn set_matchmaking_group(&self, group: Option<BotTag>) -> anyhow::Result<()> {
+        self.send_command_and_await_for_result(move |tx| {
+            ArenaCommand::SetMatchmakingGroup(SetMatchmakingGroupCommand {
+                group,
+                response: tx,
+            })
+        })
+        .await
+    }
+
+    pub async fn set_bot_tags(
+        &self,
+        id: BotId,
+        tags: Vec<BotTag>,
+    ) -> anyhow::Result<SetBotTagsResult> {
+        self.send_command_and_await_for_result(move |tx| {
+            ArenaCommand::SetBotTags(SetBotTagsCommand {
+                id,
+                tags,
+                response: tx,
+            })
+        })
+        .await
+    }
+
+    pub async fn create_tournament(
+        &self,
+        name: TournamentName,
+        format: TournamentFormat,
+        games_per_pair: u32,
+        bot_ids: Vec<BotId>,
+    ) -> anyhow::Result<CreateTournamentResult> {
+        self.send_command_and_await_for_result(move |tx| {
+            ArenaCommand::CreateTournament(CreateTournamentCommand {
+                name,
+                format,
+                games_per_pair,
+                bot_ids,
+                response: tx,
+            })
+        })
+        .await
+    }
+
+    pub async fn fetch_tournaments(&self) -> anyhow::Result<Vec<TournamentOverview>> {
+        self.send_command_and_await_for_result(move |tx| {
+            ArenaCommand::FetchTournaments(FetchTournamentsCommand { response: tx })
+        })
+        .await
+    }
+
+    pub async fn delete_tournament(&self, id: TournamentId) -> anyhow::Result<()> {
+        self.send_command_and_await_for_result(move |tx| {
+            ArenaCommand::DeleteTournament(DeleteTournamentCommand { id, response: tx })
+        })
+        .await
+    }
+
     async fn send_command_and_await_for_result<R, F: FnOnce(oneshot::Sender<R>) -> ArenaCommand>(
         &self,
         cmd_builder: F,
@@ -26,6 +26,14 @@ where
     F1: Fn(BuildBotInput) -> BuildResult + Send + 'static,
 {
     let pool = db::in_memory().await.unwrap();
+    create_test_arena_with_pool(config, pool, builder).await
+}
+
+/// Starts the arena over the existing db, as if it was restarted
+async fn create_test_arena_with_pool<F1>(config: Config, pool: SqlitePool, builder: F1) -> TestArena
+where
+    F1: Fn(BuildBotInput) -> BuildResult + Send + 'static,
+{
     let (commands_tx, commands_rx) = tokio::sync::mpsc::channel(16);
     let cancellation_token = CancellationToken::new();
     let (match_result_tx, match_result_rx) = tokio::sync::mpsc::channel(100);
@@ -74,6 +82,62 @@ where
     }
 }
 
+async fn create_bots(arena: &TestArena, names: &[&str]) -> Vec<BotId> {
+    let mut bot_ids = vec![];
+    for &name in names {
+        let res = arena
+            .handle
+            .create_bot(
+                String::from(name).try_into().unwrap(),
+                String::from("some code").try_into().unwrap(),
+                String::from("rust").try_into().unwrap(),
+            )
+            .await;
+        let CreateBotResult::Created(bot) = res.unwrap() else {
+            panic!("Bot creation should succeed");
+        };
+        bot_ids.push(bot.id);
+    }
+    bot_ids
+}
+
+/// `ranks` are in seat order, nobody errors
+async fn send_match(
+    arena: &TestArena,
+    seed: i64,
+    ranks: &[(BotId, u8)],
+    attributes: Vec<MatchAttribute>,
+) {
+    let match_result = PlayMatchOutput {
+        seed,
+        tournament_game_id: None,
+        participants: ranks
+            .iter()
+            .map(|&(bot_id, rank)| Participant {
+                bot_id,
+                rank,
+                error: false,
+            })
+            .collect(),
+        attributes,
+    };
+    arena.match_result_tx.send(match_result).await.unwrap();
+}
+
+fn attribute(
+    name: &str,
+    bot_id: Option<BotId>,
+    turn: Option<u16>,
+    value: impl ToString,
+) -> MatchAttribute {
+    MatchAttribute {
+        name: name.to_string(),
+        bot_id,
+        turn,
+        value: value.to_string().into(),
+    }
+}
+
 #[tokio::test]
 async fn cmd_create_bot_should_create_record_in_db() {
     let config = Config::default();
@@ -448,6 +512,7 @@ async fn cmd_fetch_leaderboard_e2e() {
 
     let fake_match_result = PlayMatchOutput {
         seed: 1234,
+        tournament_game_id: None,
         participants: vec![
             Participant {
                 bot_id: b1,
@@ -553,3 +618,1015 @@ async fn cmd_fetch_leaderboard_e2e() {
 
     assert_eq!(leaderboard.total_matches, 1);
 }
+
+#[tokio::test]
+async fn cmd_create_tournament_plans_first_round() {
+    let config = Config::default();
+    let arena = create_test_arena(config, |_| BuildResult::Success).await;
+
+    let bot_ids = create_bots(&arena, &["Bot1", "Bot2", "Bot3"]).await;
+
+    let res = arena
+        .handle
+        .create_tournament(
+            String::from("final").try_into().unwrap(),
+            TournamentFormat::RoundRobin,
+            2,
+            bot_ids.clone(),
+        )
+        .await;
+    let CreateTournamentResult::Created(tournament) = res.unwrap() else {
+        panic!("Tournament creation should succeed");
+    };
+
+    assert_ne!(tournament.id, TournamentId::UNINITIALIZED);
+    assert_eq!(tournament.current_round, 1);
+    // 3 pairs, 2 games per pair, symmetric game
+    assert_eq!(tournament.total_games, 6);
+    assert_eq!(tournament.standings.len(), 3);
+
+    let games_in_db: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tournament_games")
+        .fetch_one(&arena.pool)
+        .await
+        .unwrap();
+    assert_eq!(games_in_db, 6);
+
+    let unknown_bot: BotId = 1000.into();
+    let res = arena
+        .handle
+        .create_tournament(
+            String::from("other").try_into().unwrap(),
+            TournamentFormat::RoundRobin,
+            1,
+            vec![bot_ids[0], unknown_bot],
+        )
+        .await;
+    assert!(matches!(
+        res.unwrap(),
+        CreateTournamentResult::BotNotFound(id) if id == unknown_bot
+    ));
+
+    arena.handle.delete_tournament(tournament.id).await.unwrap();
+    assert!(arena.handle.fetch_tournaments().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn deleted_bot_is_not_paired_in_next_swiss_rounds() {
+    let config = Config::default();
+    let arena = create_test_arena(config, |_| BuildResult::Success).await;
+
+    let bot_ids = create_bots(&arena, &["Bot1", "Bot2", "Bot3", "Bot4"]).await;
+
+    let res = arena
+        .handle
+        .create_tournament(
+            String::from("swiss").try_into().unwrap(),
+            TournamentFormat::Swiss { rounds: 2 },
+            1,
+            bot_ids.clone(),
+        )
+        .await;
+    let CreateTournamentResult::Created(tournament) = res.unwrap() else {
+        panic!("Tournament creation should succeed");
+    };
+
+    let deleted = bot_ids[0];
+    arena.handle.delete_bot(deleted).await.unwrap();
+
+    // plays every unfinished game, the first bot wins
+    let play_round = || async {
+        let games: Vec<(i64, i64, Option<i64>)> = sqlx::query_as(
+            "SELECT id, bot_id_1, bot_id_2 FROM tournament_games WHERE rank_1 IS NULL",
+        )
+        .fetch_all(&arena.pool)
+        .await
+        .unwrap();
+        for (id, b1, b2) in games {
+            let b2 = b2.expect("byes are finished right away");
+            assert!(b1 != i64::from(deleted) && b2 != i64::from(deleted));
+            arena
+                .match_result_tx
+                .send(PlayMatchOutput {
+                    seed: 1,
+                    tournament_game_id: Some(id.into()),
+                    participants: vec![
+                        Participant {
+                            bot_id: b1.into(),
+                            rank: 0,
+                            error: false,
+                        },
+                        Participant {
+                            bot_id: b2.into(),
+                            rank: 1,
+                            error: false,
+                        },
+                    ],
+                    attributes: vec![],
+                })
+                .await
+                .unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    };
+    play_round().await;
+    play_round().await;
+
+    let tournaments = arena.handle.fetch_tournaments().await.unwrap();
+    let overview = tournaments.iter().find(|t| t.id == tournament.id).unwrap();
+    assert_eq!(overview.current_round, 2);
+    assert!(overview.finished);
+    assert_eq!(overview.standings.len(), 4);
+}
+
+#[tokio::test]
+async fn cmd_set_bot_tags_works() {
+    let config = Config::default();
+    let arena = create_test_arena(config, |_| BuildResult::Success).await;
+
+    let bot_id = create_bots(&arena, &["Bot1"]).await[0];
+
+    let tag = |s: &str| BotTag::try_from(s.to_string()).unwrap();
+    let res = arena
+        .handle
+        .set_bot_tags(bot_id, vec![tag("mcts"), tag("alice"), tag("mcts")])
+        .await
+        .unwrap();
+    assert!(matches!(res, SetBotTagsResult::Updated));
+
+    let status = arena.handle.fetch_status().await.unwrap();
+    assert_eq!(status.bots[0].tags, vec![tag("alice"), tag("mcts")]);
+
+    let db_tags: Vec<String> =
+        sqlx::query_scalar("SELECT tag FROM bot_tags WHERE bot_id = $1 ORDER BY tag")
+            .bind::<i64>(bot_id.into())
+            .fetch_all(&arena.pool)
+            .await
+            .unwrap();
+    assert_eq!(db_tags, vec!["alice", "mcts"]);
+
+    let res = arena
+        .handle
+        .set_bot_tags(1000.into(), vec![tag("alice")])
+        .await
+        .unwrap();
+    assert!(matches!(res, SetBotTagsResult::NotFound));
+}
+
+#[tokio::test]
+async fn rating_history_is_recorded() {
+    let mut config = Config::default();
+    config.leaderboards.history_every_matches = Some(2);
+    config.leaderboards.refit_interval_ms = Some(0);
+    let arena = create_test_arena(config, |_| BuildResult::Success).await;
+
+    let bot_ids = create_bots(&arena, &["Bot1", "Bot2"]).await;
+
+    for seed in 0..3 {
+        send_match(&arena, seed, &[(bot_ids[0], 0), (bot_ids[1], 1)], vec![]).await;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+
+    let history = arena
+        .handle
+        .fetch_rating_history(LeaderboardId::UNINITIALIZED)
+        .await
+        .unwrap()
+        .expect("Global leaderboard should exist");
+
+    // snapshots after the 1st and the 3rd match
+    assert_eq!(history.len(), 4);
+    assert_eq!(
+        history.iter().map(|s| s.total_matches).collect::<Vec<_>>(),
+        vec![1, 1, 3, 3]
+    );
+    let last_winner = history.iter().rfind(|s| s.bot_id == bot_ids[0]).unwrap();
+    assert_eq!(last_winner.rank, 0);
+
+    let res = arena
+        .handle
+        .fetch_rating_history(1000.into())
+        .await
+        .unwrap();
+    assert!(res.is_none());
+}
+
+#[tokio::test]
+async fn rating_history_survives_restart_unless_ranking_changes() {
+    let mut config = Config::default();
+    config.leaderboards.history_every_matches = Some(2);
+    config.leaderboards.refit_interval_ms = Some(0);
+    let arena = create_test_arena(config, |_| BuildResult::Success).await;
+
+    let bot_ids = create_bots(&arena, &["Bot1", "Bot2"]).await;
+    send_match(&arena, 0, &[(bot_ids[0], 0), (bot_ids[1], 1)], vec![]).await;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    arena.cancellation_token.cancel();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    // same ratings are not recorded again
+    let mut config = Config::default();
+    config.leaderboards.history_every_matches = Some(2);
+    let arena =
+        create_test_arena_with_pool(config, arena.pool.clone(), |_| BuildResult::Success).await;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let history = arena
+        .handle
+        .fetch_rating_history(LeaderboardId::UNINITIALIZED)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(history.len(), 2);
+    let recorded_at = history[0].created_at;
+    arena.cancellation_token.cancel();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    // history of the old ranking is wiped, only the fresh snapshot is left
+    let mut config = Config::default();
+    config.leaderboards.history_every_matches = Some(2);
+    config.ranking = serde_json::from_str(r#"{"algorithm": "Elo", "k": 16.0}"#).unwrap();
+    let arena =
+        create_test_arena_with_pool(config, arena.pool.clone(), |_| BuildResult::Success).await;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let history = arena
+        .handle
+        .fetch_rating_history(LeaderboardId::UNINITIALIZED)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(history.len(), 2);
+    assert!(history.iter().all(|s| s.created_at > recorded_at));
+}
+
+#[tokio::test]
+async fn cmd_fetch_bootstrap_works() {
+    let mut config = Config::default();
+    config.leaderboards.bootstrap_samples = Some(20);
+    let arena = create_test_arena(config, |_| BuildResult::Success).await;
+
+    let bot_ids = create_bots(&arena, &["Bot1", "Bot2", "Idle"]).await;
+
+    for seed in 0..10 {
+        send_match(&arena, seed, &[(bot_ids[0], 0), (bot_ids[1], 1)], vec![]).await;
+    }
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let res = arena
+        .handle
+        .fetch_bootstrap(LeaderboardId::UNINITIALIZED)
+        .await
+        .unwrap()
+        .expect("Global leaderboard should exist");
+    assert!(matches!(res.status, LeaderboardStatus::Computing));
+
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let res = arena
+        .handle
+        .fetch_bootstrap(LeaderboardId::UNINITIALIZED)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(res.status, LeaderboardStatus::Live));
+    assert_eq!(res.samples, 20);
+    assert_eq!(res.total_matches, 10);
+    assert_eq!(res.items.len(), 2);
+    assert_eq!(res.items[0].id, bot_ids[0]);
+    assert_eq!(res.items[0].rank, 0);
+    assert!(res.items[0].rank_probability > 0.99);
+    assert!(res.stronger_probabilities[&(bot_ids[0], bot_ids[1])] > 0.99);
+
+    // the default Elo rating of the idle bot is between the ones of the playing bots
+    let ranking = serde_json::from_str(r#"{"algorithm": "Elo", "k": 16.0}"#).unwrap();
+    let overview = arena
+        .handle
+        .create_leaderboard(
+            String::from("elo").try_into().unwrap(),
+            MatchFilter::accept_all(),
+            Some(ranking),
+            Some(0.0),
+            vec![],
+        )
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    arena.handle.fetch_bootstrap(overview.id).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let res = arena
+        .handle
+        .fetch_bootstrap(overview.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(res.status, LeaderboardStatus::Live));
+    // the idle bot is not ranked
+    assert_eq!(res.items.len(), 2);
+    assert!(res.items[0].rank_probability > 0.0);
+    assert_eq!(res.items[1].id, bot_ids[1]);
+    assert_eq!(res.items[1].rank, 1);
+    assert!(res.items[1].rank_probability > 0.99);
+
+    let res = arena.handle.fetch_bootstrap(1000.into()).await.unwrap();
+    assert!(res.is_none());
+}
+
+#[tokio::test]
+async fn leaderboard_with_own_ranking_config() {
+    let config = Config::default();
+    let arena = create_test_arena(config, |_| BuildResult::Success).await;
+
+    let bot_id = create_bots(&arena, &["Bot1"]).await[0];
+
+    let ranking = serde_json::from_str(r#"{"algorithm": "Elo", "k": 16.0}"#).unwrap();
+    let overview = arena
+        .handle
+        .create_leaderboard(
+            String::from("elo").try_into().unwrap(),
+            MatchFilter::accept_all(),
+            Some(ranking),
+            Some(0.0),
+            vec![],
+        )
+        .await
+        .unwrap();
+    assert_eq!(overview.uncertainty_coefficient, 0.0);
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let status = arena.handle.fetch_status().await.unwrap();
+    let global = &status.leaderboards[0];
+    let custom = &status.leaderboards[1];
+    assert!(global.ranking.is_none());
+    assert_eq!(global.uncertainty_coefficient, 3.0);
+    assert!(matches!(
+        custom.ranking.as_ref().map(|r| &r.algorithm),
+        Some(crate::config::RankingAlgorithmConfig::Elo(_))
+    ));
+    // default ratings differ between Bradley-Terry and Elo
+    let global_item = global.items.iter().find(|i| i.id == bot_id).unwrap();
+    let custom_item = custom.items.iter().find(|i| i.id == bot_id).unwrap();
+    assert_ne!(global_item.rating.mu, custom_item.rating.mu);
+
+    let (ranking, coefficient): (Option<String>, Option<f64>) =
+        sqlx::query_as("SELECT ranking, uncertainty_coefficient FROM leaderboards WHERE id = $1")
+            .bind::<i64>(overview.id.into())
+            .fetch_one(&arena.pool)
+            .await
+            .unwrap();
+    assert!(ranking.unwrap().contains("Elo"));
+    assert_eq!(coefficient, Some(0.0));
+
+    let res = arena
+        .handle
+        .patch_leaderboard(
+            overview.id,
+            String::from("elo").try_into().unwrap(),
+            MatchFilter::accept_all(),
+            None,
+            None,
+            vec![],
+        )
+        .await
+        .unwrap();
+    assert!(matches!(res, PatchLeaderboardResult::OK));
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let status = arena.handle.fetch_status().await.unwrap();
+    let custom = &status.leaderboards[1];
+    assert!(custom.ranking.is_none());
+    assert_eq!(custom.uncertainty_coefficient, 3.0);
+}
+
+#[tokio::test]
+async fn leaderboard_columns_aggregate_bot_attributes() {
+    let arena = create_test_arena(Config::default(), |_| BuildResult::Success).await;
+
+    let bot_ids = create_bots(&arena, &["Bot1", "Bot2"]).await;
+
+    for seed in 0..4 {
+        let score = attribute("score", Some(bot_ids[0]), None, seed);
+        send_match(
+            &arena,
+            seed,
+            &[(bot_ids[0], 0), (bot_ids[1], 1)],
+            vec![score],
+        )
+        .await;
+    }
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let column = |function| AttributeAggregation {
+        attribute: "score".to_string(),
+        function,
+    };
+    let overview = arena
+        .handle
+        .create_leaderboard(
+            String::from("scores").try_into().unwrap(),
+            MatchFilter::accept_all(),
+            None,
+            None,
+            vec![column(AggregateFunction::Avg)],
+        )
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let columns = |status: &FetchStatusResult, bot_id| {
+        let item = status.leaderboards[1]
+            .items
+            .iter()
+            .find(|i| i.id == bot_id)
+            .unwrap();
+        item.columns.clone()
+    };
+    let status = arena.handle.fetch_status().await.unwrap();
+    assert_eq!(columns(&status, bot_ids[0]), vec![Some(1.5)]);
+    assert_eq!(columns(&status, bot_ids[1]), vec![None]);
+
+    let res = arena
+        .handle
+        .patch_leaderboard(
+            overview.id,
+            String::from("scores").try_into().unwrap(),
+            MatchFilter::accept_all(),
+            None,
+            None,
+            vec![
+                column(AggregateFunction::Max),
+                column(AggregateFunction::Median),
+            ],
+        )
+        .await
+        .unwrap();
+    assert!(matches!(res, PatchLeaderboardResult::OK));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let status = arena.handle.fetch_status().await.unwrap();
+    assert_eq!(status.leaderboards[1].columns.len(), 2);
+    assert_eq!(columns(&status, bot_ids[0]), vec![Some(3.0), Some(1.5)]);
+
+    let leaderboards = db::fetch_leaderboards(&arena.pool).await.unwrap();
+    assert_eq!(leaderboards[0].columns.len(), 2);
+}
+
+#[tokio::test]
+async fn cmd_fetch_prediction_and_calibration_work() {
+    let arena = create_test_arena(Config::default(), |_| BuildResult::Success).await;
+
+    let bot_ids = create_bots(&arena, &["Bot1", "Bot2"]).await;
+
+    for seed in 0..20 {
+        send_match(&arena, seed, &[(bot_ids[0], 0), (bot_ids[1], 1)], vec![]).await;
+    }
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let res = arena
+        .handle
+        .fetch_prediction(LeaderboardId::UNINITIALIZED, bot_ids.clone())
+        .await
+        .unwrap();
+    let FetchPredictionResult::OK(prediction) = res else {
+        panic!("Prediction should succeed");
+    };
+    assert!(matches!(prediction.status, LeaderboardStatus::Live));
+    assert_eq!(prediction.items.len(), 2);
+    assert!(prediction.items[0].placement_probabilities[0] > 0.5);
+    assert!(prediction.items[0].expected_place < prediction.items[1].expected_place);
+    // 1-based places, one bot is the first and the other is the second
+    let places_total: f64 = prediction.items.iter().map(|i| i.expected_place).sum();
+    assert!((places_total - 3.0).abs() < 1e-6);
+    assert!(prediction.win_probabilities[&(bot_ids[0], bot_ids[1])] > 0.5);
+
+    let res = arena
+        .handle
+        .fetch_prediction(LeaderboardId::UNINITIALIZED, vec![bot_ids[0], 1000.into()])
+        .await
+        .unwrap();
+    assert!(matches!(res, FetchPredictionResult::BotNotFound(_)));
+
+    let res = arena
+        .handle
+        .fetch_calibration(LeaderboardId::UNINITIALIZED)
+        .await
+        .unwrap()
+        .expect("Global leaderboard should exist");
+    assert!(matches!(res.status, LeaderboardStatus::Computing));
+
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let res = arena
+        .handle
+        .fetch_calibration(LeaderboardId::UNINITIALIZED)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(res.status, LeaderboardStatus::Live));
+    let calibration = res.calibration.unwrap();
+    assert_eq!(calibration.total_matches, 20);
+    assert!(calibration.pairs > 0);
+    assert!(calibration.brier_score < 0.25);
+
+    let res = arena.handle.fetch_calibration(1000.into()).await.unwrap();
+    assert!(res.is_none());
+}
+
+#[tokio::test]
+async fn cmd_fetch_head_to_head_works() {
+    let arena = create_test_arena(Config::default(), |_| BuildResult::Success).await;
+
+    let bot_ids = create_bots(&arena, &["Bot1", "Bot2", "Bot3"]).await;
+
+    // bot 1 plays bot 2 on even seeds, losing every 4th match, and bot 3 on odd ones
+    for seed in 0..12 {
+        let opponent = bot_ids[1 + seed as usize % 2];
+        let lost = seed % 4 == 0;
+        let ranks = [(bot_ids[0], lost as u8), (opponent, !lost as u8)];
+        let score = attribute("score", Some(bot_ids[0]), None, 3);
+        send_match(&arena, seed, &ranks, vec![score]).await;
+    }
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let res = arena
+        .handle
+        .fetch_head_to_head(LeaderboardId::UNINITIALIZED, bot_ids[0], bot_ids[1])
+        .await
+        .unwrap();
+    let FetchHeadToHeadResult::OK(h2h) = res else {
+        panic!("Head-to-head should succeed");
+    };
+    assert_eq!(h2h.total_matches, 6);
+    assert_eq!((h2h.winrate_stats.wins, h2h.winrate_stats.loses), (3, 3));
+    assert!(h2h.winrate.lower < 0.5 && h2h.winrate.upper > 0.5);
+    assert_eq!(h2h.by_seat[&0].winrate_stats.total(), 6);
+    assert_eq!(h2h.lost_seeds, vec![8, 4, 0]);
+    // bot 2 has no score to compare with
+    assert!(h2h.attributes.is_empty());
+
+    let res = arena
+        .handle
+        .fetch_head_to_head(LeaderboardId::UNINITIALIZED, bot_ids[0], 1000.into())
+        .await
+        .unwrap();
+    assert!(matches!(res, FetchHeadToHeadResult::BotNotFound(_)));
+
+    let res = arena
+        .handle
+        .fetch_head_to_head(1000.into(), bot_ids[0], bot_ids[1])
+        .await
+        .unwrap();
+    assert!(matches!(res, FetchHeadToHeadResult::LeaderboardNotFound));
+}
+
+#[tokio::test]
+async fn cmd_fetch_matches_filters_and_paginates() {
+    let arena = create_test_arena(Config::default(), |_| BuildResult::Success).await;
+
+    let bot_ids = create_bots(&arena, &["Bot1", "Bot2", "Bot3"]).await;
+
+    // bot 1 plays bot 2 on even seeds and bot 3 on odd ones, maps alternate every 2 seeds
+    for seed in 0..8 {
+        let ranks = [(bot_ids[0], 0), (bot_ids[1 + seed as usize % 2], 1)];
+        let map = attribute(
+            "map",
+            None,
+            None,
+            if seed % 4 < 2 { "small" } else { "large" },
+        );
+        send_match(&arena, seed, &ranks, vec![map]).await;
+    }
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let seeds = |page: &MatchesPage| page.matches.iter().map(|m| m.seed).collect::<Vec<_>>();
+
+    let filter: MatchFilter = "match.map == \"small\"".parse().unwrap();
+    let page = arena
+        .handle
+        .fetch_matches(filter.clone(), None, 10, None)
+        .await
+        .unwrap();
+    assert_eq!(seeds(&page), vec![5, 4, 1, 0]);
+    assert!(page.next_cursor.is_none());
+
+    let page = arena
+        .handle
+        .fetch_matches(filter.clone(), Some(bot_ids[2]), 1, None)
+        .await
+        .unwrap();
+    assert_eq!(seeds(&page), vec![5]);
+    let page = arena
+        .handle
+        .fetch_matches(filter, Some(bot_ids[2]), 1, page.next_cursor)
+        .await
+        .unwrap();
+    assert_eq!(seeds(&page), vec![1]);
+
+    // bot tags can only be checked after fetching
+    let tags = vec!["old".to_string().try_into().unwrap()];
+    arena
+        .handle
+        .set_bot_tags(bot_ids[0], tags.clone())
+        .await
+        .unwrap();
+    arena.handle.set_bot_tags(bot_ids[1], tags).await.unwrap();
+    let filter: MatchFilter = "bot_tag(\"old\") AND match.map == \"large\""
+        .parse()
+        .unwrap();
+    let page = arena
+        .handle
+        .fetch_matches(filter, None, 10, None)
+        .await
+        .unwrap();
+    assert_eq!(seeds(&page), vec![6, 2]);
+    assert!(page.matches[0]
+        .attributes
+        .iter()
+        .any(|a| a.name == "map" && a.value.string_value() == Some("large")));
+}
+
+#[tokio::test]
+async fn cmd_fetch_match_loads_all_attributes() {
+    let arena = create_test_arena(Config::default(), |_| BuildResult::Success).await;
+
+    let bot_ids = create_bots(&arena, &["Bot1", "Bot2"]).await;
+
+    // the loser errors, so the match is built by hand
+    let match_result = PlayMatchOutput {
+        seed: 42,
+        tournament_game_id: None,
+        participants: vec![
+            Participant {
+                bot_id: bot_ids[1],
+                rank: 0,
+                error: false,
+            },
+            Participant {
+                bot_id: bot_ids[0],
+                rank: 1,
+                error: true,
+            },
+        ],
+        attributes: vec![
+            attribute("map", None, None, "small"),
+            attribute("units", None, Some(3), "7"),
+            attribute("score", Some(bot_ids[1]), None, "1.5"),
+            attribute("score", Some(bot_ids[1]), Some(3), "0.5"),
+        ],
+    };
+    arena.match_result_tx.send(match_result).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let page = arena
+        .handle
+        .fetch_matches("".parse().unwrap(), None, 1, None)
+        .await
+        .unwrap();
+    let id = page.matches[0].id;
+
+    let details = arena.handle.fetch_match(id).await.unwrap().unwrap();
+    assert_eq!(details.m.seed, 42);
+    let seats = details
+        .m
+        .participants
+        .iter()
+        .map(|p| (p.bot_id, p.rank, p.error))
+        .collect::<Vec<_>>();
+    assert_eq!(seats, vec![(bot_ids[1], 0, false), (bot_ids[0], 1, true)]);
+    let names = details
+        .bot_names
+        .iter()
+        .map(|n| n.as_ref().map(|n| n.to_string()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![Some("Bot2".to_string()), Some("Bot1".to_string())]
+    );
+
+    let find = |name: &str, bot_id: Option<BotId>, turn: Option<u16>| {
+        details
+            .m
+            .attributes
+            .iter()
+            .find(|a| a.name == name && a.bot_id == bot_id && a.turn == turn)
+            .map(|a| &a.value)
+    };
+    assert_eq!(
+        find("map", None, None).and_then(|v| v.string_value()),
+        Some("small")
+    );
+    assert_eq!(
+        find("units", None, Some(3)).and_then(|v| v.integer_value()),
+        Some(7)
+    );
+    assert_eq!(
+        find("score", Some(bot_ids[1]), None).and_then(|v| v.float_value()),
+        Some(1.5)
+    );
+    assert_eq!(
+        find("score", Some(bot_ids[1]), Some(3)).and_then(|v| v.float_value()),
+        Some(0.5)
+    );
+
+    assert!(arena
+        .handle
+        .fetch_match((i64::from(id) + 100).into())
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn cmd_winrate_buckets_splits_by_attribute() {
+    let arena = create_test_arena(Config::default(), |_| BuildResult::Success).await;
+
+    let bot_ids = create_bots(&arena, &["Bot1", "Bot2"]).await;
+
+    // bot 1 wins on small maps only, the last match has no map size
+    for seed in 0..9 {
+        let size = seed * 10;
+        let won = size < 40;
+        let ranks = [(bot_ids[0], !won as u8), (bot_ids[1], won as u8)];
+        let attributes = if seed < 8 {
+            vec![attribute("size", None, None, size)]
+        } else {
+            vec![]
+        };
+        send_match(&arena, seed, &ranks, attributes).await;
+    }
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let res = arena
+        .handle
+        .winrate_buckets(
+            "".parse().unwrap(),
+            "size".to_string(),
+            Buckets::Edges(vec![40.0]),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.total_matches, 8);
+    assert_eq!(res.buckets.len(), 2);
+    assert_eq!(
+        (res.buckets[0].lower, res.buckets[0].upper),
+        (None, Some(40.0))
+    );
+    assert_eq!(
+        (res.buckets[1].lower, res.buckets[1].upper),
+        (Some(40.0), None)
+    );
+    let small = &res.buckets[0].items;
+    assert_eq!(small[0].bot_id, bot_ids[0]);
+    assert_eq!(small[0].matches, 4);
+    assert_eq!(small[0].winrate_stats.winrate(), Some(1.0));
+    assert_eq!(small[1].winrate_stats.winrate(), Some(0.0));
+    assert_eq!(res.buckets[1].items[0].winrate_stats.winrate(), Some(0.0));
+
+    let res = arena
+        .handle
+        .winrate_buckets(
+            "match.seed != 100".parse().unwrap(),
+            "size".to_string(),
+            Buckets::Quantiles(4),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.buckets.len(), 4);
+    assert!(res.buckets.iter().all(|b| b.total_matches == 2));
+}
+
+#[tokio::test]
+async fn cmd_chart_aggregates_by_bot_and_outcome() {
+    let arena = create_test_arena(Config::default(), |_| BuildResult::Success).await;
+
+    let bot_ids = create_bots(&arena, &["Bot1", "Bot2"]).await;
+
+    // bot 1 wins the first 3 matches, its money on turn 0 is the seed,
+    // cells on turn 0 are 10 times the seed
+    for seed in 0..4 {
+        let won = seed < 3;
+        let ranks = [(bot_ids[0], !won as u8), (bot_ids[1], won as u8)];
+        let attributes = vec![
+            attribute("money", Some(bot_ids[0]), Some(0), seed),
+            attribute("cells", None, Some(0), seed * 10),
+        ];
+        send_match(&arena, seed, &ranks, attributes).await;
+    }
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let options = |level, split_by, bot_id| ChartOptions {
+        level,
+        split_by,
+        bot_id,
+        percentiles: vec![25.0, 75.0],
+        max_matches: 1000,
+    };
+
+    let res = arena
+        .handle
+        .chart(
+            "".parse().unwrap(),
+            "money".to_string(),
+            options(AttributeLevel::Bot, ChartSplit::Bot, None),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.total_matches, 4);
+    assert_eq!(res.items.len(), 1);
+    assert!(res.items[0].series == ChartSeries::Bot(bot_ids[0]));
+    let data = &res.items[0].data[0];
+    assert_eq!((data.count, data.min, data.max), (4, 0.0, 3.0));
+    assert_eq!((data.avg, data.median), (1.5, 1.5));
+    assert!((data.stddev - 1.25f64.sqrt()).abs() < 1e-9);
+    assert_eq!(data.percentiles, vec![0.75, 2.25]);
+
+    let res = arena
+        .handle
+        .chart(
+            "".parse().unwrap(),
+            "money".to_string(),
+            options(AttributeLevel::Bot, ChartSplit::Outcome, None),
+        )
+        .await
+        .unwrap();
+    let won = res
+        .items
+        .iter()
+        .find(|item| item.series == ChartSeries::Won)
+        .unwrap();
+    assert_eq!((won.data[0].count, won.data[0].max), (3, 2.0));
+
+    // match attributes are split by the outcome of the requested bot
+    let res = arena
+        .handle
+        .chart(
+            "".parse().unwrap(),
+            "cells".to_string(),
+            options(AttributeLevel::Match, ChartSplit::Outcome, Some(bot_ids[1])),
+        )
+        .await
+        .unwrap();
+    let won = res
+        .items
+        .iter()
+        .find(|item| item.series == ChartSeries::Won)
+        .unwrap();
+    assert_eq!((won.data[0].count, won.data[0].avg), (1, 30.0));
+
+    let res = arena
+        .handle
+        .chart(
+            "".parse().unwrap(),
+            "cells".to_string(),
+            ChartOptions {
+                max_matches: 2,
+                ..options(AttributeLevel::Match, ChartSplit::Bot, None)
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.total_matches, 2);
+    assert_eq!(res.items.len(), 2);
+    assert!(res.items.iter().all(|item| item.data[0].avg == 25.0));
+}
+
+#[tokio::test]
+async fn cmd_histogram_and_scatter_work() {
+    let arena = create_test_arena(Config::default(), |_| BuildResult::Success).await;
+
+    let bot_ids = create_bots(&arena, &["Bot1", "Bot2"]).await;
+
+    // bot 1 scores the seed and wins, bot 2 scores 10 and has no score in the last match
+    for seed in 0..5 {
+        let mut attributes = vec![
+            attribute("size", None, None, seed * 2),
+            attribute("score", Some(bot_ids[0]), None, seed),
+        ];
+        if seed < 4 {
+            attributes.push(attribute("score", Some(bot_ids[1]), None, 10));
+        }
+        send_match(
+            &arena,
+            seed,
+            &[(bot_ids[0], 0), (bot_ids[1], 1)],
+            attributes,
+        )
+        .await;
+    }
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let res = arena
+        .handle
+        .histogram("".parse().unwrap(), "score".to_string(), 5)
+        .await
+        .unwrap();
+    assert_eq!(res.total_matches, 5);
+    assert_eq!(res.bins.len(), 5);
+    assert_eq!((res.bins[0].lower, res.bins[4].upper), (0.0, 10.0));
+    assert_eq!(res.items[0].bot_id, bot_ids[0]);
+    assert_eq!(res.items[0].counts, vec![2, 2, 1, 0, 0]);
+    assert_eq!(res.items[0].avg, 2.0);
+    assert_eq!(res.items[1].counts, vec![0, 0, 0, 0, 4]);
+
+    let options = |color_by, bot_id| ScatterOptions {
+        x: ScatterAxis {
+            attribute_name: "size".to_string(),
+            level: AttributeLevel::Match,
+        },
+        y: ScatterAxis {
+            attribute_name: "score".to_string(),
+            level: AttributeLevel::Bot,
+        },
+        color_by,
+        bot_id,
+        max_matches: 1000,
+    };
+
+    let res = arena
+        .handle
+        .scatter("".parse().unwrap(), options(ChartSplit::Outcome, None))
+        .await
+        .unwrap();
+    assert_eq!(res.total_matches, 5);
+    let won = res
+        .items
+        .iter()
+        .find(|item| item.series == ChartSeries::Won)
+        .unwrap();
+    assert_eq!(won.points.len(), 5);
+    assert!(won.points.iter().all(|p| p.x == p.y * 2.0));
+    let lost = res
+        .items
+        .iter()
+        .find(|item| item.series == ChartSeries::Lost)
+        .unwrap();
+    assert_eq!(lost.points.len(), 4);
+
+    let res = arena
+        .handle
+        .scatter(
+            "".parse().unwrap(),
+            ScatterOptions {
+                max_matches: 2,
+                ..options(ChartSplit::Bot, Some(bot_ids[1]))
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.total_matches, 2);
+    assert_eq!(res.items.len(), 1);
+    assert!(res.items[0].series == ChartSeries::Bot(bot_ids[1]));
+    assert_eq!(res.items[0].points.len(), 1);
+}
+
+#[tokio::test]
+async fn cmd_outcome_correlation_ranks_attributes() {
+    let arena = create_test_arena(Config::default(), |_| BuildResult::Success).await;
+
+    let bot_ids = create_bots(&arena, &["Bot1", "Bot2"]).await;
+
+    // bot 1 wins the even seeds, its score follows the outcome closely,
+    // the map size only loosely
+    for seed in 0..8 {
+        let won = seed % 2 == 0;
+        let ranks = [(bot_ids[0], !won as u8), (bot_ids[1], won as u8)];
+        let score = if won { 10 + seed } else { seed };
+        let attributes = vec![
+            attribute("score", Some(bot_ids[0]), None, score),
+            attribute("size", None, None, if won { 3 + seed } else { seed }),
+            attribute("name", Some(bot_ids[0]), None, "abc"),
+        ];
+        send_match(&arena, seed, &ranks, attributes).await;
+    }
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let res = arena
+        .handle
+        .outcome_correlation("".parse().unwrap(), bot_ids[0])
+        .await
+        .unwrap();
+    let OutcomeCorrelationResult::OK(res) = res else {
+        panic!("Correlation should succeed");
+    };
+    assert_eq!((res.total_matches, res.wins), (8, 4));
+    let names = res
+        .items
+        .iter()
+        .map(|item| item.attribute_name.as_str())
+        .collect::<Vec<_>>();
+    // seat index is constant, seed and strings are skipped
+    assert_eq!(names, vec!["score", "size"]);
+    assert!(res.items[0].level == AttributeLevel::Bot);
+    assert!(res.items[1].level == AttributeLevel::Match);
+    assert_eq!(res.items[0].correlation.win_avg, 13.0);
+    assert_eq!(res.items[0].correlation.lose_avg, 4.0);
+    assert!(res.items[0].correlation.effect_size > res.items[1].correlation.effect_size);
+    assert!(res.items[1].correlation.correlation > 0.0);
+
+    let res = arena
+        .handle
+        .outcome_correlation("".parse().unwrap(), 1000.into())
+        .await
+        .unwrap();
+    assert!(matches!(res, OutcomeCorrelationResult::BotNotFound));
+}
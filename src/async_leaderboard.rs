@@ -3,21 +3,70 @@ use std::{
     time::{Duration, Instant},
 };
 
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
 use sqlx::SqlitePool;
 use tokio_util::sync::CancellationToken;
 
 use crate::{
     db,
-    domain::{ComputedStats, Leaderboard, Match},
+    domain::{
+        BatchFitInput, BootstrapStats, BotTags, Calibration, ComputedStats, Leaderboard, Match,
+    },
     ranking::Ranker,
 };
 
+// batch ratings are refitted sooner than the configured interval if this many matches were played
+const REFIT_EVERY_MATCHES: u64 = 100;
+
 pub struct AsyncLeaderboard {
     pub leaderboard: Leaderboard,
     ranker: Arc<Ranker>,
     pool: SqlitePool,
+    bot_tags: Arc<BotTags>,
     status: Arc<Mutex<LeaderboardStatus>>,
     live_matches: Vec<Arc<Match>>,
+    // total matches and time of the last rating snapshot
+    last_rating_snapshot: Option<(u64, Instant)>,
+    bootstrap: Arc<Mutex<BackgroundState<BootstrapStats>>>,
+    calibration: Arc<Mutex<BackgroundState<Calibration>>>,
+    // batch ratings refit running in background, at most one at a time
+    refit: Arc<Mutex<Option<CancellationToken>>>,
+    last_refit: Option<Instant>,
+}
+
+/// Stats over all the leaderboard matches which are too expensive to keep live
+struct BackgroundState<T> {
+    computing: Option<CancellationToken>,
+    // amount of matches the stats are based on
+    stats: Option<(u64, Arc<T>)>,
+    error: Option<String>,
+}
+
+impl<T> Default for BackgroundState<T> {
+    fn default() -> Self {
+        Self {
+            computing: None,
+            stats: None,
+            error: None,
+        }
+    }
+}
+
+impl<T> BackgroundState<T> {
+    fn reset(&mut self) {
+        if let Some(token) = &self.computing {
+            token.cancel();
+        }
+        *self = Self::default();
+    }
+}
+
+/// Latest background results, possibly outdated while the new ones are being computed
+pub struct BackgroundProgress<T> {
+    pub stats: Option<Arc<T>>,
+    pub computing: bool,
+    pub error: Option<String>,
 }
 
 impl Drop for AsyncLeaderboard {
@@ -28,19 +77,33 @@ impl Drop for AsyncLeaderboard {
             LeaderboardStatus::Computing(ref token) => token.cancel(),
             LeaderboardStatus::Error(_, _) => {}
         }
+        if let Some(token) = self.refit.lock().unwrap().take() {
+            token.cancel();
+        }
+        self.bootstrap.lock().unwrap().reset();
+        self.calibration.lock().unwrap().reset();
     }
 }
 
 impl AsyncLeaderboard {
-    pub fn new(leaderboard: Leaderboard, ranker: Arc<Ranker>, pool: SqlitePool) -> Self {
+    pub fn new(
+        leaderboard: Leaderboard,
+        ranker: Arc<Ranker>,
+        pool: SqlitePool,
+        bot_tags: Arc<BotTags>,
+    ) -> Self {
         Self {
             leaderboard,
             ranker,
             pool,
-            status: Arc::new(Mutex::new(
-                LeaderboardStatus::Live(ComputedStats::default()),
-            )),
+            bot_tags,
+            status: Arc::new(Mutex::new(LeaderboardStatus::Live(Box::default()))),
             live_matches: vec![],
+            last_rating_snapshot: None,
+            bootstrap: Default::default(),
+            calibration: Default::default(),
+            refit: Default::default(),
+            last_refit: None,
         }
     }
 
@@ -50,50 +113,221 @@ impl AsyncLeaderboard {
             token.cancel();
         }
 
+        // the refit is based on the stats being replaced
+        if let Some(refit_token) = self.refit.lock().unwrap().take() {
+            refit_token.cancel();
+        }
+
         let token = CancellationToken::new();
         *status = LeaderboardStatus::Computing(token.clone());
         drop(status);
 
+        // bootstrap results are bound to the matches of the leaderboard
+        self.bootstrap.lock().unwrap().reset();
+        self.calibration.lock().unwrap().reset();
+
         let status_inner = Arc::clone(&self.status);
         let ranker = Arc::clone(&self.ranker);
         let filter = self.leaderboard.filter.clone();
+        let columns = self.column_attributes();
         let pool = self.pool.clone();
+        let bot_tags = Arc::clone(&self.bot_tags);
         tokio::spawn(async move {
             let attrs = filter.needed_attributes();
-            let matches = db::fetch_matches_with_attrs(&pool, &attrs).await;
+            let matches = db::fetch_matches_with_attrs(&pool, &attrs, &columns).await;
 
-            match matches {
-                Ok(matches) => {
+            let res = match matches {
+                Ok(matches) => tokio::task::spawn_blocking(move || {
                     let filtered = matches
                         .iter()
-                        .filter(|m| filter.matches(m))
+                        .filter(|m| filter.matches(m, &bot_tags))
                         .collect::<Vec<_>>();
                     let mut stats = ComputedStats::default();
+                    stats.track_attributes(columns);
                     stats.recalc_after_matches(&ranker, &filtered);
-                    if !token.is_cancelled() {
-                        let mut status = status_inner.lock().unwrap();
-                        *status = LeaderboardStatus::Live(stats);
-                    }
-                }
-                Err(e) => {
-                    if !token.is_cancelled() {
-                        let mut status = status_inner.lock().unwrap();
-                        *status = LeaderboardStatus::Error(e, Instant::now());
-                    }
-                }
+                    stats
+                })
+                .await
+                .map_err(anyhow::Error::from),
+                Err(e) => Err(e),
+            };
+
+            if !token.is_cancelled() {
+                let mut status = status_inner.lock().unwrap();
+                *status = match res {
+                    Ok(stats) => LeaderboardStatus::Live(Box::new(stats)),
+                    Err(e) => LeaderboardStatus::Error(e, Instant::now()),
+                };
             }
         });
     }
 
+    /// Names of the bot attributes aggregated by the leaderboard columns
+    fn column_attributes(&self) -> Vec<String> {
+        self.leaderboard
+            .columns
+            .iter()
+            .map(|c| c.attribute.clone())
+            .unique()
+            .collect()
+    }
+
     pub fn stats(&self) -> Option<ComputedStats> {
         let status = self.status.lock().unwrap();
         match *status {
-            LeaderboardStatus::Live(ref computed_stats) => Some(computed_stats.clone()),
+            LeaderboardStatus::Live(ref computed_stats) => Some(computed_stats.as_ref().clone()),
             LeaderboardStatus::Computing(_) => None,
             LeaderboardStatus::Error(_, _) => None,
         }
     }
 
+    pub fn total_matches(&self) -> Option<u64> {
+        let status = self.status.lock().unwrap();
+        match *status {
+            LeaderboardStatus::Live(ref computed_stats) => Some(computed_stats.total_matches()),
+            _ => None,
+        }
+    }
+
+    /// Amount of matches the ratings are based on, batch ratings can lag behind `total_matches`
+    pub fn fitted_matches(&self) -> Option<u64> {
+        let status = self.status.lock().unwrap();
+        match *status {
+            LeaderboardStatus::Live(ref computed_stats) => Some(computed_stats.fitted_matches()),
+            _ => None,
+        }
+    }
+
+    /// Whether enough matches were played or enough time passed since the last rating snapshot
+    pub fn rating_snapshot_due(
+        &self,
+        every_matches: Option<u64>,
+        interval: Option<Duration>,
+    ) -> bool {
+        let Some(fitted_matches) = self.fitted_matches() else {
+            return false;
+        };
+        match self.last_rating_snapshot {
+            None => fitted_matches > 0,
+            Some((matches, at)) => {
+                fitted_matches != matches
+                    && (every_matches.is_some_and(|n| fitted_matches.abs_diff(matches) >= n)
+                        || interval.is_some_and(|interval| at.elapsed() >= interval))
+            }
+        }
+    }
+
+    pub fn mark_rating_snapshot_taken(&mut self, total_matches: u64) {
+        self.last_rating_snapshot = Some((total_matches, Instant::now()));
+    }
+
+    /// Picks up the rating history persisted before the restart,
+    /// so the same ratings are not recorded twice
+    pub fn restore_rating_snapshot(&mut self, total_matches: u64, taken_at: DateTime<Utc>) {
+        let elapsed = (Utc::now() - taken_at).to_std().unwrap_or_default();
+        let at = Instant::now()
+            .checked_sub(elapsed)
+            .unwrap_or_else(Instant::now);
+        self.last_rating_snapshot = Some((total_matches, at));
+    }
+
+    /// Should be called when the rating history is wiped
+    pub fn forget_rating_snapshots(&mut self) {
+        self.last_rating_snapshot = None;
+    }
+
+    /// Returns the latest bootstrap results, starting a new computation in background
+    /// if they are missing or outdated.
+    pub fn bootstrap(
+        &self,
+        samples: usize,
+        uncertainty_coefficient: f64,
+    ) -> BackgroundProgress<BootstrapStats> {
+        self.background(&self.bootstrap, move |ranker, matches| {
+            BootstrapStats::compute(
+                ranker,
+                matches,
+                samples,
+                uncertainty_coefficient,
+                &mut rand::rng(),
+            )
+        })
+    }
+
+    /// Returns the latest calibration report, starting a new computation in background
+    /// if it is missing or outdated.
+    pub fn calibration(&self) -> BackgroundProgress<Calibration> {
+        self.background(&self.calibration, Calibration::compute)
+    }
+
+    fn background<T, F>(
+        &self,
+        state: &Arc<Mutex<BackgroundState<T>>>,
+        compute: F,
+    ) -> BackgroundProgress<T>
+    where
+        T: Send + Sync + 'static,
+        F: FnOnce(&Ranker, &[&Match]) -> T + Send + 'static,
+    {
+        let total_matches = self.total_matches();
+        let mut state_guard = state.lock().unwrap();
+
+        let fresh = state_guard
+            .stats
+            .as_ref()
+            .is_some_and(|(matches, _)| Some(*matches) == total_matches);
+
+        if !fresh && total_matches.is_some() && state_guard.computing.is_none() {
+            let token = CancellationToken::new();
+            state_guard.computing = Some(token.clone());
+
+            let state_inner = Arc::clone(state);
+            let ranker = Arc::clone(&self.ranker);
+            let filter = self.leaderboard.filter.clone();
+            let pool = self.pool.clone();
+            let bot_tags = Arc::clone(&self.bot_tags);
+            tokio::spawn(async move {
+                let attrs = filter.needed_attributes();
+                let matches = db::fetch_matches_with_attrs(&pool, &attrs, &[]).await;
+
+                let res = match matches {
+                    Ok(matches) => tokio::task::spawn_blocking(move || {
+                        let filtered = matches
+                            .iter()
+                            .filter(|m| filter.matches(m, &bot_tags))
+                            .collect::<Vec<_>>();
+                        (filtered.len() as u64, compute(&ranker, &filtered))
+                    })
+                    .await
+                    .map_err(anyhow::Error::from),
+                    Err(e) => Err(e),
+                };
+
+                if token.is_cancelled() {
+                    return;
+                }
+                let mut state = state_inner.lock().unwrap();
+                state.computing = None;
+                match res {
+                    Ok((matches, stats)) => {
+                        state.stats = Some((matches, Arc::new(stats)));
+                        state.error = None;
+                    }
+                    Err(e) => state.error = Some(e.to_string()),
+                }
+            });
+        }
+
+        BackgroundProgress {
+            stats: state_guard
+                .stats
+                .as_ref()
+                .map(|(_, stats)| Arc::clone(stats)),
+            computing: state_guard.computing.is_some(),
+            error: state_guard.error.clone(),
+        }
+    }
+
     pub fn error(&self) -> Option<String> {
         let status = self.status.lock().unwrap();
         match *status {
@@ -102,34 +336,95 @@ impl AsyncLeaderboard {
         }
     }
 
+    pub fn ranker(&self) -> &Ranker {
+        &self.ranker
+    }
+
+    pub fn set_ranker(&mut self, ranker: Arc<Ranker>) {
+        self.ranker = ranker;
+        self.recalculate();
+    }
+
+    /// Recalculates the leaderboard if its filter depends on the tags
+    pub fn set_bot_tags(&mut self, bot_tags: Arc<BotTags>) {
+        self.bot_tags = bot_tags;
+        if self.leaderboard.filter.uses_bot_tags() {
+            self.recalculate();
+        }
+    }
+
     pub fn record_for_later(&mut self, m: Arc<Match>) {
         self.live_matches.push(m);
     }
 
-    pub fn catch_up_with_live_matches(&mut self) {
+    /// Batch ratings are refitted in background at most once per `refit_interval`,
+    /// so they can lag behind the live matches
+    pub fn catch_up_with_live_matches(&mut self, refit_interval: Duration) {
         let mut status = self.status.lock().unwrap();
-        match *status {
+        let refit_snapshot = match *status {
             LeaderboardStatus::Live(ref mut computed_stats) => {
                 let live_matches = std::mem::take(&mut self.live_matches);
                 let filtered = live_matches
                     .iter()
                     .map(|m| m.as_ref())
-                    .filter(|m| self.leaderboard.filter.matches(m))
+                    .filter(|m| self.leaderboard.filter.matches(m, &self.bot_tags))
                     .collect::<Vec<_>>();
-                computed_stats.recalc_after_matches(&self.ranker, &filtered);
+                computed_stats.record_matches(&self.ranker, &filtered);
+
+                let unfitted = computed_stats.unfitted_matches();
+                let due = unfitted >= REFIT_EVERY_MATCHES
+                    || self
+                        .last_refit
+                        .is_none_or(|at| at.elapsed() >= refit_interval);
+                let idle = self.refit.lock().unwrap().is_none();
+                (unfitted > 0 && due && idle).then(|| computed_stats.batch_fit_input(&self.ranker))
             }
-            LeaderboardStatus::Computing(_) => {}
+            LeaderboardStatus::Computing(_) => None,
             LeaderboardStatus::Error(_, at) => {
                 if Instant::now() > at + Duration::from_secs(3) {
+                    drop(status);
                     self.recalculate();
                 }
+                return;
             }
+        };
+        drop(status);
+
+        if let Some(input) = refit_snapshot {
+            self.last_refit = Some(Instant::now());
+            self.refit_in_background(input);
         }
     }
+
+    fn refit_in_background(&self, input: BatchFitInput) {
+        let token = CancellationToken::new();
+        *self.refit.lock().unwrap() = Some(token.clone());
+
+        let status_inner = Arc::clone(&self.status);
+        let refit_inner = Arc::clone(&self.refit);
+        let ranker = Arc::clone(&self.ranker);
+        tokio::spawn(async move {
+            let fit = tokio::task::spawn_blocking(move || input.fit(&ranker)).await;
+
+            let mut status = status_inner.lock().unwrap();
+            if token.is_cancelled() {
+                return;
+            }
+            *refit_inner.lock().unwrap() = None;
+            match fit {
+                Ok(fit) => {
+                    if let LeaderboardStatus::Live(ref mut computed_stats) = *status {
+                        computed_stats.apply_batch_fit(fit);
+                    }
+                }
+                Err(e) => *status = LeaderboardStatus::Error(e.into(), Instant::now()),
+            }
+        });
+    }
 }
 
 pub enum LeaderboardStatus {
-    Live(ComputedStats),
+    Live(Box<ComputedStats>),
     Computing(CancellationToken),
     Error(anyhow::Error, Instant),
 }
@@ -1,47 +1,62 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use itertools::Itertools;
 use sqlx::SqlitePool;
 
 use crate::{
-    arena_commands::{ChartItem, ChartOverview, ChartTurnData},
+    arena_commands::{
+        AttributeLevel, Buckets, ChartItem, ChartOptions, ChartOverview, ChartSeries, ChartSplit,
+        ChartTurnData, HistogramBin, HistogramItem, HistogramOverview, OutcomeCorrelationItem,
+        OutcomeCorrelationOverview, ScatterAxis, ScatterItem, ScatterOptions, ScatterOverview,
+        ScatterPoint, WinrateBucket, WinrateBucketItem, WinrateBucketsOverview,
+    },
     db,
-    domain::{BotId, MatchAttributeValue, MatchFilter, MatchId},
+    domain::{
+        percentile_of_sorted, AggregateFunction, BotId, BotTags, Match, MatchAttribute,
+        MatchAttributeValue, MatchFilter, MatchId, OutcomeCorrelation, WinrateStats,
+    },
 };
 
-const MATCHES_PER_CHART: usize = 1000;
-
 pub async fn visualize(
     filter: MatchFilter,
     attribute_name: String,
+    options: ChartOptions,
+    bot_tags: Arc<BotTags>,
     pool: SqlitePool,
 ) -> anyhow::Result<ChartOverview> {
-    let needed_attrs = filter.needed_attributes();
-    let matches = db::fetch_matches_with_attrs(&pool, &needed_attrs).await?;
+    let matches = fetch_filtered_matches(&filter, vec![], &[], &bot_tags, &pool).await?;
 
-    let filtered_match_ids: Vec<MatchId> = matches
+    let last_matches: HashMap<MatchId, &Match> = matches
         .iter()
-        .filter(|&m| filter.matches(m))
-        .map(|m| m.id)
-        .collect();
-
-    let last_match_ids: Vec<MatchId> = filtered_match_ids
-        .into_iter()
-        .k_largest_by_key::<_, i64>(MATCHES_PER_CHART, |id| (*id).into())
+        .filter(|m| {
+            options
+                .bot_id
+                .is_none_or(|id| m.participants.iter().any(|p| p.bot_id == id))
+        })
+        .k_largest_by_key(options.max_matches, |m| i64::from(m.id))
+        .map(|m| (m.id, m))
         .collect();
+    let last_match_ids = last_matches.keys().copied().collect_vec();
 
-    let data = db::fetch_turn_attributes(&pool, &last_match_ids, &attribute_name).await?;
+    let data = db::fetch_turn_attributes(
+        &pool,
+        &last_match_ids,
+        &attribute_name,
+        options.level == AttributeLevel::Bot,
+    )
+    .await?;
 
-    let mut res: HashMap<BotId, HashMap<u16, Stats>> = HashMap::new();
+    let mut res: HashMap<ChartSeries, HashMap<u16, Vec<f64>>> = HashMap::new();
 
-    for attr in data {
+    for (match_id, attr) in data {
         if attr.name != attribute_name {
             continue;
         }
-        let Some(bot_id) = attr.bot_id else {
+        let Some(turn) = attr.turn else {
             continue;
         };
-        let Some(turn) = attr.turn else {
+        let Some(m) = last_matches.get(&match_id) else {
             continue;
         };
         let v = match attr.value {
@@ -50,30 +65,23 @@ pub async fn visualize(
             MatchAttributeValue::String(_) => continue,
         };
 
-        res.entry(bot_id)
-            .or_default()
-            .entry(turn)
-            .and_modify(|stats| stats.adjust(v))
-            .or_insert(v.into());
+        for series in chart_series(m, attr.bot_id, &options) {
+            res.entry(series)
+                .or_default()
+                .entry(turn)
+                .or_default()
+                .push(v);
+        }
     }
 
     let overview = ChartOverview {
         items: res
             .into_iter()
-            .map(|(bot_id, data)| ChartItem {
-                bot_id,
+            .map(|(series, data)| ChartItem {
+                series,
                 data: data
                     .into_iter()
-                    .map(|(turn, stats)| ChartTurnData {
-                        turn,
-                        avg: if stats.cnt == 0 {
-                            0.0
-                        } else {
-                            stats.sum / stats.cnt as f64
-                        },
-                        min: stats.min,
-                        max: stats.max,
-                    })
+                    .map(|(turn, values)| turn_data(turn, values, &options.percentiles))
                     .sorted_by_key(|w| w.turn)
                     .collect(),
             })
@@ -84,29 +92,422 @@ pub async fn visualize(
     Ok(overview)
 }
 
-struct Stats {
-    sum: f64,
-    min: f64,
-    max: f64,
-    cnt: u64,
+/// Series the value of the bot attribute (or the match attribute if there is no bot)
+/// goes to
+fn chart_series(m: &Match, bot_id: Option<BotId>, options: &ChartOptions) -> Vec<ChartSeries> {
+    if let (Some(id), Some(target)) = (bot_id, options.bot_id) {
+        if id != target {
+            return vec![];
+        }
+    }
+    match options.split_by {
+        ChartSplit::Bot => match bot_id {
+            Some(id) => vec![ChartSeries::Bot(id)],
+            None => m
+                .participants
+                .iter()
+                .map(|p| p.bot_id)
+                .filter(|&id| options.bot_id.is_none_or(|target| target == id))
+                .unique()
+                .map(ChartSeries::Bot)
+                .collect(),
+        },
+        ChartSplit::Outcome => {
+            let Some(id) = bot_id.or(options.bot_id) else {
+                return vec![];
+            };
+            let won = m.participants.iter().any(|p| p.bot_id == id && p.rank == 0);
+            vec![if won {
+                ChartSeries::Won
+            } else {
+                ChartSeries::Lost
+            }]
+        }
+    }
+}
+
+/// `values` must not be empty
+fn turn_data(turn: u16, mut values: Vec<f64>, percentiles: &[f64]) -> ChartTurnData {
+    let count = values.len() as f64;
+    let avg = values.iter().sum::<f64>() / count;
+    let variance = values.iter().map(|v| (v - avg) * (v - avg)).sum::<f64>() / count;
+    // sorted once for all the order statistics
+    values.sort_by(f64::total_cmp);
+    ChartTurnData {
+        turn,
+        count: values.len() as u64,
+        avg,
+        min: values[0],
+        max: values[values.len() - 1],
+        median: percentile_of_sorted(&values, 50.0),
+        stddev: variance.sqrt(),
+        percentiles: percentiles
+            .iter()
+            .map(|&p| percentile_of_sorted(&values, p))
+            .collect(),
+    }
 }
 
-impl From<f64> for Stats {
-    fn from(value: f64) -> Self {
-        Self {
-            sum: value,
-            min: value,
-            max: value,
-            cnt: 1,
+/// Pairwise winrate of every bot depending on the value of a numeric match attribute,
+/// matches without the attribute are skipped
+pub async fn winrate_by_buckets(
+    filter: MatchFilter,
+    attribute_name: String,
+    buckets: Buckets,
+    bot_tags: Arc<BotTags>,
+    pool: SqlitePool,
+) -> anyhow::Result<WinrateBucketsOverview> {
+    let attr = MatchAttribute {
+        name: attribute_name.clone(),
+        bot_id: None,
+        turn: None,
+        // don't care about value
+        value: MatchAttributeValue::Integer(0),
+    };
+    let matches = fetch_filtered_matches(&filter, vec![attr], &[], &bot_tags, &pool).await?;
+
+    let valued = matches
+        .iter()
+        .filter_map(|m| Some((m, attribute_value(m, &attribute_name, None)?)))
+        .collect_vec();
+
+    let edges = match buckets {
+        Buckets::Edges(edges) => edges,
+        Buckets::Quantiles(count) => {
+            let values = valued
+                .iter()
+                .map(|(_, v)| *v)
+                .sorted_by(f64::total_cmp)
+                .collect_vec();
+            if values.is_empty() {
+                vec![]
+            } else {
+                (1..count)
+                    .map(|k| percentile_of_sorted(&values, k as f64 * 100.0 / count as f64))
+                    .dedup()
+                    .collect()
+            }
+        }
+    };
+
+    // bot -> (matches, winrate stats) of every bucket
+    let mut stats: Vec<HashMap<BotId, (u64, WinrateStats)>> = vec![HashMap::new(); edges.len() + 1];
+    let mut totals = vec![0u64; edges.len() + 1];
+    for (m, value) in &valued {
+        let bucket = edges.partition_point(|&edge| edge <= *value);
+        totals[bucket] += 1;
+        for p in m.participants.iter().unique_by(|p| p.bot_id) {
+            stats[bucket].entry(p.bot_id).or_default().0 += 1;
+        }
+        for (p1, p2) in m.participants.iter().tuple_combinations() {
+            if p1.bot_id == p2.bot_id {
+                continue;
+            }
+            let outcome = p1.rank.cmp(&p2.rank);
+            stats[bucket].entry(p1.bot_id).or_default().1.add(outcome);
+            stats[bucket]
+                .entry(p2.bot_id)
+                .or_default()
+                .1
+                .add(outcome.reverse());
         }
     }
+
+    let buckets = stats
+        .into_iter()
+        .zip(totals)
+        .enumerate()
+        .map(|(i, (stats, total_matches))| WinrateBucket {
+            lower: i.checked_sub(1).map(|j| edges[j]),
+            upper: edges.get(i).copied(),
+            total_matches,
+            items: stats
+                .into_iter()
+                .map(|(bot_id, (matches, winrate_stats))| WinrateBucketItem {
+                    bot_id,
+                    matches,
+                    winrate_stats,
+                })
+                .sorted_by_key(|item| i64::from(item.bot_id))
+                .collect(),
+        })
+        .collect();
+
+    Ok(WinrateBucketsOverview {
+        buckets,
+        total_matches: valued.len() as _,
+    })
 }
 
-impl Stats {
-    fn adjust(&mut self, v: f64) {
-        self.sum += v;
-        self.min = self.min.min(v);
-        self.max = self.max.max(v);
-        self.cnt += 1;
+/// Per-bot distribution of a bot attribute without turn
+pub async fn histogram(
+    filter: MatchFilter,
+    attribute_name: String,
+    bins: usize,
+    bot_tags: Arc<BotTags>,
+    pool: SqlitePool,
+) -> anyhow::Result<HistogramOverview> {
+    let matches = fetch_filtered_matches(
+        &filter,
+        vec![],
+        std::slice::from_ref(&attribute_name),
+        &bot_tags,
+        &pool,
+    )
+    .await?;
+
+    let mut values: HashMap<BotId, Vec<f64>> = HashMap::new();
+    let mut total_matches = 0;
+    for m in &matches {
+        let mut found = false;
+        for p in m.participants.iter().unique_by(|p| p.bot_id) {
+            if let Some(v) = attribute_value(m, &attribute_name, Some(p.bot_id)) {
+                values.entry(p.bot_id).or_default().push(v);
+                found = true;
+            }
+        }
+        if found {
+            total_matches += 1;
+        }
+    }
+
+    let all_values = values.values().flatten().copied().collect_vec();
+    let (Some(min), Some(max)) = (
+        AggregateFunction::Min.apply(&all_values),
+        AggregateFunction::Max.apply(&all_values),
+    ) else {
+        return Ok(HistogramOverview {
+            bins: vec![],
+            items: vec![],
+            total_matches: 0,
+        });
+    };
+    // a single bin if all the values are the same
+    let bins = if min == max { 1 } else { bins };
+    let width = (max - min) / bins as f64;
+
+    Ok(HistogramOverview {
+        bins: (0..bins)
+            .map(|i| HistogramBin {
+                lower: min + width * i as f64,
+                upper: if i + 1 == bins {
+                    max
+                } else {
+                    min + width * (i + 1) as f64
+                },
+            })
+            .collect(),
+        items: values
+            .into_iter()
+            .map(|(bot_id, values)| {
+                let mut counts = vec![0; bins];
+                for &v in &values {
+                    let bin = if width == 0.0 {
+                        0
+                    } else {
+                        (((v - min) / width) as usize).min(bins - 1)
+                    };
+                    counts[bin] += 1;
+                }
+                HistogramItem {
+                    bot_id,
+                    counts,
+                    avg: AggregateFunction::Avg.apply(&values).unwrap_or_default(),
+                }
+            })
+            .sorted_by_key(|item| i64::from(item.bot_id))
+            .collect(),
+        total_matches,
+    })
+}
+
+/// Attribute X vs attribute Y of every participant of the latest matches
+pub async fn scatter(
+    filter: MatchFilter,
+    options: ScatterOptions,
+    bot_tags: Arc<BotTags>,
+    pool: SqlitePool,
+) -> anyhow::Result<ScatterOverview> {
+    let ScatterOptions {
+        x,
+        y,
+        color_by,
+        bot_id,
+        max_matches,
+    } = options;
+    let mut extra_attrs = vec![];
+    let mut bot_attrs = vec![];
+    for axis in [&x, &y] {
+        match axis.level {
+            AttributeLevel::Bot => bot_attrs.push(axis.attribute_name.clone()),
+            AttributeLevel::Match => extra_attrs.push(MatchAttribute {
+                name: axis.attribute_name.clone(),
+                bot_id: None,
+                turn: None,
+                // don't care about value
+                value: MatchAttributeValue::Integer(0),
+            }),
+        }
+    }
+    let matches =
+        fetch_filtered_matches(&filter, extra_attrs, &bot_attrs, &bot_tags, &pool).await?;
+
+    let last_matches = matches
+        .iter()
+        .filter(|m| bot_id.is_none_or(|id| m.participants.iter().any(|p| p.bot_id == id)))
+        .k_largest_by_key(max_matches, |m| i64::from(m.id))
+        .sorted_by_key(|m| i64::from(m.id))
+        .collect_vec();
+
+    let axis_value = |m: &Match, axis: &ScatterAxis, id: BotId| {
+        let owner = match axis.level {
+            AttributeLevel::Bot => Some(id),
+            AttributeLevel::Match => None,
+        };
+        attribute_value(m, &axis.attribute_name, owner)
+    };
+
+    let mut res: HashMap<ChartSeries, Vec<ScatterPoint>> = HashMap::new();
+    for m in &last_matches {
+        for p in m.participants.iter().unique_by(|p| p.bot_id) {
+            if bot_id.is_some_and(|id| id != p.bot_id) {
+                continue;
+            }
+            let (Some(vx), Some(vy)) = (axis_value(m, &x, p.bot_id), axis_value(m, &y, p.bot_id))
+            else {
+                continue;
+            };
+            let series = match color_by {
+                ChartSplit::Bot => ChartSeries::Bot(p.bot_id),
+                ChartSplit::Outcome if p.rank == 0 => ChartSeries::Won,
+                ChartSplit::Outcome => ChartSeries::Lost,
+            };
+            res.entry(series).or_default().push(ScatterPoint {
+                match_id: m.id,
+                x: vx,
+                y: vy,
+            });
+        }
+    }
+
+    Ok(ScatterOverview {
+        items: res
+            .into_iter()
+            .map(|(series, points)| ScatterItem { series, points })
+            .collect(),
+        total_matches: last_matches.len() as _,
+    })
+}
+
+/// How the numeric attributes without turn, the bot ones and the match ones, differ
+/// between the matches the bot won and lost. Won means the first place, the matches
+/// where the bot played against itself are skipped
+pub async fn outcome_correlation(
+    filter: MatchFilter,
+    bot_id: BotId,
+    bot_tags: Arc<BotTags>,
+    pool: SqlitePool,
+) -> anyhow::Result<OutcomeCorrelationOverview> {
+    let names = db::fetch_attribute_names(&pool).await?;
+    let match_attrs = names
+        .iter()
+        .map(|name| MatchAttribute {
+            name: name.clone(),
+            bot_id: None,
+            turn: None,
+            // don't care about value
+            value: MatchAttributeValue::Integer(0),
+        })
+        .collect();
+    let matches = fetch_filtered_matches(&filter, match_attrs, &names, &bot_tags, &pool).await?;
+
+    let mut samples: HashMap<(AttributeLevel, &str), Vec<(f64, bool)>> = HashMap::new();
+    let mut total_matches = 0;
+    let mut wins = 0;
+    for m in &matches {
+        let Ok(p) = m
+            .participants
+            .iter()
+            .filter(|p| p.bot_id == bot_id)
+            .exactly_one()
+        else {
+            continue;
+        };
+        let won = p.rank == 0;
+        total_matches += 1;
+        if won {
+            wins += 1;
+        }
+
+        for attr in &m.attributes {
+            let level = match attr.bot_id {
+                _ if attr.turn.is_some() => continue,
+                None if attr.name == "seed" => continue,
+                None => AttributeLevel::Match,
+                Some(id) if id == bot_id => AttributeLevel::Bot,
+                Some(_) => continue,
+            };
+            let v = match attr.value {
+                MatchAttributeValue::Integer(x) => x as f64,
+                MatchAttributeValue::Float(x) => x,
+                MatchAttributeValue::String(_) => continue,
+            };
+            samples
+                .entry((level, attr.name.as_str()))
+                .or_default()
+                .push((v, won));
+        }
+    }
+
+    Ok(OutcomeCorrelationOverview {
+        items: samples
+            .into_iter()
+            .filter_map(|((level, name), samples)| {
+                Some(OutcomeCorrelationItem {
+                    attribute_name: name.to_string(),
+                    level,
+                    correlation: OutcomeCorrelation::compute(&samples)?,
+                })
+            })
+            .sorted_by(|a, b| {
+                b.correlation
+                    .effect_size
+                    .abs()
+                    .total_cmp(&a.correlation.effect_size.abs())
+            })
+            .collect(),
+        total_matches,
+        wins,
+    })
+}
+
+/// Matches passing the filter, in the order they were played, with the attributes
+/// needed by the filter, the `extra_attrs` and the `bot_attrs` without turn of every bot
+async fn fetch_filtered_matches(
+    filter: &MatchFilter,
+    extra_attrs: Vec<MatchAttribute>,
+    bot_attrs: &[String],
+    bot_tags: &BotTags,
+    pool: &SqlitePool,
+) -> anyhow::Result<Vec<Match>> {
+    let mut attrs = filter.needed_attributes();
+    attrs.extend(extra_attrs);
+    let matches = db::fetch_matches_with_attrs(pool, &attrs, bot_attrs).await?;
+    Ok(matches
+        .into_iter()
+        .filter(|m| filter.matches(m, bot_tags))
+        .collect())
+}
+
+/// Numeric value of the attribute without turn of the given bot, or the match-level one
+fn attribute_value(m: &Match, name: &str, bot_id: Option<BotId>) -> Option<f64> {
+    let attr = m
+        .attributes
+        .iter()
+        .find(|a| a.name == name && a.bot_id == bot_id && a.turn.is_none())?;
+    match attr.value {
+        MatchAttributeValue::Integer(x) => Some(x as f64),
+        MatchAttributeValue::Float(x) => Some(x),
+        MatchAttributeValue::String(_) => None,
     }
 }
@@ -1,10 +1,13 @@
 use anyhow::{bail, Context};
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
 use crate::{
-    matchmaking::MatchmakingAlgorithmConfig,
-    ranking::algorithms::{bradley_terry, elo, openskill, trueskill},
+    domain::{AttributeAggregation, BotTag},
+    matchmaking::{MatchmakingAlgorithmConfig, NewcomersConfig},
+    ranking::algorithms::{bradley_terry, elo, glicko2, openskill, plackett_luce, trueskill},
+    ranking::ErrorPolicy,
 };
 
 #[derive(Serialize, Deserialize)]
@@ -26,6 +29,8 @@ pub struct GameConfig {
     pub min_players: u32,
     pub max_players: u32,
     pub symmetric: bool,
+    /// i-th weight is for `min_players + i` players, uniform if omitted
+    pub player_count_weights: Option<Vec<f64>>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -33,15 +38,74 @@ pub struct MatchmakingConfig {
     #[serde(flatten)]
     pub algorithm: MatchmakingAlgorithmConfig,
     pub enabled_on_start: Option<bool>,
+    pub newcomers: Option<NewcomersConfig>,
+    /// only bots with this tag take part in matchmaking
+    pub group: Option<String>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RankingConfig {
+    #[serde(flatten)]
+    pub algorithm: RankingAlgorithmConfig,
+    pub per_player_count: Option<bool>,
+    pub error_policy: Option<ErrorPolicy>,
+    pub decay: Option<DecayConfig>,
+    /// online algorithms only, amount of random match orders to average the ratings over
+    pub permutations: Option<usize>,
+    /// bots with fixed ratings, other ratings are expressed relative to them
+    pub anchors: Option<Vec<AnchorConfig>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct AnchorConfig {
+    pub bot_id: i64,
+    pub rating: f64,
+}
+
+/// Makes older matches count less, exactly one half-life should be set
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct DecayConfig {
+    pub half_life_matches: Option<f64>,
+    pub half_life_days: Option<f64>,
+}
+
+impl RankingConfig {
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        if self.permutations == Some(0) {
+            bail!("ranking.permutations must be positive");
+        }
+        if let Some(anchors) = &self.anchors {
+            if anchors.iter().any(|a| !a.rating.is_finite()) {
+                bail!("ranking.anchors ratings must be finite");
+            }
+            if !anchors.iter().map(|a| a.bot_id).all_unique() {
+                bail!("ranking.anchors must not contain the same bot twice");
+            }
+        }
+        if let Some(decay) = &self.decay {
+            let half_life = match (decay.half_life_matches, decay.half_life_days) {
+                (Some(half_life), None) | (None, Some(half_life)) => half_life,
+                _ => bail!(
+                    "ranking.decay must have exactly one of half_life_matches and half_life_days"
+                ),
+            };
+            if !half_life.is_finite() || half_life <= 0.0 {
+                bail!("ranking.decay half-life must be positive");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(tag = "algorithm")]
-pub enum RankingConfig {
+pub enum RankingAlgorithmConfig {
     OpenSkill(openskill::Config),
     TrueSkill(trueskill::Config),
     Elo(elo::Config),
+    Glicko2(glicko2::Config),
     BradleyTerry(bradley_terry::Config),
+    PlackettLuce(plackett_luce::Config),
 }
 
 #[derive(Serialize, Deserialize)]
@@ -77,6 +141,12 @@ pub struct LogConfig {
 #[derive(Serialize, Deserialize, Default)]
 pub struct LeaderboardsConfig {
     pub uncertainty_coefficient: Option<f64>,
+    pub history_every_matches: Option<u64>,
+    pub history_interval_minutes: Option<u64>,
+    pub bootstrap_samples: Option<usize>,
+    pub refit_interval_ms: Option<u64>,
+    /// extra columns of the global leaderboard
+    pub columns: Option<Vec<AttributeAggregation>>,
 }
 
 impl Default for Config {
@@ -101,6 +171,41 @@ impl Config {
         if self.game.min_players > self.game.max_players {
             bail!("game.max_players must be not less than game.min_players");
         }
+        self.ranking.validate()?;
+        if let Some(group) = &self.matchmaking.group {
+            BotTag::try_from(group.clone()).context("matchmaking.group must be a valid bot tag")?;
+        }
+        if let Some(newcomers) = &self.matchmaking.newcomers {
+            if !(0.0..=1.0).contains(&newcomers.share) {
+                bail!("matchmaking.newcomers.share must be between 0.0 and 1.0");
+            }
+        }
+        if let Some(weights) = &self.game.player_count_weights {
+            let player_counts = self.game.max_players - self.game.min_players + 1;
+            if weights.len() != player_counts as usize {
+                bail!("game.player_count_weights must have exactly {player_counts} values, one per player count");
+            }
+            if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+                bail!("game.player_count_weights must be non-negative");
+            }
+            if weights.iter().sum::<f64>() <= 0.0 {
+                bail!("game.player_count_weights must have at least one positive value");
+            }
+        }
+        if self.leaderboards.history_every_matches == Some(0) {
+            bail!("leaderboards.history_every_matches must be positive");
+        }
+        if self.leaderboards.history_interval_minutes == Some(0) {
+            bail!("leaderboards.history_interval_minutes must be positive");
+        }
+        if self.leaderboards.bootstrap_samples == Some(0) {
+            bail!("leaderboards.bootstrap_samples must be positive");
+        }
+        for column in self.leaderboards.columns.iter().flatten() {
+            column
+                .validate()
+                .context("leaderboards.columns must be valid")?;
+        }
         for config in &self.workers {
             let WorkerConfig::Embedded(config) = config;
 
@@ -128,6 +233,7 @@ static DEFAULT_CONFIG_CONTENT: &str = include_str!(concat!(
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::domain::AggregateFunction;
 
     #[test]
     fn default_config_is_valid() {
@@ -212,4 +318,176 @@ mod test {
             "Should fail because V2 is missing 'min_matches_per_pair'"
         );
     }
+
+    #[test]
+    fn test_matchmaking_newcomers() {
+        let toml_str = r#"
+            algorithm = "v2"
+            min_matches_per_pair = 20
+
+            [newcomers]
+            max_age_minutes = 30
+            share = 0.5
+        "#;
+
+        let config: MatchmakingConfig =
+            toml::from_str(toml_str).expect("Should parse newcomers config");
+
+        assert!(matches!(
+            config.algorithm,
+            MatchmakingAlgorithmConfig::V2(_)
+        ));
+        let newcomers = config.newcomers.expect("newcomers should be set");
+        assert_eq!(newcomers.max_age_minutes, Some(30));
+        assert!(newcomers.max_matches.is_none());
+        assert_eq!(newcomers.share, 0.5);
+        assert!(newcomers.top_k.is_none());
+    }
+
+    #[test]
+    fn test_newcomers_share_validation() {
+        let mut config = Config::default();
+        config.matchmaking.newcomers = Some(NewcomersConfig {
+            max_age_minutes: Some(30),
+            max_matches: None,
+            share: 1.0,
+            top_k: None,
+        });
+        assert!(config.validate().is_ok());
+
+        config.matchmaking.newcomers.as_mut().unwrap().share = 1.5;
+        assert!(config.validate().is_err());
+
+        config.matchmaking.newcomers.as_mut().unwrap().share = -0.1;
+        assert!(config.validate().is_err());
+
+        config.matchmaking.newcomers.as_mut().unwrap().share = f64::NAN;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_player_count_weights_validation() {
+        let mut config = Config::default();
+        config.game.min_players = 2;
+        config.game.max_players = 4;
+
+        config.game.player_count_weights = Some(vec![0.5, 0.3, 0.2]);
+        assert!(config.validate().is_ok());
+
+        config.game.player_count_weights = Some(vec![0.5, 0.5]);
+        assert!(config.validate().is_err());
+
+        config.game.player_count_weights = Some(vec![0.0, 0.0, 0.0]);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_ranking_per_player_count() {
+        let toml_str = r#"
+            algorithm = "Elo"
+            k = 16.0
+            per_player_count = true
+        "#;
+
+        let config: RankingConfig = toml::from_str(toml_str).expect("Should parse ranking config");
+
+        assert!(matches!(config.algorithm, RankingAlgorithmConfig::Elo(_)));
+        assert_eq!(config.per_player_count, Some(true));
+    }
+
+    #[test]
+    fn test_ranking_error_policy() {
+        let toml_str = r#"
+            algorithm = "BradleyTerry"
+            error_policy = "exclude_opponent_gain"
+        "#;
+
+        let config: RankingConfig = toml::from_str(toml_str).expect("Should parse ranking config");
+
+        assert_eq!(config.error_policy, Some(ErrorPolicy::ExcludeOpponentGain));
+    }
+
+    #[test]
+    fn test_ranking_decay() {
+        let toml_str = r#"
+            algorithm = "BradleyTerry"
+
+            [decay]
+            half_life_days = 7
+        "#;
+
+        let config: RankingConfig = toml::from_str(toml_str).expect("Should parse ranking config");
+
+        assert!(config.validate().is_ok());
+        let decay = config.decay.unwrap();
+        assert_eq!(decay.half_life_days, Some(7.0));
+        assert_eq!(decay.half_life_matches, None);
+
+        let toml_str = r#"
+            algorithm = "BradleyTerry"
+
+            [decay]
+            half_life_days = 7
+            half_life_matches = 100
+        "#;
+
+        let config: RankingConfig = toml::from_str(toml_str).expect("Should parse ranking config");
+
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_ranking_anchors() {
+        let toml_str = r#"
+            algorithm = "BradleyTerry"
+
+            [[anchors]]
+            bot_id = 3
+            rating = 0
+
+            [[anchors]]
+            bot_id = 5
+            rating = 200
+        "#;
+
+        let config: RankingConfig = toml::from_str(toml_str).expect("Should parse ranking config");
+
+        assert!(config.validate().is_ok());
+        let anchors = config.anchors.unwrap();
+        assert_eq!(anchors.len(), 2);
+        assert_eq!(anchors[1].bot_id, 5);
+        assert_eq!(anchors[1].rating, 200.0);
+    }
+
+    #[test]
+    fn test_ranking_permutations() {
+        let toml_str = r#"
+            algorithm = "OpenSkill"
+            permutations = 10
+        "#;
+
+        let config: RankingConfig = toml::from_str(toml_str).expect("Should parse ranking config");
+
+        assert_eq!(config.permutations, Some(10));
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_leaderboards_columns() {
+        let toml_str = r#"
+            [[columns]]
+            attribute = "score"
+            function = "avg"
+
+            [[columns]]
+            attribute = "turns"
+            function = { percentile = 90 }
+        "#;
+
+        let config: LeaderboardsConfig = toml::from_str(toml_str).expect("Should parse columns");
+
+        let columns = config.columns.unwrap();
+        assert_eq!(columns[0].function, AggregateFunction::Avg);
+        assert_eq!(columns[1].function, AggregateFunction::Percentile(90.0));
+    }
 }
@@ -1,6 +1,7 @@
 use crate::domain::{
-    Bot, BotId, Build, BuildResult, BuildStatus, Leaderboard, LeaderboardId, Match, MatchAttribute,
-    MatchAttributeValue, MatchId, Participant,
+    quote_sql_string, Bot, BotId, BotTag, Build, BuildResult, BuildStatus, Leaderboard,
+    LeaderboardId, Match, MatchAttribute, MatchAttributeValue, MatchId, Participant, Rating,
+    RatingSnapshot, Tournament, TournamentFormat, TournamentGame, TournamentGameId, TournamentId,
 };
 use anyhow::bail;
 use chrono::{DateTime, Utc};
@@ -8,7 +9,7 @@ use indoc::{formatdoc, indoc};
 use itertools::Itertools;
 use sqlx::SqlitePool;
 use sqlx::{sqlite::SqliteConnectOptions, ConnectOptions};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::time::Duration;
 use tracing::warn;
@@ -22,11 +23,28 @@ struct BotsRow {
     pub created_at: DateTime<Utc>,
 }
 
+#[derive(sqlx::FromRow)]
+struct BotTagsRow {
+    pub bot_id: i64,
+    pub tag: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct RatingSnapshotsRow {
+    pub bot_id: i64,
+    pub rating_mu: f64,
+    pub rating_sigma: f64,
+    pub rank: u32,
+    pub total_matches: i64,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(sqlx::FromRow)]
 struct MatchesRow {
     pub id: i64,
     pub seed: i64,
     pub participant_cnt: u8,
+    pub created_at: DateTime<Utc>,
 }
 
 #[derive(sqlx::FromRow)]
@@ -63,8 +81,106 @@ pub struct LeaderboardsRow {
     pub id: i64,
     pub name: String,
     pub filter: String,
+    pub ranking: Option<String>,
+    pub uncertainty_coefficient: Option<f64>,
+    pub columns: Option<String>,
 }
 
+#[derive(sqlx::FromRow)]
+pub struct TournamentsRow {
+    pub id: i64,
+    pub name: String,
+    pub format: String,
+    pub rounds: u32,
+    pub games_per_pair: u32,
+    pub finished: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct TournamentParticipantsRow {
+    pub tournament_id: i64,
+    pub bot_id: i64,
+    pub index: u32,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct TournamentGamesRow {
+    pub id: i64,
+    pub tournament_id: i64,
+    pub round: u32,
+    pub seed: i64,
+    pub bot_id_1: i64,
+    pub bot_id_2: Option<i64>,
+    pub rank_1: Option<u8>,
+    pub rank_2: Option<u8>,
+}
+
+impl TryFrom<TournamentGamesRow> for TournamentGame {
+    type Error = anyhow::Error;
+
+    fn try_from(row: TournamentGamesRow) -> Result<Self, Self::Error> {
+        let (bot_ids, ranks) = match (row.bot_id_2, row.rank_1, row.rank_2) {
+            (None, Some(r1), None) => (vec![row.bot_id_1.into()], Some(vec![r1])),
+            (Some(b2), None, None) => (vec![row.bot_id_1.into(), b2.into()], None),
+            (Some(b2), Some(r1), Some(r2)) => {
+                (vec![row.bot_id_1.into(), b2.into()], Some(vec![r1, r2]))
+            }
+            _ => bail!("unexpected tournament game state in db"),
+        };
+        Ok(TournamentGame {
+            id: row.id.into(),
+            round: row.round,
+            seed: row.seed,
+            bot_ids,
+            ranks,
+        })
+    }
+}
+
+impl
+    TryFrom<(
+        TournamentsRow,
+        Vec<TournamentParticipantsRow>,
+        Vec<TournamentGamesRow>,
+    )> for Tournament
+{
+    type Error = anyhow::Error;
+
+    fn try_from(
+        (t, mut ps, gs): (
+            TournamentsRow,
+            Vec<TournamentParticipantsRow>,
+            Vec<TournamentGamesRow>,
+        ),
+    ) -> Result<Self, Self::Error> {
+        let format = match t.format.as_str() {
+            FORMAT_ROUND_ROBIN => TournamentFormat::RoundRobin,
+            FORMAT_SWISS => TournamentFormat::Swiss { rounds: t.rounds },
+            _ => bail!("unexpected tournament format in db"),
+        };
+        ps.sort_by_key(|p| p.index);
+        Ok(Tournament {
+            id: t.id.into(),
+            name: t.name.try_into()?,
+            format,
+            games_per_pair: t.games_per_pair,
+            bot_ids: ps.into_iter().map(|p| p.bot_id.into()).collect(),
+            games: gs
+                .into_iter()
+                .map(TournamentGame::try_from)
+                .collect::<Result<_, _>>()?,
+            finished: t.finished,
+            created_at: t.created_at,
+            // filled by the arena from the bots which don't exist anymore
+            withdrawn: HashSet::new(),
+        })
+    }
+}
+
+const FORMAT_ROUND_ROBIN: &str = "round_robin";
+const FORMAT_SWISS: &str = "swiss";
+
 impl TryFrom<LeaderboardsRow> for Leaderboard {
     type Error = anyhow::Error;
 
@@ -73,10 +189,32 @@ impl TryFrom<LeaderboardsRow> for Leaderboard {
             id: row.id.into(),
             name: row.name.try_into()?,
             filter: row.filter.parse()?,
+            ranking: row
+                .ranking
+                .map(|ranking| serde_json::from_str(&ranking))
+                .transpose()?,
+            uncertainty_coefficient: row.uncertainty_coefficient,
+            columns: row
+                .columns
+                .map(|columns| serde_json::from_str(&columns))
+                .transpose()?
+                .unwrap_or_default(),
         })
     }
 }
 
+impl From<RatingSnapshotsRow> for RatingSnapshot {
+    fn from(row: RatingSnapshotsRow) -> Self {
+        RatingSnapshot {
+            bot_id: row.bot_id.into(),
+            rating: Rating::new(row.rating_mu, row.rating_sigma),
+            rank: row.rank as _,
+            total_matches: row.total_matches as _,
+            created_at: row.created_at,
+        }
+    }
+}
+
 impl TryFrom<MatchAttributesJoinedRow> for MatchAttribute {
     type Error = anyhow::Error;
 
@@ -124,6 +262,7 @@ impl TryFrom<BotsRow> for Bot {
             source_code: bot.source_code.try_into()?,
             language: bot.language.try_into()?,
             created_at: bot.created_at,
+            tags: vec![],
         })
     }
 }
@@ -181,6 +320,7 @@ impl
                         .ok()
                 })
                 .collect(),
+            created_at: m.created_at,
         })
     }
 }
@@ -266,6 +406,17 @@ pub async fn delete_bot(pool: &SqlitePool, id: BotId) -> anyhow::Result<()> {
 }
 
 pub async fn fetch_bots(pool: &SqlitePool) -> anyhow::Result<Vec<Bot>> {
+    let mut tags: HashMap<i64, Vec<BotTag>> = HashMap::new();
+    for row in sqlx::query_as::<_, BotTagsRow>("SELECT * from bot_tags ORDER BY tag")
+        .fetch_all(pool)
+        .await?
+    {
+        match BotTag::try_from(row.tag) {
+            Ok(tag) => tags.entry(row.bot_id).or_default().push(tag),
+            Err(e) => warn!("Invalid db data (bot {} tag): {}. Skipping.", row.bot_id, e),
+        }
+    }
+
     let bots = sqlx::query_as::<_, BotsRow>("SELECT * from bots")
         .fetch_all(pool)
         .await?
@@ -276,10 +427,36 @@ pub async fn fetch_bots(pool: &SqlitePool) -> anyhow::Result<Vec<Bot>> {
                 .inspect_err(|e| warn!("Invalid db data (bot {}): {}. Skipping.", id, e))
                 .ok()
         })
+        .map(|mut bot| {
+            bot.tags = tags.remove(&bot.id.into()).unwrap_or_default();
+            bot
+        })
         .collect();
     Ok(bots)
 }
 
+/// replaces all the tags of the bot
+pub async fn persist_bot_tags(pool: &SqlitePool, bot: &Bot) -> anyhow::Result<()> {
+    assert_ne!(bot.id, BotId::UNINITIALIZED);
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM bot_tags WHERE bot_id = $1")
+        .bind::<i64>(bot.id.into())
+        .execute(&mut *tx)
+        .await?;
+
+    for tag in &bot.tags {
+        sqlx::query("INSERT INTO bot_tags (bot_id, tag) VALUES ($1, $2)")
+            .bind::<i64>(bot.id.into())
+            .bind::<&str>(tag)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
 pub async fn fetch_builds(pool: &SqlitePool) -> anyhow::Result<Vec<Build>> {
     let builds = sqlx::query_as::<_, BuildsRow>("SELECT * from builds")
         .fetch_all(pool)
@@ -331,9 +508,10 @@ pub async fn create_match(pool: &SqlitePool, m: &Match) -> anyhow::Result<MatchI
     let mut tx = pool.begin().await?;
 
     let match_id: MatchId =
-        sqlx::query("INSERT INTO matches (seed, participant_cnt) VALUES ($1, $2)")
+        sqlx::query("INSERT INTO matches (seed, participant_cnt, created_at) VALUES ($1, $2, $3)")
             .bind::<i64>(m.seed)
             .bind::<u8>(m.participants.len() as _)
+            .bind::<DateTime<Utc>>(m.created_at)
             .execute(&mut *tx)
             .await?
             .last_insert_rowid()
@@ -407,11 +585,14 @@ pub async fn create_match(pool: &SqlitePool, m: &Match) -> anyhow::Result<MatchI
     Ok(match_id)
 }
 
+/// Turn attributes with the given name along with their match ids, bot-level ones
+/// if `bot_level` is set, match-level ones otherwise
 pub async fn fetch_turn_attributes(
     pool: &SqlitePool,
     match_ids: &[MatchId],
     attribute_name: &str,
-) -> anyhow::Result<Vec<MatchAttribute>> {
+    bot_level: bool,
+) -> anyhow::Result<Vec<(MatchId, MatchAttribute)>> {
     if match_ids.is_empty() {
         return Ok(vec![]);
     }
@@ -421,6 +602,11 @@ pub async fn fetch_turn_attributes(
         .map::<i64, _>(|&id| id.into())
         .map(|id| id.to_string())
         .join(",");
+    let bot_condition = if bot_level {
+        "ma.bot_id IS NOT NULL"
+    } else {
+        "ma.bot_id IS NULL"
+    };
 
     let sql = formatdoc! {
         "SELECT
@@ -434,7 +620,7 @@ pub async fn fetch_turn_attributes(
         FROM match_attributes ma
         INNER JOIN match_attribute_names n ON (n.id = ma.name_id)
         WHERE n.name = $1
-        AND ma.bot_id IS NOT NULL
+        AND {bot_condition}
         AND ma.turn IS NOT NULL
         AND ma.match_id IN ({match_ids_joined})"
     };
@@ -444,7 +630,13 @@ pub async fn fetch_turn_attributes(
         .fetch_all(pool)
         .await?;
 
-    let res = res.into_iter().flat_map(TryInto::try_into).collect();
+    let res = res
+        .into_iter()
+        .flat_map(|row| {
+            let match_id = row.match_id.into();
+            MatchAttribute::try_from(row).map(|attr| (match_id, attr))
+        })
+        .collect();
 
     Ok(res)
 }
@@ -499,11 +691,23 @@ pub async fn wipe_old_matches<F: Fn(usize) -> bool>(
     Ok(amount_to_delete as usize)
 }
 
+/// names of all the match attributes ever recorded
+pub async fn fetch_attribute_names(pool: &SqlitePool) -> anyhow::Result<Vec<String>> {
+    let names: Vec<(String,)> = sqlx::query_as("SELECT name FROM match_attribute_names")
+        .fetch_all(pool)
+        .await?;
+
+    Ok(names.into_iter().map(|(name,)| name).collect())
+}
+
+/// `bot_attrs` are fetched for every bot, only the values without turn
 pub async fn fetch_matches_with_attrs(
     pool: &SqlitePool,
     attrs: &[MatchAttribute],
+    bot_attrs: &[String],
 ) -> anyhow::Result<Vec<Match>> {
-    let matches: Vec<MatchesRow> = sqlx::query_as("SELECT * from matches")
+    // replayed by online ranking algorithms, so the order must be stable
+    let matches: Vec<MatchesRow> = sqlx::query_as("SELECT * from matches ORDER BY id")
         .fetch_all(pool)
         .await?;
 
@@ -511,10 +715,9 @@ pub async fn fetch_matches_with_attrs(
         .fetch_all(pool)
         .await?;
 
-    let attributes: Vec<MatchAttributesJoinedRow> = if attrs.is_empty() {
-        vec![]
-    } else {
-        let names_joined = attrs.iter().map(|a| format!("'{}'", a.name)).join(",");
+    let mut conditions = vec![];
+    if !attrs.is_empty() {
+        let names_joined = attrs.iter().map(|a| quote_sql_string(&a.name)).join(",");
         let turns = attrs.iter().flat_map(|a| a.turn).collect_vec();
         let turns_condition = if turns.is_empty() {
             "ma.turn IS NULL".to_string()
@@ -537,7 +740,24 @@ pub async fn fetch_matches_with_attrs(
                 bot_ids.iter().join(",")
             )
         };
+        conditions.push(format!(
+            "(n.name IN ({names_joined}) AND {turns_condition} AND {bots_condition})"
+        ));
+    }
+    if !bot_attrs.is_empty() {
+        let names_joined = bot_attrs
+            .iter()
+            .map(|name| quote_sql_string(name))
+            .join(",");
+        conditions.push(format!(
+            "(n.name IN ({names_joined}) AND ma.turn IS NULL AND ma.bot_id IS NOT NULL)"
+        ));
+    }
 
+    let attributes: Vec<MatchAttributesJoinedRow> = if conditions.is_empty() {
+        vec![]
+    } else {
+        let condition = conditions.join(" OR ");
         let sql = formatdoc! {
             "SELECT
                 n.name as name,
@@ -550,40 +770,189 @@ pub async fn fetch_matches_with_attrs(
             FROM match_attributes ma
             INNER JOIN match_attribute_names n ON (n.id = ma.name_id)
             LEFT JOIN match_attribute_string_values v ON (v.id = ma.value_string_id)
-            WHERE n.name IN ({names_joined}) AND {turns_condition} AND {bots_condition}"
+            WHERE {condition}"
         };
         sqlx::query_as(&sql).fetch_all(pool).await?
     };
 
-    let mut combined = HashMap::with_capacity(matches.len());
-    for m in matches {
-        combined.insert(m.id, (m, vec![], vec![]));
+    Ok(assemble_matches(matches, participations, attributes))
+}
+
+// matches fetched at once when paging through all of them
+const MATCHES_BATCH_SIZE: usize = 1000;
+// filters which can't be fully checked in SQL need several batches to fill a limited page
+const MIN_MATCHES_BATCH_SIZE: usize = 100;
+
+/// Newest matches first, accepted by `keep`, fetched in batches until `limit` of them
+/// are collected or the matches are exhausted. The rest of the arguments are the same
+/// as for `fetch_matches_page`. Returns the matches and whether the `limit` was reached.
+pub async fn fetch_matches_paged(
+    pool: &SqlitePool,
+    condition: Option<&str>,
+    bot_ids: &[BotId],
+    before: Option<MatchId>,
+    limit: Option<usize>,
+    turn_attrs: &[MatchAttribute],
+    keep: impl Fn(&Match) -> bool,
+) -> anyhow::Result<(Vec<Match>, bool)> {
+    let batch_size = limit.map_or(MATCHES_BATCH_SIZE, |limit| {
+        limit.max(MIN_MATCHES_BATCH_SIZE)
+    });
+
+    let mut matches = vec![];
+    let mut before = before;
+    loop {
+        let batch =
+            fetch_matches_page(pool, condition, bot_ids, before, batch_size, turn_attrs).await?;
+        let exhausted = batch.len() < batch_size;
+        before = batch.last().map(|m| m.id);
+
+        for m in batch {
+            if !keep(&m) {
+                continue;
+            }
+            matches.push(m);
+            if limit == Some(matches.len()) {
+                return Ok((matches, true));
+            }
+        }
+
+        if exhausted {
+            return Ok((matches, false));
+        }
     }
-    for p in participations {
-        let target = combined.get_mut(&p.match_id);
-        let Some(target) = target else {
-            continue;
-        };
-        target.1.push(p);
+}
+
+/// Newest matches first. `condition` is an SQL condition on the `matches` table aliased `m`,
+/// every bot of `bot_ids` takes part in the matches.
+/// Only the attributes without turn are fetched, besides the `turn_attrs`.
+async fn fetch_matches_page(
+    pool: &SqlitePool,
+    condition: Option<&str>,
+    bot_ids: &[BotId],
+    before: Option<MatchId>,
+    limit: usize,
+    turn_attrs: &[MatchAttribute],
+) -> anyhow::Result<Vec<Match>> {
+    let mut conditions = vec![];
+    if let Some(condition) = condition {
+        conditions.push(condition.to_string());
     }
-    for attr in attributes {
-        let target = combined.get_mut(&attr.match_id);
-        let Some(target) = target else {
-            continue;
-        };
-        target.2.push(attr);
+    for &bot_id in bot_ids {
+        conditions.push(format!(
+            "m.id IN (SELECT match_id FROM participations WHERE bot_id = {})",
+            i64::from(bot_id)
+        ));
+    }
+    if let Some(before) = before {
+        conditions.push(format!("m.id < {}", i64::from(before)));
     }
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
 
-    let matches = combined
-        .into_values()
-        .filter_map(|item| {
-            let id = item.0.id;
-            Match::try_from(item)
+    let sql = format!("SELECT m.* FROM matches m {where_clause} ORDER BY m.id DESC LIMIT {limit}");
+    let matches: Vec<MatchesRow> = sqlx::query_as(&sql).fetch_all(pool).await?;
+    if matches.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let match_ids_joined = matches.iter().map(|m| m.id).join(",");
+
+    let sql = format!("SELECT * FROM participations WHERE match_id IN ({match_ids_joined})");
+    let participations: Vec<ParticipationsRow> = sqlx::query_as(&sql).fetch_all(pool).await?;
+
+    let turns_condition = std::iter::once("ma.turn IS NULL".to_string())
+        .chain(turn_attrs.iter().filter_map(|a| {
+            a.turn.map(|turn| {
+                format!(
+                    "(n.name = {} AND ma.turn = {turn})",
+                    quote_sql_string(&a.name)
+                )
+            })
+        }))
+        .join(" OR ");
+    let sql = formatdoc! {
+        "SELECT
+            n.name as name,
+            ma.match_id as match_id,
+            ma.bot_id as bot_id,
+            ma.turn as turn,
+            ma.value_int as value_int,
+            ma.value_float as value_float,
+            v.value as value_string
+        FROM match_attributes ma
+        INNER JOIN match_attribute_names n ON (n.id = ma.name_id)
+        LEFT JOIN match_attribute_string_values v ON (v.id = ma.value_string_id)
+        WHERE ma.match_id IN ({match_ids_joined}) AND ({turns_condition})"
+    };
+    let attributes: Vec<MatchAttributesJoinedRow> = sqlx::query_as(&sql).fetch_all(pool).await?;
+
+    Ok(assemble_matches(matches, participations, attributes))
+}
+
+/// Match with all its attributes
+pub async fn fetch_match(pool: &SqlitePool, id: MatchId) -> anyhow::Result<Option<Match>> {
+    let matches: Vec<MatchesRow> = sqlx::query_as("SELECT * FROM matches WHERE id = $1")
+        .bind::<i64>(id.into())
+        .fetch_all(pool)
+        .await?;
+    if matches.is_empty() {
+        return Ok(None);
+    }
+
+    let participations: Vec<ParticipationsRow> =
+        sqlx::query_as("SELECT * FROM participations WHERE match_id = $1")
+            .bind::<i64>(id.into())
+            .fetch_all(pool)
+            .await?;
+
+    const SQL: &str = indoc! {
+        "SELECT
+            n.name as name,
+            ma.match_id as match_id,
+            ma.bot_id as bot_id,
+            ma.turn as turn,
+            ma.value_int as value_int,
+            ma.value_float as value_float,
+            v.value as value_string
+        FROM match_attributes ma
+        INNER JOIN match_attribute_names n ON (n.id = ma.name_id)
+        LEFT JOIN match_attribute_string_values v ON (v.id = ma.value_string_id)
+        WHERE ma.match_id = $1"
+    };
+    let attributes: Vec<MatchAttributesJoinedRow> = sqlx::query_as(SQL)
+        .bind::<i64>(id.into())
+        .fetch_all(pool)
+        .await?;
+
+    Ok(assemble_matches(matches, participations, attributes)
+        .into_iter()
+        .next())
+}
+
+/// Keeps the order of `matches`, invalid ones are skipped
+fn assemble_matches(
+    matches: Vec<MatchesRow>,
+    participations: Vec<ParticipationsRow>,
+    attributes: Vec<MatchAttributesJoinedRow>,
+) -> Vec<Match> {
+    let mut participations = participations.into_iter().into_group_map_by(|p| p.match_id);
+    let mut attributes = attributes.into_iter().into_group_map_by(|a| a.match_id);
+
+    matches
+        .into_iter()
+        .filter_map(|m| {
+            let id = m.id;
+            let ps = participations.remove(&id).unwrap_or_default();
+            let ar = attributes.remove(&id).unwrap_or_default();
+            Match::try_from((m, ps, ar))
                 .inspect_err(|e| warn!("Invalid db data (match {}): {}. Skipping.", id, e))
                 .ok()
         })
-        .collect();
-    Ok(matches)
+        .collect()
 }
 
 pub async fn persist_leaderboard(
@@ -604,13 +973,16 @@ async fn insert_leaderboard(
 ) -> anyhow::Result<LeaderboardId> {
     assert_eq!(leaderboard.id, LeaderboardId::UNINITIALIZED);
     const SQL: &str = indoc! {"
-        INSERT INTO leaderboards (name, filter) \
-        VALUES ($1, $2) \
+        INSERT INTO leaderboards (name, filter, ranking, uncertainty_coefficient, columns) \
+        VALUES ($1, $2, $3, $4, $5) \
     "};
 
     let res = sqlx::query(SQL)
         .bind::<&str>(&leaderboard.name)
         .bind::<&str>(&leaderboard.filter.to_string())
+        .bind::<Option<String>>(serialize_ranking(leaderboard)?)
+        .bind::<Option<f64>>(leaderboard.uncertainty_coefficient)
+        .bind::<Option<String>>(serialize_columns(leaderboard)?)
         .execute(pool)
         .await?;
 
@@ -621,13 +993,16 @@ async fn insert_leaderboard(
 async fn update_leaderboard(pool: &SqlitePool, leaderboard: &Leaderboard) -> anyhow::Result<()> {
     assert_ne!(leaderboard.id, LeaderboardId::UNINITIALIZED);
     const SQL: &str = indoc! {"
-        UPDATE leaderboards SET name = $1, filter = $2 \
-        WHERE id = $3"
+        UPDATE leaderboards SET name = $1, filter = $2, ranking = $3, uncertainty_coefficient = $4, \
+        columns = $5 WHERE id = $6"
     };
 
     let res = sqlx::query(SQL)
         .bind::<&str>(&leaderboard.name)
         .bind::<&str>(&leaderboard.filter.to_string())
+        .bind::<Option<String>>(serialize_ranking(leaderboard)?)
+        .bind::<Option<f64>>(leaderboard.uncertainty_coefficient)
+        .bind::<Option<String>>(serialize_columns(leaderboard)?)
         .bind::<i64>(leaderboard.id.into())
         .execute(pool)
         .await?;
@@ -636,9 +1011,149 @@ async fn update_leaderboard(pool: &SqlitePool, leaderboard: &Leaderboard) -> any
     Ok(())
 }
 
+fn serialize_ranking(leaderboard: &Leaderboard) -> anyhow::Result<Option<String>> {
+    Ok(leaderboard
+        .ranking
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()?)
+}
+
+fn serialize_columns(leaderboard: &Leaderboard) -> anyhow::Result<Option<String>> {
+    if leaderboard.columns.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::to_string(&leaderboard.columns)?))
+}
+
 pub async fn delete_leaderboard(pool: &SqlitePool, id: LeaderboardId) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
     sqlx::query("DELETE FROM leaderboards WHERE id = $1")
         .bind::<i64>(id.into())
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM rating_snapshots WHERE leaderboard_id = $1")
+        .bind::<i64>(id.into())
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn persist_rating_snapshots(
+    pool: &SqlitePool,
+    leaderboard_id: LeaderboardId,
+    snapshots: &[RatingSnapshot],
+) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    for snapshot in snapshots {
+        const SQL: &str = indoc! {"
+            INSERT INTO rating_snapshots (leaderboard_id, bot_id, rating_mu, rating_sigma, rank, total_matches, created_at) \
+            VALUES ($1, $2, $3, $4, $5, $6, $7) \
+        "};
+
+        sqlx::query(SQL)
+            .bind::<i64>(leaderboard_id.into())
+            .bind::<i64>(snapshot.bot_id.into())
+            .bind::<f64>(snapshot.rating.mu)
+            .bind::<f64>(snapshot.rating.sigma)
+            .bind::<u32>(snapshot.rank as _)
+            .bind::<i64>(snapshot.total_matches as _)
+            .bind::<DateTime<Utc>>(snapshot.created_at)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// ordered by time
+pub async fn fetch_rating_snapshots(
+    pool: &SqlitePool,
+    leaderboard_id: LeaderboardId,
+) -> anyhow::Result<Vec<RatingSnapshot>> {
+    const SQL: &str = indoc! {"
+        SELECT bot_id, rating_mu, rating_sigma, rank, total_matches, created_at \
+        FROM rating_snapshots \
+        WHERE leaderboard_id = $1 \
+        ORDER BY created_at, rowid \
+    "};
+
+    let snapshots = sqlx::query_as::<_, RatingSnapshotsRow>(SQL)
+        .bind::<i64>(leaderboard_id.into())
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(RatingSnapshot::from)
+        .collect();
+    Ok(snapshots)
+}
+
+pub async fn delete_rating_snapshots(
+    pool: &SqlitePool,
+    leaderboard_id: LeaderboardId,
+) -> anyhow::Result<()> {
+    sqlx::query("DELETE FROM rating_snapshots WHERE leaderboard_id = $1")
+        .bind::<i64>(leaderboard_id.into())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// total matches and time of the latest rating snapshot of every leaderboard
+pub async fn fetch_last_rating_snapshots(
+    pool: &SqlitePool,
+) -> anyhow::Result<Vec<(LeaderboardId, u64, DateTime<Utc>)>> {
+    const SQL: &str = indoc! {"
+        SELECT leaderboard_id, total_matches, MAX(created_at) \
+        FROM rating_snapshots \
+        GROUP BY leaderboard_id \
+    "};
+
+    let rows = sqlx::query_as::<_, (i64, i64, DateTime<Utc>)>(SQL)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|(id, total_matches, created_at)| {
+            // the global leaderboard has the uninitialized id
+            let id = match id {
+                0 => LeaderboardId::UNINITIALIZED,
+                id => id.into(),
+            };
+            (id, total_matches as u64, created_at)
+        })
+        .collect();
+    Ok(rows)
+}
+
+/// ranking config as JSON and uncertainty coefficient the global rating history was recorded with
+pub async fn fetch_global_rating_settings(
+    pool: &SqlitePool,
+) -> anyhow::Result<Option<(String, f64)>> {
+    let settings = sqlx::query_as::<_, (String, f64)>(
+        "SELECT ranking, uncertainty_coefficient FROM global_rating_settings WHERE id = 0",
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(settings)
+}
+
+pub async fn persist_global_rating_settings(
+    pool: &SqlitePool,
+    ranking: &str,
+    uncertainty_coefficient: f64,
+) -> anyhow::Result<()> {
+    const SQL: &str = indoc! {"
+        INSERT INTO global_rating_settings (id, ranking, uncertainty_coefficient) \
+        VALUES (0, $1, $2) \
+        ON CONFLICT (id) DO UPDATE SET ranking = $1, uncertainty_coefficient = $2 \
+    "};
+
+    sqlx::query(SQL)
+        .bind::<&str>(ranking)
+        .bind::<f64>(uncertainty_coefficient)
         .execute(pool)
         .await?;
     Ok(())
@@ -658,3 +1173,161 @@ pub async fn fetch_leaderboards(pool: &SqlitePool) -> anyhow::Result<Vec<Leaderb
         .collect();
     Ok(leaderboards)
 }
+
+pub async fn fetch_tournaments(pool: &SqlitePool) -> anyhow::Result<Vec<Tournament>> {
+    let tournaments: Vec<TournamentsRow> = sqlx::query_as("SELECT * from tournaments")
+        .fetch_all(pool)
+        .await?;
+
+    let participants: Vec<TournamentParticipantsRow> =
+        sqlx::query_as("SELECT * from tournament_participants")
+            .fetch_all(pool)
+            .await?;
+
+    let games: Vec<TournamentGamesRow> =
+        sqlx::query_as("SELECT * from tournament_games ORDER BY id")
+            .fetch_all(pool)
+            .await?;
+
+    let mut combined = HashMap::with_capacity(tournaments.len());
+    for t in tournaments {
+        combined.insert(t.id, (t, vec![], vec![]));
+    }
+    for p in participants {
+        if let Some(target) = combined.get_mut(&p.tournament_id) {
+            target.1.push(p);
+        }
+    }
+    for g in games {
+        if let Some(target) = combined.get_mut(&g.tournament_id) {
+            target.2.push(g);
+        }
+    }
+
+    let tournaments = combined
+        .into_values()
+        .filter_map(|item| {
+            let id = item.0.id;
+            Tournament::try_from(item)
+                .inspect_err(|e| warn!("Invalid db data (tournament {}): {}. Skipping.", id, e))
+                .ok()
+        })
+        .sorted_by_key(|t| i64::from(t.id))
+        .collect();
+    Ok(tournaments)
+}
+
+pub async fn persist_tournament(
+    pool: &SqlitePool,
+    tournament: &mut Tournament,
+) -> anyhow::Result<()> {
+    if tournament.id == TournamentId::UNINITIALIZED {
+        tournament.id = insert_tournament(pool, tournament).await?;
+    } else {
+        update_tournament(pool, tournament).await?;
+    }
+    Ok(())
+}
+
+async fn insert_tournament(
+    pool: &SqlitePool,
+    tournament: &Tournament,
+) -> anyhow::Result<TournamentId> {
+    assert_eq!(tournament.id, TournamentId::UNINITIALIZED);
+    let mut tx = pool.begin().await?;
+
+    const SQL: &str = indoc! {"
+        INSERT INTO tournaments (name, format, rounds, games_per_pair, finished, created_at) \
+        VALUES ($1, $2, $3, $4, $5, $6) \
+    "};
+
+    let format = match tournament.format {
+        TournamentFormat::RoundRobin => FORMAT_ROUND_ROBIN,
+        TournamentFormat::Swiss { .. } => FORMAT_SWISS,
+    };
+
+    let id: TournamentId = sqlx::query(SQL)
+        .bind::<&str>(&tournament.name)
+        .bind::<&str>(format)
+        .bind::<u32>(tournament.total_rounds())
+        .bind::<u32>(tournament.games_per_pair)
+        .bind::<bool>(tournament.finished)
+        .bind::<DateTime<Utc>>(tournament.created_at)
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid()
+        .into();
+
+    for (index, bot_id) in tournament.bot_ids.iter().enumerate() {
+        const SQL: &str = indoc! {"
+            INSERT INTO tournament_participants (tournament_id, bot_id, `index`) \
+            VALUES ($1, $2, $3) \
+        "};
+
+        sqlx::query(SQL)
+            .bind::<i64>(id.into())
+            .bind::<i64>((*bot_id).into())
+            .bind::<u32>(index as _)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+    Ok(id)
+}
+
+/// only updates mutable fields
+async fn update_tournament(pool: &SqlitePool, tournament: &Tournament) -> anyhow::Result<()> {
+    assert_ne!(tournament.id, TournamentId::UNINITIALIZED);
+    let res = sqlx::query("UPDATE tournaments SET finished = $1 WHERE id = $2")
+        .bind::<bool>(tournament.finished)
+        .bind::<i64>(tournament.id.into())
+        .execute(pool)
+        .await?;
+
+    assert_eq!(res.rows_affected(), 1);
+    Ok(())
+}
+
+pub async fn persist_tournament_game(
+    pool: &SqlitePool,
+    tournament_id: TournamentId,
+    game: &mut TournamentGame,
+) -> anyhow::Result<()> {
+    let rank = |i: usize| game.ranks.as_ref().and_then(|r| r.get(i).copied());
+    if game.id == TournamentGameId::UNINITIALIZED {
+        const SQL: &str = indoc! {"
+            INSERT INTO tournament_games (tournament_id, round, seed, bot_id_1, bot_id_2, rank_1, rank_2) \
+            VALUES ($1, $2, $3, $4, $5, $6, $7) \
+        "};
+
+        let res = sqlx::query(SQL)
+            .bind::<i64>(tournament_id.into())
+            .bind::<u32>(game.round)
+            .bind::<i64>(game.seed)
+            .bind::<i64>(game.bot_ids[0].into())
+            .bind::<Option<i64>>(game.bot_ids.get(1).map(|&id| id.into()))
+            .bind::<Option<u8>>(rank(0))
+            .bind::<Option<u8>>(rank(1))
+            .execute(pool)
+            .await?;
+        game.id = res.last_insert_rowid().into();
+    } else {
+        let res = sqlx::query("UPDATE tournament_games SET rank_1 = $1, rank_2 = $2 WHERE id = $3")
+            .bind::<Option<u8>>(rank(0))
+            .bind::<Option<u8>>(rank(1))
+            .bind::<i64>(game.id.into())
+            .execute(pool)
+            .await?;
+        assert_eq!(res.rows_affected(), 1);
+    }
+    Ok(())
+}
+
+pub async fn delete_tournament(pool: &SqlitePool, id: TournamentId) -> anyhow::Result<()> {
+    sqlx::query("DELETE FROM tournaments WHERE id = $1")
+        .bind::<i64>(id.into())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
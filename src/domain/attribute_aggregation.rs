@@ -0,0 +1,134 @@
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+
+/// Extra leaderboard column, aggregates a numeric bot attribute without turn
+/// over the leaderboard matches of every bot
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct AttributeAggregation {
+    pub attribute: String,
+    pub function: AggregateFunction,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregateFunction {
+    Avg,
+    Min,
+    Max,
+    Median,
+    /// from 0 to 100
+    Percentile(f64),
+}
+
+impl AttributeAggregation {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.attribute.is_empty() {
+            bail!("Aggregated attribute name should not be empty");
+        }
+        if let AggregateFunction::Percentile(p) = self.function {
+            if !(0.0..=100.0).contains(&p) {
+                bail!("Percentile should be from 0 to 100");
+            }
+        }
+        Ok(())
+    }
+}
+
+impl AggregateFunction {
+    /// `None` if there are no values
+    pub fn apply(&self, values: &[f64]) -> Option<f64> {
+        if values.is_empty() {
+            return None;
+        }
+        match *self {
+            AggregateFunction::Avg => Some(values.iter().sum::<f64>() / values.len() as f64),
+            AggregateFunction::Min => values.iter().copied().reduce(f64::min),
+            AggregateFunction::Max => values.iter().copied().reduce(f64::max),
+            AggregateFunction::Median => AggregateFunction::Percentile(50.0).apply(values),
+            AggregateFunction::Percentile(p) => {
+                let mut sorted = values.to_vec();
+                sorted.sort_by(f64::total_cmp);
+                Some(percentile_of_sorted(&sorted, p))
+            }
+        }
+    }
+
+    /// Same as `apply` for the values already sorted in ascending order
+    pub fn apply_sorted(&self, sorted: &[f64]) -> Option<f64> {
+        let (&min, &max) = (sorted.first()?, sorted.last()?);
+        match *self {
+            AggregateFunction::Avg => Some(sorted.iter().sum::<f64>() / sorted.len() as f64),
+            AggregateFunction::Min => Some(min),
+            AggregateFunction::Max => Some(max),
+            AggregateFunction::Median => Some(percentile_of_sorted(sorted, 50.0)),
+            AggregateFunction::Percentile(p) => Some(percentile_of_sorted(sorted, p)),
+        }
+    }
+}
+
+/// Same as `AggregateFunction::Percentile` for the values already sorted in ascending order,
+/// `values` must not be empty
+pub fn percentile_of_sorted(sorted: &[f64], p: f64) -> f64 {
+    // linear interpolation between the closest ranks
+    let pos = p / 100.0 * (sorted.len() - 1) as f64;
+    let lower = pos.floor() as usize;
+    let upper = pos.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (pos - lower as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregate_functions() {
+        let values = [4.0, 1.0, 3.0, 2.0];
+        assert_eq!(AggregateFunction::Avg.apply(&values), Some(2.5));
+        assert_eq!(AggregateFunction::Min.apply(&values), Some(1.0));
+        assert_eq!(AggregateFunction::Max.apply(&values), Some(4.0));
+        assert_eq!(AggregateFunction::Median.apply(&values), Some(2.5));
+        assert_eq!(AggregateFunction::Percentile(0.0).apply(&values), Some(1.0));
+        assert_eq!(
+            AggregateFunction::Percentile(100.0).apply(&values),
+            Some(4.0)
+        );
+        let p90 = AggregateFunction::Percentile(90.0).apply(&values).unwrap();
+        assert!((p90 - 3.7).abs() < 1e-9);
+        assert_eq!(AggregateFunction::Avg.apply(&[]), None);
+
+        let sorted = [1.0, 2.0, 3.0, 4.0];
+        for function in [
+            AggregateFunction::Avg,
+            AggregateFunction::Min,
+            AggregateFunction::Max,
+            AggregateFunction::Median,
+            AggregateFunction::Percentile(90.0),
+        ] {
+            assert_eq!(function.apply_sorted(&sorted), function.apply(&values));
+        }
+        assert_eq!(AggregateFunction::Avg.apply_sorted(&[]), None);
+    }
+
+    #[test]
+    fn serialization() {
+        let aggregations: Vec<AttributeAggregation> = serde_json::from_str(
+            r#"[
+                { "attribute": "score", "function": "median" },
+                { "attribute": "turns", "function": { "percentile": 90 } }
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(aggregations[0].function, AggregateFunction::Median);
+        assert_eq!(
+            aggregations[1].function,
+            AggregateFunction::Percentile(90.0)
+        );
+        assert!(aggregations.iter().all(|a| a.validate().is_ok()));
+
+        let invalid = AttributeAggregation {
+            attribute: "score".to_string(),
+            function: AggregateFunction::Percentile(101.0),
+        };
+        assert!(invalid.validate().is_err());
+    }
+}
@@ -0,0 +1,173 @@
+use itertools::Itertools;
+use rand::Rng;
+use std::collections::HashMap;
+
+use crate::domain::{BotId, ComputedStats, Match};
+use crate::ranking::Ranker;
+
+/// Rating uncertainty estimated by resampling the leaderboard matches with replacement
+/// and re-running the ranker on each sample.
+pub struct BootstrapStats {
+    pub samples: usize,
+    /// amount of matches the bootstrap is based on
+    pub total_matches: u64,
+    pub bots: HashMap<BotId, BotBootstrapStats>,
+    /// probability that the first bot has higher leaderboard score than the second one
+    pub stronger_probabilities: HashMap<(BotId, BotId), f64>,
+}
+
+pub struct BotBootstrapStats {
+    /// 95% confidence interval of rating mu
+    pub mu_lower: f64,
+    pub mu_upper: f64,
+    /// i-th value is the probability of the bot having rank i
+    pub rank_probabilities: Vec<f64>,
+}
+
+impl BootstrapStats {
+    pub fn compute<R: Rng>(
+        ranker: &Ranker,
+        matches: &[&Match],
+        samples: usize,
+        uncertainty_coefficient: f64,
+        rng: &mut R,
+    ) -> BootstrapStats {
+        let bot_ids = matches
+            .iter()
+            .flat_map(|m| m.participants.iter().map(|p| p.bot_id))
+            .unique()
+            .collect_vec();
+        let default_rating = ranker.default_rating();
+
+        let mut mus: HashMap<BotId, Vec<f64>> = HashMap::new();
+        let mut rank_counts: HashMap<BotId, Vec<u64>> = bot_ids
+            .iter()
+            .map(|&id| (id, vec![0; bot_ids.len()]))
+            .collect();
+        let mut stronger_counts: HashMap<(BotId, BotId), u64> = HashMap::new();
+
+        for _ in 0..samples {
+            // sorted to keep the chronological order for online algorithms
+            let resampled = (0..matches.len())
+                .map(|_| rng.random_range(0..matches.len()))
+                .sorted_unstable()
+                .map(|i| matches[i])
+                .collect_vec();
+
+            let mut stats = ComputedStats::default();
+            stats.recalc_after_matches(ranker, &resampled);
+
+            let ratings = bot_ids
+                .iter()
+                .map(|&id| {
+                    let rating = stats.rating(id).unwrap_or(default_rating);
+                    (id, rating.mu, rating.score(uncertainty_coefficient))
+                })
+                .collect_vec();
+
+            for &(id, mu, score) in &ratings {
+                mus.entry(id).or_default().push(mu);
+
+                let rank = ratings.iter().filter(|(_, _, s)| *s > score).count();
+                rank_counts.get_mut(&id).unwrap()[rank] += 1;
+
+                for &(opp, _, opp_score) in &ratings {
+                    if score > opp_score {
+                        *stronger_counts.entry((id, opp)).or_default() += 1;
+                    }
+                }
+            }
+        }
+
+        let probability = |count: u64| count as f64 / samples.max(1) as f64;
+
+        let bots = rank_counts
+            .into_iter()
+            .map(|(id, counts)| {
+                let mut mus = mus.remove(&id).unwrap_or_default();
+                mus.sort_by(f64::total_cmp);
+                let stats = BotBootstrapStats {
+                    mu_lower: percentile(&mus, 0.025),
+                    mu_upper: percentile(&mus, 0.975),
+                    rank_probabilities: counts.into_iter().map(probability).collect(),
+                };
+                (id, stats)
+            })
+            .collect();
+
+        let stronger_probabilities = bot_ids
+            .iter()
+            .cartesian_product(bot_ids.iter())
+            .filter(|(a, b)| a != b)
+            .map(|(&a, &b)| {
+                let count = stronger_counts.get(&(a, b)).copied().unwrap_or(0);
+                ((a, b), probability(count))
+            })
+            .collect();
+
+        BootstrapStats {
+            samples,
+            total_matches: matches.len() as u64,
+            bots,
+            stronger_probabilities,
+        }
+    }
+}
+
+/// `sorted` must be sorted in ascending order
+fn percentile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let idx = ((sorted.len() - 1) as f64 * q).round() as usize;
+    sorted[idx]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::domain::create_match;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn dominant_bot_holds_first_rank() {
+        let ranker = Ranker::new(Config::default().ranking);
+        let mut matches = vec![];
+        for _ in 0..30 {
+            matches.push(create_match(0, &[(1, 0), (2, 1)]));
+            matches.push(create_match(0, &[(1, 0), (3, 1)]));
+            matches.push(create_match(0, &[(2, 0), (3, 1)]));
+        }
+        // a few upsets
+        matches.push(create_match(0, &[(3, 0), (2, 1)]));
+        matches.push(create_match(0, &[(2, 0), (1, 1)]));
+        let matches = matches.iter().collect_vec();
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let stats = BootstrapStats::compute(&ranker, &matches, 50, 3.0, &mut rng);
+
+        assert_eq!(stats.samples, 50);
+        assert_eq!(stats.total_matches, 92);
+
+        let bot1 = &stats.bots[&1.into()];
+        assert!(bot1.rank_probabilities[0] > 0.9);
+        assert!(bot1.mu_lower <= bot1.mu_upper);
+        assert!(stats.stronger_probabilities[&(1.into(), 3.into())] > 0.9);
+        assert!(stats.stronger_probabilities[&(3.into(), 1.into())] < 0.1);
+
+        let bot3 = &stats.bots[&3.into()];
+        assert!(bot1.mu_lower > bot3.mu_upper);
+    }
+
+    #[test]
+    fn no_matches() {
+        let ranker = Ranker::new(Config::default().ranking);
+        let mut rng = StdRng::seed_from_u64(42);
+        let stats = BootstrapStats::compute(&ranker, &[], 10, 3.0, &mut rng);
+
+        assert!(stats.bots.is_empty());
+        assert!(stats.stronger_probabilities.is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::domain::{BotId, BotName, Language, SourceCode};
+use crate::domain::{BotId, BotName, BotTag, Language, SourceCode};
 use chrono::{DateTime, Utc};
 
 pub struct Bot {
@@ -7,6 +7,7 @@ pub struct Bot {
     pub source_code: SourceCode,
     pub language: Language,
     pub created_at: DateTime<Utc>,
+    pub tags: Vec<BotTag>,
 }
 
 impl Bot {
@@ -17,6 +18,11 @@ impl Bot {
             source_code,
             language,
             created_at: Utc::now(),
+            tags: vec![],
         }
     }
+
+    pub fn has_tag(&self, tag: &BotTag) -> bool {
+        self.tags.contains(tag)
+    }
 }
@@ -0,0 +1,47 @@
+use anyhow::bail;
+use std::collections::HashMap;
+use std::ops::Deref;
+
+use crate::domain::BotId;
+
+#[derive(Eq, PartialEq, Hash, Ord, PartialOrd, Clone, Debug)]
+pub struct BotTag(String);
+
+/// Snapshot of all the bots' tags, used for evaluating match filters
+pub type BotTags = HashMap<BotId, Vec<BotTag>>;
+
+impl TryFrom<String> for BotTag {
+    type Error = anyhow::Error;
+
+    fn try_from(src: String) -> Result<Self, Self::Error> {
+        if src.is_empty() {
+            bail!("BotTag cannot be empty");
+        }
+        if src.len() >= LEN_LIMIT {
+            bail!("BotTag should be less than {} characters", LEN_LIMIT);
+        }
+        if !src
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            bail!("BotTag can only contain latin letters, digits, '_' and '-'");
+        }
+        Ok(Self(src))
+    }
+}
+
+impl From<BotTag> for String {
+    fn from(value: BotTag) -> Self {
+        value.0
+    }
+}
+
+impl Deref for BotTag {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+const LEN_LIMIT: usize = 32;
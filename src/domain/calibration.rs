@@ -0,0 +1,140 @@
+use crate::domain::{ComputedStats, Match};
+use crate::ranking::Ranker;
+
+/// How well the ranker predicts match outcomes. Every pair of match participants
+/// is predicted using only the matches played before, so the ratings never see the
+/// outcome they predict.
+pub struct Calibration {
+    /// amount of matches the calibration is based on
+    pub total_matches: u64,
+    /// amount of predicted pairwise outcomes
+    pub pairs: u64,
+    pub brier_score: f64,
+    pub log_loss: f64,
+    /// predictions grouped by the win probability of the favourite
+    pub buckets: Vec<CalibrationBucket>,
+}
+
+pub struct CalibrationBucket {
+    /// average predicted win probability of the favourite
+    pub predicted: f64,
+    /// share of pairs the favourite actually won, draws count as half
+    pub actual: f64,
+    pub pairs: u64,
+}
+
+// ratings are refitted once per chunk to keep batch algorithms affordable
+const CHUNKS: usize = 20;
+const BUCKETS: usize = 10;
+
+impl Calibration {
+    pub fn compute(ranker: &Ranker, matches: &[&Match]) -> Calibration {
+        let noise = ranker.performance_noise();
+        let default_rating = ranker.default_rating();
+        let chunk_size = matches.len().div_ceil(CHUNKS).max(1);
+
+        let mut stats = ComputedStats::default();
+        let mut pairs = 0;
+        let mut brier_sum = 0.0;
+        let mut log_loss_sum = 0.0;
+        // (predicted sum, actual sum, pairs)
+        let mut buckets = vec![(0.0, 0.0, 0u64); BUCKETS];
+
+        for (i, chunk) in matches.chunks(chunk_size).enumerate() {
+            // the first chunk has nothing to be predicted from
+            if i > 0 {
+                for m in chunk {
+                    for (a, p1) in m.participants.iter().enumerate() {
+                        for p2 in &m.participants[a + 1..] {
+                            let r1 = stats.rating(p1.bot_id).unwrap_or(default_rating);
+                            let r2 = stats.rating(p2.bot_id).unwrap_or(default_rating);
+                            let predicted = noise.win_probability(&r1, &r2);
+                            let actual = match p1.rank.cmp(&p2.rank) {
+                                std::cmp::Ordering::Less => 1.0,
+                                std::cmp::Ordering::Equal => 0.5,
+                                std::cmp::Ordering::Greater => 0.0,
+                            };
+
+                            pairs += 1;
+                            brier_sum += (predicted - actual).powi(2);
+                            let clamped = predicted.clamp(1e-12, 1.0 - 1e-12);
+                            log_loss_sum -=
+                                actual * clamped.ln() + (1.0 - actual) * (1.0 - clamped).ln();
+
+                            let (predicted, actual) = if predicted >= 0.5 {
+                                (predicted, actual)
+                            } else {
+                                (1.0 - predicted, 1.0 - actual)
+                            };
+                            let bucket = (((predicted - 0.5) * 2.0 * BUCKETS as f64) as usize)
+                                .min(BUCKETS - 1);
+                            buckets[bucket].0 += predicted;
+                            buckets[bucket].1 += actual;
+                            buckets[bucket].2 += 1;
+                        }
+                    }
+                }
+            }
+            stats.recalc_after_matches(ranker, chunk);
+        }
+
+        let average = |sum: f64| sum / pairs.max(1) as f64;
+        Calibration {
+            total_matches: matches.len() as u64,
+            pairs,
+            brier_score: average(brier_sum),
+            log_loss: average(log_loss_sum),
+            buckets: buckets
+                .into_iter()
+                .filter(|&(_, _, n)| n > 0)
+                .map(|(predicted, actual, n)| CalibrationBucket {
+                    predicted: predicted / n as f64,
+                    actual: actual / n as f64,
+                    pairs: n,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::domain::create_match;
+    use itertools::Itertools;
+
+    #[test]
+    fn predictable_outcomes_score_better_than_coin_flip() {
+        let ranker = Ranker::new(Config::default().ranking);
+        let mut matches = vec![];
+        for _ in 0..50 {
+            matches.push(create_match(0, &[(1, 0), (2, 1)]));
+            matches.push(create_match(0, &[(2, 0), (3, 1)]));
+            matches.push(create_match(0, &[(1, 0), (3, 1)]));
+        }
+        let matches = matches.iter().collect_vec();
+
+        let calibration = Calibration::compute(&ranker, &matches);
+
+        assert_eq!(calibration.total_matches, 150);
+        // the first chunk is not predicted
+        assert!(calibration.pairs > 100 && calibration.pairs < 150);
+        assert!(calibration.brier_score < 0.25);
+        assert!(calibration.log_loss < std::f64::consts::LN_2);
+        assert!(calibration.buckets.iter().all(|b| b.predicted >= 0.5));
+        assert_eq!(
+            calibration.buckets.iter().map(|b| b.pairs).sum::<u64>(),
+            calibration.pairs
+        );
+    }
+
+    #[test]
+    fn no_matches() {
+        let ranker = Ranker::new(Config::default().ranking);
+        let calibration = Calibration::compute(&ranker, &[]);
+
+        assert_eq!(calibration.pairs, 0);
+        assert!(calibration.buckets.is_empty());
+    }
+}
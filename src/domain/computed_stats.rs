@@ -1,20 +1,119 @@
 use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
 
-use crate::domain::{BotId, Match, Rating};
-use crate::ranking::{Ranker, RankingStrategyKind};
-use std::collections::{HashMap, VecDeque};
+use crate::domain::{AttributeAggregation, BotId, Match, Participant, Rating};
+use crate::ranking::{BatchInput, ErrorPolicy, Ranker, RankingStrategyKind};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
 
 #[derive(Default, Clone)]
 pub struct ComputedStats {
     ratings: HashMap<BotId, Rating>,
     winrate_stats: HashMap<(BotId, BotId), WinrateStats>,
+    // pairwise games of every bot regardless of the error policy, for matchmaking
+    games_played: HashMap<(BotId, BotId), u64>,
+    // pairwise outcomes of every bot by its seat index
+    seat_stats: HashMap<(BotId, u8), WinrateStats>,
     matches_with_error: HashMap<BotId, u64>,
     total_matches: u64,
     example_seeds: VecDeque<i64>,
+    // populated only when ranker keeps separate ratings per player count
+    ratings_by_player_count: HashMap<u8, HashMap<BotId, Rating>>,
+    winrate_stats_by_player_count: HashMap<u8, HashMap<(BotId, BotId), WinrateStats>>,
+    // populated only when ranker fits on match placements, shared between snapshots
+    placements: Arc<Vec<MatchPlacement>>,
+    // populated only when ranker decays older matches
+    decay_state: DecayState,
+    decay_state_by_player_count: HashMap<u8, DecayState>,
+    // populated only when ranker averages online ratings over several match orders,
+    // matches without attributes, shared between snapshots
+    online_history: Arc<Vec<(Arc<Match>, f64)>>,
+    // batch and permuted online ratings lag behind the matches until refitted,
+    // see `record_matches`
+    fitted_matches: u64,
+    // total matches after the latest unfitted match of every player count
+    unfitted_player_counts: HashMap<u8, u64>,
+    // populated only for the tracked attributes, values of every bot are shared between snapshots
+    attribute_values: HashMap<String, HashMap<BotId, Arc<AttributeValues>>>,
 }
 
+/// Part of the stats the batch fit reads, see `ComputedStats::batch_fit_input`
+pub struct BatchFitInput {
+    total_matches: u64,
+    placements: Arc<Vec<MatchPlacement>>,
+    online_history: Arc<Vec<(Arc<Match>, f64)>>,
+    // `None` if the overall ratings are up to date
+    overall: Option<BatchFitTarget>,
+    by_player_count: HashMap<u8, BatchFitTarget>,
+}
+
+// online ratings are replayed from `online_history` and need none of these
+#[derive(Default)]
+struct BatchFitTarget {
+    winrate_stats: HashMap<(BotId, BotId), WinrateStats>,
+    decay_state: Option<DecayState>,
+    previous: HashMap<BotId, Rating>,
+}
+
+/// Batch ratings fitted on a snapshot of the stats, see `ComputedStats::fit_batch`
+pub struct BatchFit {
+    total_matches: u64,
+    ratings: Option<HashMap<BotId, Rating>>,
+    ratings_by_player_count: HashMap<u8, HashMap<BotId, Rating>>,
+}
+
+#[derive(Clone)]
+struct MatchPlacement {
+    player_count: u8,
+    ranks: Vec<(BotId, u8, bool)>,
+    clock: f64,
+}
+
+/// Recency-weighted match outcomes, see `Decay::clock` for the time axis
+#[derive(Default, Clone)]
+struct DecayState {
+    // weights are relative to the origin so that they don't need rescaling on every match
+    origin: Option<f64>,
+    latest_clock: f64,
+    winrate_stats: HashMap<(BotId, BotId), DecayedWinrateStats>,
+    last_played: HashMap<BotId, f64>,
+}
+
+// rescaling keeps the weights far from the f64 limits
+const DECAY_RESCALE_HALF_LIVES: f64 = 512.0;
+
 const EXAMPLE_SEEDS_LIMIT: usize = 10;
 
+// attribute columns aggregate this many latest values of every bot
+const ATTRIBUTE_VALUES_LIMIT: usize = 1000;
+
+/// Latest values of a bot attribute, kept sorted for the aggregation
+#[derive(Default, Clone)]
+struct AttributeValues {
+    // in the order of recording, to know which value is the oldest
+    recent: VecDeque<f64>,
+    sorted: Vec<f64>,
+}
+
+impl AttributeValues {
+    fn push(&mut self, value: f64) {
+        self.recent.push_back(value);
+        let pos = self.sorted.partition_point(|v| v.total_cmp(&value).is_lt());
+        self.sorted.insert(pos, value);
+
+        if self.recent.len() > ATTRIBUTE_VALUES_LIMIT {
+            let oldest = self.recent.pop_front().unwrap();
+            let pos = self
+                .sorted
+                .partition_point(|v| v.total_cmp(&oldest).is_lt());
+            self.sorted.remove(pos);
+        }
+    }
+}
+
 #[derive(Default, Clone)]
 pub struct WinrateStats {
     pub wins: u64,
@@ -26,27 +125,315 @@ impl WinrateStats {
     pub fn total(&self) -> u64 {
         self.wins + self.loses + self.draws
     }
+
+    /// `outcome` is the rank comparison with the opponent
+    pub fn add(&mut self, outcome: Ordering) {
+        match outcome {
+            Ordering::Less => self.wins += 1,
+            Ordering::Equal => self.draws += 1,
+            Ordering::Greater => self.loses += 1,
+        }
+    }
+
+    /// Draws count as half, `None` if there are no games
+    pub fn winrate(&self) -> Option<f64> {
+        let total = self.total();
+        (total > 0).then(|| (self.wins as f64 + 0.5 * self.draws as f64) / total as f64)
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct DecayedWinrateStats {
+    pub wins: f64,
+    pub draws: f64,
+    pub loses: f64,
+}
+
+impl DecayState {
+    fn add_match(&mut self, m: &Match, clock: f64, error_policy: ErrorPolicy) {
+        let mut origin = *self.origin.get_or_insert(clock);
+        if clock - origin > DECAY_RESCALE_HALF_LIVES {
+            let factor = 2f64.powf(origin - clock);
+            for stats in self.winrate_stats.values_mut() {
+                stats.wins *= factor;
+                stats.draws *= factor;
+                stats.loses *= factor;
+            }
+            origin = clock;
+            self.origin = Some(origin);
+        }
+        self.latest_clock = self.latest_clock.max(clock);
+
+        let weight = 2f64.powf(clock - origin);
+        for_each_outcome(m, error_policy, |key, _, outcome| {
+            let entry = self.winrate_stats.entry(key).or_default();
+            match outcome {
+                Ordering::Less => entry.wins += weight,
+                Ordering::Equal => entry.draws += weight,
+                Ordering::Greater => entry.loses += weight,
+            }
+        });
+    }
+
+    /// Winrate stats where the most recent match has weight 1
+    fn winrate_stats(&self) -> HashMap<(BotId, BotId), DecayedWinrateStats> {
+        let factor = 2f64.powf(self.origin.unwrap_or_default() - self.latest_clock);
+        self.winrate_stats
+            .iter()
+            .map(|(&key, stats)| {
+                let stats = DecayedWinrateStats {
+                    wins: stats.wins * factor,
+                    draws: stats.draws * factor,
+                    loses: stats.loses * factor,
+                };
+                (key, stats)
+            })
+            .collect()
+    }
+
+    fn weight(&self, clock: f64) -> f64 {
+        2f64.powf(clock - self.latest_clock)
+    }
+
+    /// Inflates the uncertainty of the participants depending on how long ago they last played
+    fn inflate_ratings(
+        &mut self,
+        ranker: &Ranker,
+        ratings: &mut HashMap<BotId, Rating>,
+        m: &Match,
+        clock: f64,
+    ) {
+        for p in &m.participants {
+            let last_played = self.last_played.insert(p.bot_id, clock);
+            if let (Some(last_played), Some(rating)) = (last_played, ratings.get_mut(&p.bot_id)) {
+                *rating = ranker.inflate_sigma(*rating, clock - last_played);
+            }
+        }
+    }
 }
 
 impl ComputedStats {
     pub fn recalc_after_matches(&mut self, ranker: &Ranker, matches: &[&Match]) {
+        self.record_matches(ranker, matches);
+        let fit = self.fit_batch(ranker);
+        self.apply_batch_fit(fit);
+    }
+
+    /// Same as `recalc_after_matches`, but batch ratings are left as they are
+    /// until a `fit_batch` result is applied
+    pub fn record_matches(&mut self, ranker: &Ranker, matches: &[&Match]) {
+        let first_index = self.total_matches;
         self.total_matches += matches.len() as u64;
 
         for &m in matches {
             self.recalc_example_seeds_after_match(m);
             self.recalc_matches_with_error_after_match(m);
-            self.recalc_winrate_stats_after_match(m);
+            for_each_outcome(m, ErrorPolicy::Count, |key, _, _| {
+                *self.games_played.entry(key).or_default() += 1;
+            });
+        }
+        if !self.attribute_values.is_empty() {
+            self.recalc_attribute_values_after_matches(matches);
+        }
+
+        let error_policy = ranker.error_policy();
+        let decay = ranker.decay();
+        let matches = matches
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, m)| {
+                error_policy != ErrorPolicy::ExcludeMatch || m.participants.iter().all(|p| !p.error)
+            })
+            .map(|(i, m)| {
+                let clock = decay.map_or(0.0, |d| d.clock(first_index + i as u64, m));
+                (m, clock)
+            })
+            .collect_vec();
+
+        for &(m, _) in &matches {
+            update_winrate_stats(&mut self.winrate_stats, m, error_policy);
+            update_seat_stats(&mut self.seat_stats, m, error_policy);
+        }
+
+        if ranker.needs_placements() {
+            Arc::make_mut(&mut self.placements).extend(matches.iter().map(|&(m, clock)| {
+                let ranks = m
+                    .participants
+                    .iter()
+                    // nobody can gain from beating an errored participant
+                    .map(|p| {
+                        let no_gain = error_policy == ErrorPolicy::ExcludeOpponentGain && p.error;
+                        (p.bot_id, p.rank, no_gain)
+                    })
+                    .collect_vec();
+                MatchPlacement {
+                    player_count: m.participants.len() as u8,
+                    ranks,
+                    clock,
+                }
+            }));
         }
 
         // rating
         match ranker.strategy_kind() {
             RankingStrategyKind::Online => {
-                for &m in matches {
-                    ranker.recalc_rating(&mut self.ratings, m);
+                if ranker.permutations() > 1 {
+                    // the whole history is replayed on refit, like the batch ratings
+                    let history = Arc::make_mut(&mut self.online_history);
+                    history.extend(matches.iter().map(|&(m, clock)| {
+                        let participants = m
+                            .participants
+                            .iter()
+                            .map(|p| Participant {
+                                bot_id: p.bot_id,
+                                rank: p.rank,
+                                error: p.error,
+                            })
+                            .collect();
+                        (Arc::new(Match::new(m.seed, participants, vec![])), clock)
+                    }));
+                } else {
+                    replay_online(ranker, &mut self.ratings, &mut self.decay_state, &matches);
+                    self.fitted_matches = self.total_matches;
+                }
+            }
+            RankingStrategyKind::Batch => {
+                if decay.is_some() {
+                    for &(m, clock) in &matches {
+                        self.decay_state.add_match(m, clock, error_policy);
+                    }
+                }
+            }
+        }
+
+        if ranker.per_player_count() {
+            self.recalc_ratings_by_player_count(ranker, &matches);
+        }
+    }
+
+    fn recalc_ratings_by_player_count(&mut self, ranker: &Ranker, matches: &[(&Match, f64)]) {
+        let total_matches = self.total_matches;
+        let decay = ranker.decay();
+        match ranker.strategy_kind() {
+            RankingStrategyKind::Online => {
+                let by_player_count = matches
+                    .iter()
+                    .copied()
+                    .into_group_map_by(|(m, _)| m.participants.len() as u8);
+                for (player_count, matches) in by_player_count {
+                    if ranker.permutations() > 1 {
+                        self.unfitted_player_counts
+                            .insert(player_count, total_matches);
+                    } else {
+                        let ratings = self
+                            .ratings_by_player_count
+                            .entry(player_count)
+                            .or_default();
+                        let decay_state = self
+                            .decay_state_by_player_count
+                            .entry(player_count)
+                            .or_default();
+                        replay_online(ranker, ratings, decay_state, &matches);
+                    }
                 }
             }
             RankingStrategyKind::Batch => {
-                self.ratings = ranker.recalc_rating_batch(&self.winrate_stats)
+                for &(m, clock) in matches {
+                    let player_count = m.participants.len() as u8;
+                    let winrate_stats = self
+                        .winrate_stats_by_player_count
+                        .entry(player_count)
+                        .or_default();
+                    update_winrate_stats(winrate_stats, m, ranker.error_policy());
+                    if decay.is_some() {
+                        self.decay_state_by_player_count
+                            .entry(player_count)
+                            .or_default()
+                            .add_match(m, clock, ranker.error_policy());
+                    }
+                    self.unfitted_player_counts
+                        .insert(player_count, total_matches);
+                }
+            }
+        }
+    }
+
+    /// Amount of matches the ratings are based on
+    pub fn fitted_matches(&self) -> u64 {
+        self.fitted_matches
+    }
+
+    /// Amount of recorded matches not yet reflected by the refitted ratings
+    pub fn unfitted_matches(&self) -> u64 {
+        self.total_matches - self.fitted_matches
+    }
+
+    /// Refits the batch ratings which are behind the recorded matches,
+    /// warm-started from the current ones
+    pub fn fit_batch(&self, ranker: &Ranker) -> BatchFit {
+        self.batch_fit_input(ranker).fit(ranker)
+    }
+
+    /// Snapshot of the ratings which are behind the recorded matches and everything
+    /// needed to refit them, so that the fit can run without the stats
+    pub fn batch_fit_input(&self, ranker: &Ranker) -> BatchFitInput {
+        let mut input = BatchFitInput {
+            total_matches: self.total_matches,
+            placements: Arc::clone(&self.placements),
+            online_history: Arc::clone(&self.online_history),
+            overall: None,
+            by_player_count: HashMap::new(),
+        };
+        if !matches!(ranker.strategy_kind(), RankingStrategyKind::Batch) {
+            if self.unfitted_matches() > 0 {
+                input.overall = Some(BatchFitTarget::default());
+            }
+            for &player_count in self.unfitted_player_counts.keys() {
+                input
+                    .by_player_count
+                    .insert(player_count, BatchFitTarget::default());
+            }
+            return input;
+        }
+
+        if self.unfitted_matches() > 0 {
+            input.overall = Some(BatchFitTarget {
+                winrate_stats: self.winrate_stats.clone(),
+                decay_state: ranker.decay().map(|_| self.decay_state.clone()),
+                previous: self.ratings.clone(),
+            });
+        }
+        for &player_count in self.unfitted_player_counts.keys() {
+            let target = BatchFitTarget {
+                winrate_stats: self.winrate_stats_by_player_count[&player_count].clone(),
+                decay_state: self.decay_state_by_player_count.get(&player_count).cloned(),
+                previous: self
+                    .ratings_by_player_count
+                    .get(&player_count)
+                    .cloned()
+                    .unwrap_or_default(),
+            };
+            input.by_player_count.insert(player_count, target);
+        }
+        input
+    }
+
+    /// Applies the ratings fitted on an earlier snapshot of these stats,
+    /// the matches recorded after the snapshot stay unfitted
+    pub fn apply_batch_fit(&mut self, fit: BatchFit) {
+        if let Some(ratings) = fit.ratings {
+            self.ratings = ratings;
+            self.fitted_matches = self.fitted_matches.max(fit.total_matches);
+        }
+        for (player_count, ratings) in fit.ratings_by_player_count {
+            self.ratings_by_player_count.insert(player_count, ratings);
+            if self
+                .unfitted_player_counts
+                .get(&player_count)
+                .is_some_and(|&at| at <= fit.total_matches)
+            {
+                self.unfitted_player_counts.remove(&player_count);
             }
         }
     }
@@ -60,6 +447,32 @@ impl ComputedStats {
         }
     }
 
+    /// Values of these bot attributes are kept by `record_matches` to be aggregated,
+    /// should be called before recording any matches
+    pub fn track_attributes(&mut self, names: impl IntoIterator<Item = String>) {
+        for name in names {
+            self.attribute_values.entry(name).or_default();
+        }
+    }
+
+    fn recalc_attribute_values_after_matches(&mut self, matches: &[&Match]) {
+        for attr in matches.iter().flat_map(|m| &m.attributes) {
+            let (Some(bot_id), None) = (attr.bot_id, attr.turn) else {
+                continue;
+            };
+            let Some(values) = self.attribute_values.get_mut(&attr.name) else {
+                continue;
+            };
+            let value = attr
+                .value
+                .float_value()
+                .or_else(|| attr.value.integer_value().map(|v| v as f64));
+            if let Some(value) = value {
+                Arc::make_mut(values.entry(bot_id).or_default()).push(value);
+            }
+        }
+    }
+
     fn recalc_matches_with_error_after_match(&mut self, m: &Match) {
         for p in &m.participants {
             if p.error {
@@ -71,52 +484,82 @@ impl ComputedStats {
         }
     }
 
-    fn recalc_winrate_stats_after_match(&mut self, m: &Match) {
-        for (p1, p2) in m
-            .participants
-            .iter()
-            .cartesian_product(m.participants.iter())
-        {
-            if p1.bot_id == p2.bot_id {
-                continue;
-            }
-            let entry = self
-                .winrate_stats
-                .entry((p1.bot_id, p2.bot_id))
-                .or_default();
-
-            match p1.rank.cmp(&p2.rank) {
-                std::cmp::Ordering::Less => entry.wins += 1,
-                std::cmp::Ordering::Equal => entry.draws += 1,
-                std::cmp::Ordering::Greater => entry.loses += 1,
-            }
-        }
-    }
-
     pub fn rating(&self, id: BotId) -> Option<Rating> {
         self.ratings.get(&id).cloned()
     }
 
+    pub fn ratings_by_player_count(&self, id: BotId) -> BTreeMap<u8, Rating> {
+        self.ratings_by_player_count
+            .iter()
+            .filter_map(|(&player_count, ratings)| {
+                ratings.get(&id).map(|&rating| (player_count, rating))
+            })
+            .collect()
+    }
+
+    /// Counts the matches excluded from the ratings by the error policy as well
     pub fn matches_played(&self, id: BotId) -> u64 {
-        self.winrate_stats
+        self.games_played
             .iter()
             .filter(|(k, _)| k.0 == id)
-            .map(|(_, v)| v.total())
+            .map(|(_, &v)| v)
             .sum()
     }
 
+    /// Counts the matches excluded from the ratings by the error policy as well
     pub fn matches_played_vs(&self, id: BotId, opp: BotId) -> u64 {
-        self.winrate_stats
-            .iter()
-            .find(|(k, _)| k.0 == id && k.1 == opp)
-            .map(|(_, v)| v.total())
-            .unwrap_or(0)
+        self.games_played
+            .get(&(id, opp))
+            .copied()
+            .unwrap_or_default()
     }
 
     pub fn winrate_stats_snapshot(&self) -> HashMap<(BotId, BotId), WinrateStats> {
         self.winrate_stats.clone()
     }
 
+    /// Pairwise outcomes of the bot keyed by its seat index
+    pub fn seat_stats(&self, id: BotId) -> BTreeMap<u8, WinrateStats> {
+        self.seat_stats
+            .iter()
+            .filter(|((bot_id, _), _)| *bot_id == id)
+            .map(|(&(_, seat), stats)| (seat, stats.clone()))
+            .collect()
+    }
+
+    /// Pairwise outcomes of all the bots keyed by their seat index
+    pub fn seat_totals(&self) -> BTreeMap<u8, WinrateStats> {
+        let mut totals: BTreeMap<u8, WinrateStats> = BTreeMap::new();
+        for (&(_, seat), stats) in &self.seat_stats {
+            let total = totals.entry(seat).or_default();
+            total.wins += stats.wins;
+            total.draws += stats.draws;
+            total.loses += stats.loses;
+        }
+        totals
+    }
+
+    /// How much the first seat winrate is above the fair 0.5, negative if
+    /// the first player is at a disadvantage
+    pub fn seat_advantage(&self) -> Option<f64> {
+        let winrate = self.seat_totals().get(&0)?.winrate()?;
+        Some(winrate - 0.5)
+    }
+
+    /// `None` if the attribute is not tracked or the bot has no values of it,
+    /// only `ATTRIBUTE_VALUES_LIMIT` latest values are aggregated
+    pub fn aggregate_attribute(
+        &self,
+        id: BotId,
+        aggregation: &AttributeAggregation,
+    ) -> Option<f64> {
+        let values = self
+            .attribute_values
+            .get(&aggregation.attribute)?
+            .get(&id)?;
+        aggregation.function.apply_sorted(&values.sorted)
+    }
+
     pub fn matches_with_error(&self, id: BotId) -> u64 {
         self.matches_with_error
             .get(&id)
@@ -132,3 +575,562 @@ impl ComputedStats {
         self.example_seeds.iter().cloned().collect()
     }
 }
+
+fn replay_online(
+    ranker: &Ranker,
+    ratings: &mut HashMap<BotId, Rating>,
+    decay_state: &mut DecayState,
+    matches: &[(&Match, f64)],
+) {
+    for &(m, clock) in matches {
+        if ranker.decay().is_some() {
+            decay_state.inflate_ratings(ranker, ratings, m, clock);
+        }
+        ranker.recalc_rating(ratings, m);
+    }
+}
+
+/// Replays the matches in several shuffled orders and averages the results.
+/// Shuffles are seeded to keep the recomputes reproducible.
+fn replay_permutations(ranker: &Ranker, matches: &[(&Match, f64)]) -> HashMap<BotId, Rating> {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut shuffled = matches.to_vec();
+    let count = ranker.permutations() as f64;
+    let mut average: HashMap<BotId, Rating> = HashMap::new();
+    for _ in 0..ranker.permutations() {
+        shuffled.shuffle(&mut rng);
+        let mut ratings = HashMap::new();
+        replay_online(ranker, &mut ratings, &mut DecayState::default(), &shuffled);
+
+        // every order has the same matches, hence the same bots
+        for (id, rating) in ratings {
+            let entry = average.entry(id).or_insert(Rating::new(0.0, 0.0));
+            entry.mu += rating.mu / count;
+            entry.sigma += rating.sigma / count;
+            if let Some(volatility) = rating.volatility {
+                *entry.volatility.get_or_insert(0.0) += volatility / count;
+            }
+        }
+    }
+    average
+}
+
+impl BatchFitInput {
+    pub fn fit(&self, ranker: &Ranker) -> BatchFit {
+        let recalc = |target: &BatchFitTarget, player_count: Option<u8>| match ranker
+            .strategy_kind()
+        {
+            RankingStrategyKind::Online => {
+                let matches = self
+                    .online_history
+                    .iter()
+                    .filter(|(m, _)| player_count.is_none_or(|c| m.participants.len() as u8 == c))
+                    .map(|(m, clock)| (m.as_ref(), *clock))
+                    .collect_vec();
+                replay_permutations(ranker, &matches)
+            }
+            RankingStrategyKind::Batch => recalc_batch(
+                ranker,
+                &target.winrate_stats,
+                &self.placements,
+                |p| player_count.is_none_or(|c| p.player_count == c),
+                target.decay_state.as_ref(),
+                &target.previous,
+            ),
+        };
+        BatchFit {
+            total_matches: self.total_matches,
+            ratings: self.overall.as_ref().map(|target| recalc(target, None)),
+            ratings_by_player_count: self
+                .by_player_count
+                .iter()
+                .map(|(&player_count, target)| (player_count, recalc(target, Some(player_count))))
+                .collect(),
+        }
+    }
+}
+
+fn recalc_batch(
+    ranker: &Ranker,
+    winrate_stats: &HashMap<(BotId, BotId), WinrateStats>,
+    placements: &[MatchPlacement],
+    placement_filter: impl Fn(&MatchPlacement) -> bool,
+    decay_state: Option<&DecayState>,
+    previous: &HashMap<BotId, Rating>,
+) -> HashMap<BotId, Rating> {
+    let placements = placements
+        .iter()
+        .filter(|p| placement_filter(p))
+        .collect_vec();
+    let decayed_winrate_stats = decay_state.map(DecayState::winrate_stats);
+    let placement_weights = decay_state.map(|state| {
+        placements
+            .iter()
+            .map(|p| state.weight(p.clock))
+            .collect_vec()
+    });
+    let placements = placements.iter().map(|p| p.ranks.as_slice()).collect_vec();
+
+    ranker.recalc_rating_batch(&BatchInput {
+        winrate_stats,
+        placements: &placements,
+        decayed_winrate_stats: decayed_winrate_stats.as_ref(),
+        placement_weights: placement_weights.as_deref(),
+        anchors: ranker.anchors(),
+        previous,
+    })
+}
+
+fn update_winrate_stats(
+    winrate_stats: &mut HashMap<(BotId, BotId), WinrateStats>,
+    m: &Match,
+    error_policy: ErrorPolicy,
+) {
+    for_each_outcome(m, error_policy, |key, _, outcome| {
+        winrate_stats.entry(key).or_default().add(outcome);
+    });
+}
+
+fn update_seat_stats(
+    seat_stats: &mut HashMap<(BotId, u8), WinrateStats>,
+    m: &Match,
+    error_policy: ErrorPolicy,
+) {
+    for_each_outcome(m, error_policy, |(bot_id, _), seat, outcome| {
+        seat_stats.entry((bot_id, seat)).or_default().add(outcome);
+    });
+}
+
+/// Calls `f` with the rank comparison of every ordered pair of the match participants
+/// and the seat index of the first one
+fn for_each_outcome(
+    m: &Match,
+    error_policy: ErrorPolicy,
+    mut f: impl FnMut((BotId, BotId), u8, Ordering),
+) {
+    for ((seat, p1), p2) in m
+        .participants
+        .iter()
+        .enumerate()
+        .cartesian_product(m.participants.iter())
+    {
+        if p1.bot_id == p2.bot_id {
+            continue;
+        }
+        // the errored bot's loss is still counted on its side
+        if error_policy == ErrorPolicy::ExcludeOpponentGain
+            && !p1.error
+            && p2.error
+            && p1.rank < p2.rank
+        {
+            continue;
+        }
+        f((p1.bot_id, p2.bot_id), seat as u8, p1.rank.cmp(&p2.rank));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RankingConfig;
+    use crate::domain::{create_match, AggregateFunction, MatchAttribute};
+
+    fn ranker(config: &str) -> Ranker {
+        let config: RankingConfig = toml::from_str(config).unwrap();
+        Ranker::new(config)
+    }
+
+    #[test]
+    fn errors_counted_by_default() {
+        let ranker = ranker(r#"algorithm = "Elo""#);
+        let mut m = create_match(0, &[(1, 0), (2, 1)]);
+        m.participants[1].error = true;
+
+        let mut stats = ComputedStats::default();
+        stats.recalc_after_matches(&ranker, &[&m]);
+
+        assert_eq!(stats.matches_played(1.into()), 1);
+        assert_eq!(stats.matches_with_error(2.into()), 1);
+        assert!(stats.rating(1.into()).unwrap().mu > stats.rating(2.into()).unwrap().mu);
+    }
+
+    #[test]
+    fn exclude_match_policy() {
+        let ranker = ranker(
+            r#"
+            algorithm = "Elo"
+            error_policy = "exclude_match"
+        "#,
+        );
+        let mut m1 = create_match(0, &[(1, 0), (2, 1)]);
+        m1.participants[1].error = true;
+        let m2 = create_match(0, &[(2, 0), (1, 1)]);
+
+        let mut stats = ComputedStats::default();
+        stats.recalc_after_matches(&ranker, &[&m1, &m2]);
+
+        assert_eq!(stats.total_matches(), 2);
+        assert_eq!(stats.matches_with_error(2.into()), 1);
+        // excluded matches are still played, e.g. for the matchmaking quotas
+        assert_eq!(stats.matches_played(1.into()), 2);
+        assert_eq!(
+            stats.winrate_stats_snapshot()[&(1.into(), 2.into())].total(),
+            1
+        );
+        assert!(stats.rating(2.into()).unwrap().mu > stats.rating(1.into()).unwrap().mu);
+    }
+
+    #[test]
+    fn exclude_opponent_gain_policy() {
+        let ranker = ranker(
+            r#"
+            algorithm = "Elo"
+            error_policy = "exclude_opponent_gain"
+        "#,
+        );
+        let mut m = create_match(0, &[(1, 0), (2, 1)]);
+        m.participants[1].error = true;
+
+        let mut stats = ComputedStats::default();
+        stats.recalc_after_matches(&ranker, &[&m]);
+
+        let default_rating = ranker.default_rating();
+        assert_eq!(stats.rating(1.into()).unwrap().mu, default_rating.mu);
+        assert!(stats.rating(2.into()).unwrap().mu < default_rating.mu);
+
+        let winrate_stats = stats.winrate_stats_snapshot();
+        assert!(!winrate_stats.contains_key(&(1.into(), 2.into())));
+        assert_eq!(winrate_stats[&(2.into(), 1.into())].loses, 1);
+        assert_eq!(stats.matches_played_vs(1.into(), 2.into()), 1);
+        assert_eq!(stats.matches_played_vs(2.into(), 1.into()), 1);
+    }
+
+    #[test]
+    fn exclude_opponent_gain_policy_on_placements() {
+        let ranker = ranker(
+            r#"
+            algorithm = "PlackettLuce"
+            error_policy = "exclude_opponent_gain"
+        "#,
+        );
+        let mut m = create_match(0, &[(1, 0), (2, 1)]);
+        m.participants[1].error = true;
+
+        let mut stats = ComputedStats::default();
+        stats.recalc_after_matches(&ranker, &[&m, &m, &m]);
+
+        // same as online: the winner stays at the prior, the errored bot still loses
+        let winner = stats.rating(1.into()).unwrap();
+        let loser = stats.rating(2.into()).unwrap();
+        assert!((winner.mu - 1500.0).abs() < 1e-6);
+        assert!(loser.mu < 1500.0);
+
+        // the winner still gains over the other participants
+        let mut m = create_match(0, &[(1, 0), (2, 1)]);
+        m.participants[1].error = true;
+        m.participants.push(Participant {
+            bot_id: 3.into(),
+            rank: 2,
+            error: false,
+        });
+        let mut stats = ComputedStats::default();
+        stats.recalc_after_matches(&ranker, &[&m, &m, &m]);
+        let rating = |id: i64| stats.rating(id.into()).unwrap().mu;
+        assert!(rating(1) > rating(3));
+        assert!(rating(2) < rating(1));
+    }
+
+    #[test]
+    fn online_anchors_keep_their_rating() {
+        let ranker = ranker(
+            r#"
+            algorithm = "OpenSkill"
+            anchors = [{ bot_id = 2, rating = 25 }]
+        "#,
+        );
+        let m1 = create_match(0, &[(1, 0), (2, 1)]);
+        let m2 = create_match(0, &[(2, 0), (3, 1)]);
+
+        let mut stats = ComputedStats::default();
+        stats.recalc_after_matches(&ranker, &[&m1, &m2, &m1]);
+
+        let anchor = stats.rating(2.into()).unwrap();
+        assert_eq!(anchor.mu, 25.0);
+        assert_eq!(anchor.sigma, 0.0);
+        assert!(stats.rating(1.into()).unwrap().mu > 25.0);
+        assert!(stats.rating(3.into()).unwrap().mu < 25.0);
+    }
+
+    #[test]
+    fn permutations_reduce_order_dependence() {
+        let mut matches = vec![];
+        for _ in 0..10 {
+            matches.push(create_match(0, &[(1, 0), (2, 1)]));
+        }
+        for _ in 0..10 {
+            matches.push(create_match(0, &[(2, 0), (1, 1)]));
+        }
+        let forward = matches.iter().collect_vec();
+        let backward = matches.iter().rev().collect_vec();
+
+        let rating_gap = |ranker: &Ranker| {
+            let mut forward_stats = ComputedStats::default();
+            forward_stats.recalc_after_matches(ranker, &forward);
+            let mut backward_stats = ComputedStats::default();
+            backward_stats.recalc_after_matches(ranker, &backward);
+            let forward_mu = forward_stats.rating(1.into()).unwrap().mu;
+            let backward_mu = backward_stats.rating(1.into()).unwrap().mu;
+            (forward_mu - backward_mu).abs()
+        };
+
+        let single = ranker(r#"algorithm = "Elo""#);
+        let permuted = ranker(
+            r#"
+            algorithm = "Elo"
+            permutations = 50
+        "#,
+        );
+        assert!(rating_gap(&permuted) < rating_gap(&single) / 4.0);
+
+        // reproducible
+        let mut first = ComputedStats::default();
+        first.recalc_after_matches(&permuted, &forward);
+        let mut second = ComputedStats::default();
+        second.recalc_after_matches(&permuted, &forward);
+        assert_eq!(
+            first.rating(1.into()).unwrap().mu,
+            second.rating(1.into()).unwrap().mu
+        );
+    }
+
+    #[test]
+    fn permutations_agree_between_live_and_full_recompute() {
+        let ranker = ranker(
+            r#"
+            algorithm = "Elo"
+            permutations = 20
+        "#,
+        );
+        let mut matches = vec![];
+        for _ in 0..10 {
+            matches.push(create_match(0, &[(1, 0), (2, 1)]));
+        }
+        for _ in 0..10 {
+            matches.push(create_match(0, &[(2, 0), (1, 1)]));
+        }
+
+        let mut live = ComputedStats::default();
+        for m in &matches {
+            live.record_matches(&ranker, &[m]);
+        }
+        // refitted over the whole history like the batch ratings
+        assert_eq!(live.unfitted_matches(), 20);
+        let fit = live.fit_batch(&ranker);
+        live.apply_batch_fit(fit);
+
+        let mut full = ComputedStats::default();
+        full.recalc_after_matches(&ranker, &matches.iter().collect_vec());
+        for id in [1, 2] {
+            let live = live.rating(id.into()).unwrap();
+            let full = full.rating(id.into()).unwrap();
+            assert!((live.mu - full.mu).abs() < 1e-9);
+            assert!((live.sigma - full.sigma).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn decay_prefers_recent_matches() {
+        let mut matches = vec![];
+        for _ in 0..30 {
+            matches.push(create_match(0, &[(1, 0), (2, 1)]));
+        }
+        for _ in 0..10 {
+            matches.push(create_match(0, &[(2, 0), (1, 1)]));
+        }
+        let matches = matches.iter().collect_vec();
+
+        let mut stats = ComputedStats::default();
+        stats.recalc_after_matches(&ranker(r#"algorithm = "BradleyTerry""#), &matches);
+        assert!(stats.rating(1.into()).unwrap().mu > stats.rating(2.into()).unwrap().mu);
+
+        let ranker = ranker(
+            r#"
+            algorithm = "BradleyTerry"
+            decay = { half_life_matches = 5 }
+        "#,
+        );
+        let mut stats = ComputedStats::default();
+        stats.recalc_after_matches(&ranker, &matches);
+        assert!(stats.rating(2.into()).unwrap().mu > stats.rating(1.into()).unwrap().mu);
+        // undecayed counts are still reported
+        assert_eq!(stats.matches_played(1.into()), 40);
+    }
+
+    #[test]
+    fn batch_refit_can_be_deferred() {
+        let ranker = ranker(
+            r#"
+            algorithm = "BradleyTerry"
+            per_player_count = true
+        "#,
+        );
+        let m1 = create_match(0, &[(1, 0), (2, 1)]);
+        let m2 = create_match(0, &[(2, 0), (3, 1)]);
+
+        let mut stats = ComputedStats::default();
+        stats.record_matches(&ranker, &[&m1, &m1]);
+        assert_eq!(stats.unfitted_matches(), 2);
+        assert!(stats.rating(1.into()).is_none());
+
+        // matches recorded after the snapshot stay unfitted
+        let fit = stats.fit_batch(&ranker);
+        stats.record_matches(&ranker, &[&m2]);
+        stats.apply_batch_fit(fit);
+        assert_eq!(stats.unfitted_matches(), 1);
+        assert!(stats.rating(1.into()).is_some());
+        assert!(stats.rating(3.into()).is_none());
+
+        let fit = stats.fit_batch(&ranker);
+        stats.apply_batch_fit(fit);
+        assert_eq!(stats.unfitted_matches(), 0);
+
+        let mut full = ComputedStats::default();
+        full.recalc_after_matches(&ranker, &[&m1, &m1, &m2]);
+        for id in [1, 2, 3] {
+            let rating = stats.rating(id.into()).unwrap();
+            let expected = full.rating(id.into()).unwrap();
+            assert!((rating.mu - expected.mu).abs() < 1e-6);
+            assert!(
+                (stats.ratings_by_player_count(id.into())[&2].mu
+                    - full.ratings_by_player_count(id.into())[&2].mu)
+                    .abs()
+                    < 1e-6
+            );
+        }
+    }
+
+    #[test]
+    fn decay_inflates_sigma_of_idle_bots() {
+        let ranker = ranker(
+            r#"
+            algorithm = "OpenSkill"
+            decay = { half_life_days = 1 }
+        "#,
+        );
+        let start = chrono::Utc::now();
+        let mut matches = vec![];
+        for _ in 0..20 {
+            let mut m = create_match(0, &[(1, 0), (2, 1)]);
+            m.created_at = start;
+            matches.push(m);
+        }
+        let matches = matches.iter().collect_vec();
+
+        let mut stats = ComputedStats::default();
+        stats.recalc_after_matches(&ranker, &matches);
+        let sigma_before = stats.rating(1.into()).unwrap().sigma;
+
+        let mut active = stats.clone();
+        let mut m = create_match(0, &[(1, 0), (2, 1)]);
+        m.created_at = start;
+        active.recalc_after_matches(&ranker, &[&m]);
+
+        let mut idle = stats.clone();
+        m.created_at = start + chrono::Duration::days(30);
+        idle.recalc_after_matches(&ranker, &[&m]);
+
+        let sigma_active = active.rating(1.into()).unwrap().sigma;
+        let sigma_idle = idle.rating(1.into()).unwrap().sigma;
+        assert!(sigma_active < sigma_before);
+        assert!(sigma_idle > sigma_before);
+    }
+
+    #[test]
+    fn seat_stats_and_advantage() {
+        let ranker = ranker(r#"algorithm = "Elo""#);
+        // the first seat wins 3 of 4 matches
+        let matches = [
+            create_match(0, &[(1, 0), (2, 1)]),
+            create_match(0, &[(2, 0), (1, 1)]),
+            create_match(0, &[(1, 0), (3, 1)]),
+            create_match(0, &[(2, 0), (3, 1)]),
+        ];
+        let mut matches = matches.into_iter().collect_vec();
+        matches[3].participants.swap(0, 1);
+        let matches = matches.iter().collect_vec();
+
+        let mut stats = ComputedStats::default();
+        assert!(stats.seat_advantage().is_none());
+        stats.recalc_after_matches(&ranker, &matches);
+
+        let seats = stats.seat_stats(1.into());
+        assert_eq!(seats[&0].wins, 2);
+        assert_eq!(seats[&1].loses, 1);
+        let totals = stats.seat_totals();
+        assert_eq!((totals[&0].wins, totals[&0].loses), (3, 1));
+        assert_eq!(totals[&1].winrate(), Some(0.25));
+        assert_eq!(stats.seat_advantage(), Some(0.25));
+    }
+
+    #[test]
+    fn tracked_attributes_are_aggregated() {
+        let ranker = ranker(r#"algorithm = "Elo""#);
+        let score = |bot_id: i64, value: &str| MatchAttribute {
+            name: "score".to_string(),
+            bot_id: Some(bot_id.into()),
+            turn: None,
+            value: value.to_string().into(),
+        };
+        let mut m1 = create_match(0, &[(1, 0), (2, 1)]);
+        m1.attributes = vec![score(1, "10"), score(2, "4.5")];
+        let mut m2 = create_match(0, &[(2, 0), (1, 1)]);
+        m2.attributes = vec![score(1, "20")];
+
+        let mut stats = ComputedStats::default();
+        stats.track_attributes(["score".to_string()]);
+        stats.recalc_after_matches(&ranker, &[&m1]);
+        stats.recalc_after_matches(&ranker, &[&m2]);
+
+        let aggregation = |function| AttributeAggregation {
+            attribute: "score".to_string(),
+            function,
+        };
+        assert_eq!(
+            stats.aggregate_attribute(1.into(), &aggregation(AggregateFunction::Avg)),
+            Some(15.0)
+        );
+        assert_eq!(
+            stats.aggregate_attribute(2.into(), &aggregation(AggregateFunction::Max)),
+            Some(4.5)
+        );
+        assert_eq!(
+            stats.aggregate_attribute(3.into(), &aggregation(AggregateFunction::Avg)),
+            None
+        );
+        let untracked = AttributeAggregation {
+            attribute: "turns".to_string(),
+            function: AggregateFunction::Avg,
+        };
+        assert_eq!(stats.aggregate_attribute(1.into(), &untracked), None);
+    }
+
+    #[test]
+    fn only_latest_attribute_values_are_aggregated() {
+        let mut values = AttributeValues::default();
+        for value in (0..ATTRIBUTE_VALUES_LIMIT + 10).rev() {
+            values.push(value as f64);
+        }
+
+        // the largest values were recorded first and are dropped
+        assert_eq!(values.sorted.len(), ATTRIBUTE_VALUES_LIMIT);
+        assert!(values.sorted.is_sorted());
+        assert_eq!(
+            AggregateFunction::Max.apply_sorted(&values.sorted),
+            Some((ATTRIBUTE_VALUES_LIMIT - 1) as f64)
+        );
+        assert_eq!(
+            AggregateFunction::Min.apply_sorted(&values.sorted),
+            Some(0.0)
+        );
+    }
+}
@@ -0,0 +1,264 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::domain::{BotId, Match, WinrateStats};
+use crate::ranking::ErrorPolicy;
+
+/// How bot A did against bot B in the matches they played together
+pub struct HeadToHead {
+    /// amount of matches both bots played in
+    pub total_matches: u64,
+    /// outcomes from A's point of view
+    pub winrate_stats: WinrateStats,
+    pub winrate: WinrateInterval,
+    /// keyed by A's seat index
+    pub by_seat: BTreeMap<u8, HeadToHeadSplit>,
+    pub by_player_count: BTreeMap<u8, HeadToHeadSplit>,
+    /// seeds of the matches A lost to B, newest first
+    pub lost_seeds: Vec<i64>,
+    /// numeric bot attributes both bots have, sorted by name
+    pub attributes: Vec<AttributeComparison>,
+}
+
+#[derive(Default)]
+pub struct HeadToHeadSplit {
+    pub winrate_stats: WinrateStats,
+    pub winrate: WinrateInterval,
+}
+
+/// Share of games won, draws count as half, with its 95% Wilson score interval
+#[derive(Default, Clone, Copy)]
+pub struct WinrateInterval {
+    pub winrate: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+/// Per-match attribute values of both bots, only the matches where both of them have it
+pub struct AttributeComparison {
+    pub name: String,
+    pub matches: u64,
+    pub a_avg: f64,
+    pub b_avg: f64,
+    /// amount of matches A's value was higher than B's one
+    pub a_higher: u64,
+}
+
+const LOST_SEEDS_LIMIT: usize = 20;
+const Z_95: f64 = 1.96;
+
+impl WinrateInterval {
+    pub fn new(stats: &WinrateStats) -> WinrateInterval {
+        let Some(p) = stats.winrate() else {
+            return WinrateInterval {
+                winrate: 0.5,
+                lower: 0.0,
+                upper: 1.0,
+            };
+        };
+
+        let n = stats.total() as f64;
+        let z2 = Z_95 * Z_95;
+        let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+        let margin = Z_95 / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+        WinrateInterval {
+            winrate: p,
+            lower: (center - margin).max(0.0),
+            upper: (center + margin).min(1.0),
+        }
+    }
+}
+
+impl HeadToHead {
+    /// `matches` are expected in the order they were played, the ones without
+    /// both bots are skipped
+    pub fn compute(
+        a: BotId,
+        b: BotId,
+        matches: &[&Match],
+        error_policy: ErrorPolicy,
+    ) -> HeadToHead {
+        let mut total_matches = 0;
+        let mut winrate_stats = WinrateStats::default();
+        let mut by_seat: BTreeMap<u8, HeadToHeadSplit> = BTreeMap::new();
+        let mut by_player_count: BTreeMap<u8, HeadToHeadSplit> = BTreeMap::new();
+        let mut lost_seeds = vec![];
+        // name -> (a value, b value) of every match
+        let mut attributes: BTreeMap<&str, Vec<(f64, f64)>> = BTreeMap::new();
+
+        for &m in matches {
+            let has = |id| m.participants.iter().any(|p| p.bot_id == id);
+            if !has(a) || !has(b) {
+                continue;
+            }
+            if error_policy == ErrorPolicy::ExcludeMatch && m.participants.iter().any(|p| p.error) {
+                continue;
+            }
+            total_matches += 1;
+
+            let player_count = m.participants.len() as u8;
+            let mut lost = false;
+            for (seat, pa) in m.participants.iter().enumerate() {
+                if pa.bot_id != a {
+                    continue;
+                }
+                for pb in m.participants.iter().filter(|p| p.bot_id == b) {
+                    // same as the leaderboard winrate stats of A against B
+                    if error_policy == ErrorPolicy::ExcludeOpponentGain
+                        && !pa.error
+                        && pb.error
+                        && pa.rank < pb.rank
+                    {
+                        continue;
+                    }
+                    let outcome = pa.rank.cmp(&pb.rank);
+                    lost |= outcome == Ordering::Greater;
+                    winrate_stats.add(outcome);
+                    by_seat
+                        .entry(seat as u8)
+                        .or_default()
+                        .winrate_stats
+                        .add(outcome);
+                    by_player_count
+                        .entry(player_count)
+                        .or_default()
+                        .winrate_stats
+                        .add(outcome);
+                }
+            }
+            if lost {
+                lost_seeds.push(m.seed);
+            }
+
+            let values = bot_attribute_values(m, a);
+            for (name, vb) in bot_attribute_values(m, b) {
+                // seats are compared in `by_seat` already
+                if name == "index" {
+                    continue;
+                }
+                if let Some(&va) = values.get(name) {
+                    attributes.entry(name).or_default().push((va, vb));
+                }
+            }
+        }
+
+        for split in by_seat.values_mut().chain(by_player_count.values_mut()) {
+            split.winrate = WinrateInterval::new(&split.winrate_stats);
+        }
+
+        HeadToHead {
+            total_matches,
+            winrate: WinrateInterval::new(&winrate_stats),
+            winrate_stats,
+            by_seat,
+            by_player_count,
+            lost_seeds: lost_seeds
+                .into_iter()
+                .rev()
+                .take(LOST_SEEDS_LIMIT)
+                .collect(),
+            attributes: attributes
+                .into_iter()
+                .map(|(name, values)| {
+                    let n = values.len() as f64;
+                    AttributeComparison {
+                        name: name.to_string(),
+                        matches: values.len() as u64,
+                        a_avg: values.iter().map(|v| v.0).sum::<f64>() / n,
+                        b_avg: values.iter().map(|v| v.1).sum::<f64>() / n,
+                        a_higher: values.iter().filter(|v| v.0 > v.1).count() as u64,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Numeric attributes of the bot which don't belong to a turn
+fn bot_attribute_values(m: &Match, bot_id: BotId) -> HashMap<&str, f64> {
+    m.attributes
+        .iter()
+        .filter(|attr| attr.bot_id == Some(bot_id) && attr.turn.is_none())
+        .filter_map(|attr| {
+            let value = attr
+                .value
+                .float_value()
+                .or_else(|| attr.value.integer_value().map(|v| v as f64))?;
+            Some((attr.name.as_str(), value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{create_match, MatchAttribute};
+    use itertools::Itertools;
+
+    fn with_scores(mut m: Match, scores: &[(i64, i64)]) -> Match {
+        m.attributes = scores
+            .iter()
+            .map(|&(bot_id, score)| MatchAttribute {
+                name: "score".to_string(),
+                bot_id: Some(bot_id.into()),
+                turn: None,
+                value: score.to_string().into(),
+            })
+            .collect();
+        m
+    }
+
+    #[test]
+    fn wilson_interval() {
+        let stats = WinrateStats {
+            wins: 8,
+            draws: 0,
+            loses: 2,
+        };
+        let interval = WinrateInterval::new(&stats);
+        assert!((interval.winrate - 0.8).abs() < 1e-9);
+        assert!((interval.lower - 0.490).abs() < 1e-3);
+        assert!((interval.upper - 0.943).abs() < 1e-3);
+
+        let interval = WinrateInterval::new(&WinrateStats::default());
+        assert_eq!((interval.lower, interval.upper), (0.0, 1.0));
+    }
+
+    #[test]
+    fn splits_by_seat_and_player_count() {
+        let matches = [
+            with_scores(create_match(1, &[(1, 0), (2, 1)]), &[(1, 10), (2, 5)]),
+            with_scores(create_match(2, &[(2, 0), (1, 1)]), &[(1, 3), (2, 7)]),
+            with_scores(create_match(3, &[(1, 0), (3, 1), (2, 2)]), &[(1, 6)]),
+            with_scores(create_match(4, &[(1, 1), (3, 0)]), &[]),
+            with_scores(create_match(5, &[(2, 0), (1, 0)]), &[(1, 4), (2, 4)]),
+        ];
+        let matches = matches.iter().collect_vec();
+
+        let h2h = HeadToHead::compute(1.into(), 2.into(), &matches, ErrorPolicy::Count);
+
+        assert_eq!(h2h.total_matches, 4);
+        assert_eq!(
+            (
+                h2h.winrate_stats.wins,
+                h2h.winrate_stats.draws,
+                h2h.winrate_stats.loses
+            ),
+            (2, 1, 1)
+        );
+        assert!((h2h.winrate.winrate - 0.625).abs() < 1e-9);
+        assert_eq!(h2h.by_seat.keys().copied().collect_vec(), vec![0, 1]);
+        assert_eq!(h2h.by_seat[&0].winrate_stats.wins, 2);
+        assert_eq!(h2h.by_seat[&1].winrate_stats.total(), 2);
+        assert_eq!(h2h.by_player_count[&3].winrate_stats.wins, 1);
+        assert_eq!(h2h.by_player_count[&2].winrate_stats.total(), 3);
+        assert_eq!(h2h.lost_seeds, vec![2]);
+
+        assert_eq!(h2h.attributes.len(), 1);
+        let score = &h2h.attributes[0];
+        assert_eq!(score.matches, 3);
+        assert!((score.a_avg - 17.0 / 3.0).abs() < 1e-9);
+        assert!((score.b_avg - 16.0 / 3.0).abs() < 1e-9);
+        assert_eq!(score.a_higher, 1);
+    }
+}
@@ -1,25 +1,44 @@
-use crate::domain::{LeaderboardId, LeaderboardName, MatchFilter};
+use crate::config::RankingConfig;
+use crate::domain::{AttributeAggregation, LeaderboardId, LeaderboardName, MatchFilter};
 
 pub struct Leaderboard {
     pub id: LeaderboardId,
     pub name: LeaderboardName,
     pub filter: MatchFilter,
+    /// overrides the arena-wide ranking config
+    pub ranking: Option<RankingConfig>,
+    /// overrides the arena-wide uncertainty coefficient
+    pub uncertainty_coefficient: Option<f64>,
+    /// extra columns aggregating bot attributes
+    pub columns: Vec<AttributeAggregation>,
 }
 
 impl Leaderboard {
-    pub fn new(name: LeaderboardName, filter: MatchFilter) -> Leaderboard {
+    pub fn new(
+        name: LeaderboardName,
+        filter: MatchFilter,
+        ranking: Option<RankingConfig>,
+        uncertainty_coefficient: Option<f64>,
+        columns: Vec<AttributeAggregation>,
+    ) -> Leaderboard {
         Leaderboard {
             id: LeaderboardId::UNINITIALIZED,
             name,
             filter,
+            ranking,
+            uncertainty_coefficient,
+            columns,
         }
     }
 
-    pub fn global() -> Leaderboard {
+    pub fn global(columns: Vec<AttributeAggregation>) -> Leaderboard {
         Leaderboard {
             id: LeaderboardId::UNINITIALIZED,
             name: LeaderboardName::global(),
             filter: MatchFilter::accept_all(),
+            ranking: None,
+            uncertainty_coefficient: None,
+            columns,
         }
     }
 }
@@ -1,4 +1,5 @@
 use crate::domain::{BotId, MatchAttribute, MatchId};
+use chrono::{DateTime, Utc};
 
 // only successfully finished matches would be stored in DB
 pub struct Match {
@@ -6,6 +7,7 @@ pub struct Match {
     pub seed: i64,
     pub participants: Vec<Participant>,
     pub attributes: Vec<MatchAttribute>,
+    pub created_at: DateTime<Utc>,
 }
 
 pub struct Participant {
@@ -25,6 +27,21 @@ impl Match {
             seed,
             participants,
             attributes,
+            created_at: Utc::now(),
         }
     }
 }
+
+/// Match of the bots in seat order with their ranks, nobody errors
+#[cfg(test)]
+pub fn create_match(seed: i64, ranks: &[(i64, u8)]) -> Match {
+    let participants = ranks
+        .iter()
+        .map(|&(bot_id, rank)| Participant {
+            bot_id: bot_id.into(),
+            rank,
+            error: false,
+        })
+        .collect();
+    Match::new(seed, participants, vec![])
+}
@@ -2,7 +2,7 @@ use std::{fmt::Display, str::FromStr};
 
 use anyhow::{anyhow, bail};
 
-use crate::domain::{Match, MatchAttribute, MatchAttributeValue};
+use crate::domain::{BotTags, Match, MatchAttribute, MatchAttributeValue};
 
 #[derive(Clone)]
 pub struct MatchFilter {
@@ -14,14 +14,26 @@ impl MatchFilter {
         Self { expr: None }
     }
 
-    pub fn matches(&self, m: &Match) -> bool {
+    pub fn matches(&self, m: &Match, bot_tags: &BotTags) -> bool {
         if let Some(ref expr) = self.expr {
-            check_expr(expr, m).unwrap_or(false)
+            check_expr(expr, m, bot_tags).unwrap_or(false)
         } else {
             true
         }
     }
 
+    /// Whether the filter result depends on the bots' tags
+    pub fn uses_bot_tags(&self) -> bool {
+        self.expr.as_ref().is_some_and(uses_bot_tags)
+    }
+
+    /// SQL condition on the `matches` table (aliased `m`) which holds for every match
+    /// accepted by the filter. Parts of the filter which can't be expressed in SQL are
+    /// left out, so the fetched matches still need to be checked with `matches`.
+    pub fn sql_condition(&self) -> Option<String> {
+        self.expr.as_ref().and_then(sql_condition)
+    }
+
     pub fn needed_attributes(&self) -> Vec<MatchAttribute> {
         let mut res = vec![];
         if let Some(ref expr) = self.expr {
@@ -31,8 +43,100 @@ impl MatchFilter {
     }
 }
 
+fn uses_bot_tags(expr: &ast::Expr) -> bool {
+    match expr {
+        ast::Expr::Condition(_, _, _) => false,
+        ast::Expr::BotTag(_) => true,
+        ast::Expr::Paren(expr) => uses_bot_tags(expr),
+        ast::Expr::And(expr1, expr2) => uses_bot_tags(expr1) || uses_bot_tags(expr2),
+        ast::Expr::Or(expr1, expr2) => uses_bot_tags(expr1) || uses_bot_tags(expr2),
+        ast::Expr::Not(expr) => uses_bot_tags(expr),
+    }
+}
+
+fn sql_condition(expr: &ast::Expr) -> Option<String> {
+    match expr {
+        ast::Expr::Condition(arg1, op, arg2) => sql_attribute_condition(arg1, op, arg2),
+        ast::Expr::BotTag(_) => None,
+        ast::Expr::Paren(expr) => sql_condition(expr),
+        ast::Expr::And(expr1, expr2) => match (sql_condition(expr1), sql_condition(expr2)) {
+            (Some(cond1), Some(cond2)) => Some(format!("({cond1} AND {cond2})")),
+            (cond1, cond2) => cond1.or(cond2),
+        },
+        ast::Expr::Or(expr1, expr2) => Some(format!(
+            "({} OR {})",
+            sql_condition(expr1)?,
+            sql_condition(expr2)?
+        )),
+        // the negation is also true for the matches missing the attribute
+        ast::Expr::Not(_) => None,
+    }
+}
+
+/// Only attribute to value comparisons are supported
+fn sql_attribute_condition(
+    arg1: &ast::Argument,
+    op: &ast::ConditionOp,
+    arg2: &ast::Argument,
+) -> Option<String> {
+    let (attr, op, value) = match (arg1, arg2) {
+        (attr, ast::Argument::Value(value)) => (attr, op.clone(), value),
+        (ast::Argument::Value(value), attr) => (attr, op.flipped(), value),
+        _ => return None,
+    };
+    let (name, bot_id, turn) = match attr {
+        ast::Argument::MatchAttr(attr) => (&attr.name, None, attr.turn),
+        ast::Argument::BotAttr(attr) => (&attr.name, Some(attr.bot_id), attr.turn),
+        ast::Argument::Value(_) => return None,
+    };
+
+    let sql_op = match op {
+        ast::ConditionOp::Eq => "=",
+        ast::ConditionOp::NotEq => "<>",
+        ast::ConditionOp::Less => "<",
+        ast::ConditionOp::LessOrEqual => "<=",
+        ast::ConditionOp::Greater => ">",
+        ast::ConditionOp::GreaterOrEqual => ">=",
+    };
+    let value_condition = match value {
+        ast::Value::Number(v) if v.is_finite() => {
+            format!("COALESCE(ma.value_int, ma.value_float) {sql_op} {v:?}")
+        }
+        ast::Value::String(v) if matches!(op, ast::ConditionOp::Eq | ast::ConditionOp::NotEq) => {
+            format!(
+                "ma.value_string_id IN \
+                    (SELECT id FROM match_attribute_string_values WHERE value {sql_op} {})",
+                quote_sql_string(v)
+            )
+        }
+        _ => return None,
+    };
+    let bot_condition = match bot_id {
+        Some(bot_id) => format!("ma.bot_id = {}", i64::from(bot_id)),
+        None => "ma.bot_id IS NULL".to_string(),
+    };
+    let turn_condition = match turn {
+        Some(turn) => format!("ma.turn = {turn}"),
+        None => "ma.turn IS NULL".to_string(),
+    };
+
+    Some(format!(
+        "EXISTS (SELECT 1 FROM match_attributes ma \
+            INNER JOIN match_attribute_names n ON (n.id = ma.name_id) \
+            WHERE ma.match_id = m.id AND n.name = {} AND {bot_condition} AND {turn_condition} \
+            AND {value_condition})",
+        quote_sql_string(name)
+    ))
+}
+
+/// SQL string literal
+pub fn quote_sql_string(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
 fn collect_needed_attributes(expr: &ast::Expr, res: &mut Vec<MatchAttribute>) {
     match expr {
+        ast::Expr::BotTag(_) => {}
         ast::Expr::Condition(argument1, _, argument2) => {
             collect_needed_attributes_from_arg(argument1, res);
             collect_needed_attributes_from_arg(argument2, res);
@@ -76,16 +180,30 @@ fn collect_needed_attributes_from_arg(arg: &ast::Argument, res: &mut Vec<MatchAt
     }
 }
 
-fn check_expr(expr: &ast::Expr, m: &Match) -> Result<bool, anyhow::Error> {
+fn check_expr(expr: &ast::Expr, m: &Match, bot_tags: &BotTags) -> Result<bool, anyhow::Error> {
     match expr {
         ast::Expr::Condition(arg1, op, arg2) => check_condition(arg1, op, arg2, m),
-        ast::Expr::Paren(expr) => check_expr(expr, m),
-        ast::Expr::And(expr1, expr2) => Ok(check_expr(expr1, m)? && check_expr(expr2, m)?),
-        ast::Expr::Or(expr1, expr2) => Ok(check_expr(expr1, m)? || check_expr(expr2, m)?),
-        ast::Expr::Not(expr) => Ok(!check_expr(expr, m)?),
+        ast::Expr::BotTag(tag) => Ok(check_bot_tag(tag, m, bot_tags)),
+        ast::Expr::Paren(expr) => check_expr(expr, m, bot_tags),
+        ast::Expr::And(expr1, expr2) => {
+            Ok(check_expr(expr1, m, bot_tags)? && check_expr(expr2, m, bot_tags)?)
+        }
+        ast::Expr::Or(expr1, expr2) => {
+            Ok(check_expr(expr1, m, bot_tags)? || check_expr(expr2, m, bot_tags)?)
+        }
+        ast::Expr::Not(expr) => Ok(!check_expr(expr, m, bot_tags)?),
     }
 }
 
+/// all the match participants should have the tag
+fn check_bot_tag(tag: &str, m: &Match, bot_tags: &BotTags) -> bool {
+    m.participants.iter().all(|p| {
+        bot_tags
+            .get(&p.bot_id)
+            .is_some_and(|tags| tags.iter().any(|t| **t == *tag))
+    })
+}
+
 fn check_condition(
     arg1: &ast::Argument,
     op: &ast::ConditionOp,
@@ -206,6 +324,7 @@ mod ast {
     #[derive(Clone)]
     pub enum Expr {
         Condition(Argument, ConditionOp, Argument),
+        BotTag(String),
         Paren(Box<Expr>),
         And(Box<Expr>, Box<Expr>),
         Or(Box<Expr>, Box<Expr>),
@@ -248,6 +367,20 @@ mod ast {
         pub bot_id: BotId,
     }
 
+    impl ConditionOp {
+        /// Same comparison with the arguments swapped
+        pub fn flipped(&self) -> ConditionOp {
+            match self {
+                ConditionOp::Eq => ConditionOp::Eq,
+                ConditionOp::NotEq => ConditionOp::NotEq,
+                ConditionOp::Less => ConditionOp::Greater,
+                ConditionOp::LessOrEqual => ConditionOp::GreaterOrEqual,
+                ConditionOp::Greater => ConditionOp::Less,
+                ConditionOp::GreaterOrEqual => ConditionOp::LessOrEqual,
+            }
+        }
+    }
+
     impl Display for ConditionOp {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             match self {
@@ -306,6 +439,7 @@ mod ast {
                 Expr::Condition(ref arg1, ref op, ref arg2) => {
                     write!(f, "{} {} {}", arg1, op, arg2)
                 }
+                Expr::BotTag(ref tag) => write!(f, "bot_tag(\"{}\")", tag),
                 Expr::Paren(ref expr) => write!(f, "({})", expr),
                 Expr::And(ref expr1, ref expr2) => write!(f, "{} AND {}", expr1, expr2),
                 Expr::Or(ref expr1, ref expr2) => write!(f, "{} OR {}", expr1, expr2),
@@ -364,7 +498,26 @@ mod ast {
     }
 
     fn factor_inner(i: &str) -> IResult<&str, Expr> {
-        alt((condition, parens)).parse(i)
+        alt((bot_tag, condition, parens)).parse(i)
+    }
+
+    fn bot_tag(i: &str) -> IResult<&str, Expr> {
+        delimited(
+            multispace0,
+            map(
+                preceded(
+                    tag_no_case("bot_tag"),
+                    delimited(
+                        tag("(\""),
+                        map(is_not("\""), |s: &str| s.to_string()),
+                        tag("\")"),
+                    ),
+                ),
+                Expr::BotTag,
+            ),
+            multispace0,
+        )
+        .parse(i)
     }
 
     fn condition(i: &str) -> IResult<&str, Expr> {
@@ -488,7 +641,7 @@ mod ast {
 
 #[cfg(test)]
 mod tests {
-    use crate::domain::{BotId, MatchAttribute};
+    use crate::domain::{create_match, BotId, BotTag, MatchAttribute};
 
     use super::*;
 
@@ -525,6 +678,8 @@ mod tests {
         and_or_grouped: "match.a == 2 AND (match.x > 1 OR match.y < 1)",
         or_and_grouped: "match.a == 2 OR (match.x > 1 AND match.y < 1)",
         match_eq_match: "match.a == match.b",
+        bot_tag: "bot_tag(\"current\")",
+        not_bot_tag_and: "NOT bot_tag(\"baseline\") AND match.a == 1",
     );
 
     #[test]
@@ -606,53 +761,106 @@ mod tests {
         ];
 
         let m = Match::new(1234, vec![], attributes);
+        let bot_tags = BotTags::new();
 
         let filter = MatchFilter::accept_all();
-        assert!(filter.matches(&m));
+        assert!(filter.matches(&m, &bot_tags));
 
         let filter = MatchFilter::from_str("match.initial_stones == 25").unwrap();
-        assert!(filter.matches(&m));
+        assert!(filter.matches(&m, &bot_tags));
 
         let filter = MatchFilter::from_str("match.initial_stones == 24").unwrap();
-        assert!(!filter.matches(&m));
+        assert!(!filter.matches(&m, &bot_tags));
 
         let filter = MatchFilter::from_str("match.map_type == \"small\"").unwrap();
-        assert!(filter.matches(&m));
+        assert!(filter.matches(&m, &bot_tags));
 
         let filter = MatchFilter::from_str("match.stones_percentage == 0.75").unwrap();
-        assert!(filter.matches(&m));
+        assert!(filter.matches(&m, &bot_tags));
 
         let filter = MatchFilter::from_str(
             "match.stones_percentage > 0.7 AND match.stones_percentage < 0.8",
         )
         .unwrap();
-        assert!(filter.matches(&m));
+        assert!(filter.matches(&m, &bot_tags));
 
         let filter = MatchFilter::from_str(&format!("bot({bot_id1}).final_score >= 75")).unwrap();
-        assert!(filter.matches(&m));
+        assert!(filter.matches(&m, &bot_tags));
 
         let filter = MatchFilter::from_str(&format!(
             "bot({bot_id1}).final_score > bot({bot_id2}).final_score"
         ))
         .unwrap();
-        assert!(filter.matches(&m));
+        assert!(filter.matches(&m, &bot_tags));
 
         let filter = MatchFilter::from_str(&format!(
             "bot({bot_id1}).final_score < bot({bot_id2}).final_score"
         ))
         .unwrap();
-        assert!(!filter.matches(&m));
+        assert!(!filter.matches(&m, &bot_tags));
 
         let filter = MatchFilter::from_str(&format!("bot({bot_id1})[50].score == 30")).unwrap();
-        assert!(filter.matches(&m));
+        assert!(filter.matches(&m, &bot_tags));
 
         let filter = MatchFilter::from_str(&format!("bot({bot_id1})[20].score == 30")).unwrap();
-        assert!(!filter.matches(&m));
+        assert!(!filter.matches(&m, &bot_tags));
 
         let filter = MatchFilter::from_str(&format!("bot({bot_id3}).final_score == 75")).unwrap();
-        assert!(!filter.matches(&m));
+        assert!(!filter.matches(&m, &bot_tags));
 
         let filter = MatchFilter::from_str("match.invalid_attr == 24").unwrap();
-        assert!(!filter.matches(&m));
+        assert!(!filter.matches(&m, &bot_tags));
+    }
+
+    #[test]
+    fn sql_condition() {
+        let condition = |filter: &str| MatchFilter::from_str(filter).unwrap().sql_condition();
+
+        assert!(condition("").is_none());
+        assert!(condition("bot_tag(\"current\")").is_none());
+        assert!(condition("NOT match.a == 1").is_none());
+        assert!(condition("match.a == match.b").is_none());
+        assert!(condition("match.a == 1 OR bot_tag(\"current\")").is_none());
+
+        let sql = condition("bot_tag(\"current\") AND match.map == \"o'neil\"").unwrap();
+        assert!(sql.contains("n.name = 'map'"));
+        assert!(sql.contains("ma.bot_id IS NULL AND ma.turn IS NULL"));
+        assert!(sql.contains("WHERE value = 'o''neil'"));
+
+        let sql = condition("5 < bot(3)[10].score").unwrap();
+        assert!(sql.contains("ma.bot_id = 3 AND ma.turn = 10"));
+        assert!(sql.contains("COALESCE(ma.value_int, ma.value_float) > 5.0"));
+
+        let sql = condition("match.a == 1 OR match.b == 2").unwrap();
+        assert!(sql.starts_with("(EXISTS"));
+        assert!(sql.contains(" OR EXISTS"));
+    }
+
+    #[test]
+    fn filtering_by_bot_tag() {
+        let bot_id1: BotId = 1i64.into();
+        let bot_id2: BotId = 2i64.into();
+
+        let m = create_match(1234, &[(1, 0), (2, 1)]);
+
+        let tag = |s: &str| BotTag::try_from(s.to_string()).unwrap();
+        let bot_tags: BotTags = [
+            (bot_id1, vec![tag("alice"), tag("current")]),
+            (bot_id2, vec![tag("current")]),
+        ]
+        .into();
+
+        let filter = MatchFilter::from_str("bot_tag(\"current\")").unwrap();
+        assert!(filter.uses_bot_tags());
+        assert!(filter.matches(&m, &bot_tags));
+
+        let filter = MatchFilter::from_str("bot_tag(\"alice\")").unwrap();
+        assert!(!filter.matches(&m, &bot_tags));
+
+        let filter = MatchFilter::from_str("NOT bot_tag(\"alice\")").unwrap();
+        assert!(filter.matches(&m, &bot_tags));
+
+        let filter = MatchFilter::from_str("match.a == 1").unwrap();
+        assert!(!filter.uses_bot_tags());
     }
 }
@@ -1,9 +1,14 @@
+mod attribute_aggregation;
+mod bootstrap_stats;
 mod bot;
 mod bot_id;
 mod bot_name;
+mod bot_tag;
 mod build;
 mod build_status;
+mod calibration;
 mod computed_stats;
+mod head_to_head;
 mod language;
 mod leaderboard;
 mod leaderboard_id;
@@ -12,16 +17,26 @@ mod r#match;
 mod match_attribute;
 mod match_filter;
 mod match_id;
+mod outcome_correlation;
 mod rating;
+mod rating_snapshot;
 mod source_code;
+mod tournament;
+mod tournament_id;
+mod tournament_name;
 mod worker_name;
 
+pub use attribute_aggregation::*;
+pub use bootstrap_stats::*;
 pub use bot::*;
 pub use bot_id::*;
 pub use bot_name::*;
+pub use bot_tag::*;
 pub use build::*;
 pub use build_status::*;
+pub use calibration::*;
 pub use computed_stats::*;
+pub use head_to_head::*;
 pub use language::*;
 pub use leaderboard::*;
 pub use leaderboard_id::*;
@@ -29,7 +44,12 @@ pub use leaderboard_name::*;
 pub use match_attribute::*;
 pub use match_filter::*;
 pub use match_id::*;
+pub use outcome_correlation::*;
 pub use r#match::*;
 pub use rating::*;
+pub use rating_snapshot::*;
 pub use source_code::*;
+pub use tournament::*;
+pub use tournament_id::*;
+pub use tournament_name::*;
 pub use worker_name::*;
@@ -0,0 +1,87 @@
+/// How a numeric attribute differs between the won and the lost matches
+pub struct OutcomeCorrelation {
+    pub wins: u64,
+    pub loses: u64,
+    pub win_avg: f64,
+    pub lose_avg: f64,
+    /// point-biserial correlation with winning, from -1 to 1
+    pub correlation: f64,
+    /// Cohen's d, difference of the averages in pooled standard deviations
+    pub effect_size: f64,
+}
+
+impl OutcomeCorrelation {
+    /// `samples` are the attribute values along with whether the match was won,
+    /// `None` if there are less than 2 wins or loses or all the values are the same
+    pub fn compute(samples: &[(f64, bool)]) -> Option<OutcomeCorrelation> {
+        let (won, lost): (Vec<_>, Vec<_>) = samples.iter().partition(|(_, won)| *won);
+        if won.len() < 2 || lost.len() < 2 {
+            return None;
+        }
+
+        let avg = |values: &[&(f64, bool)]| {
+            values.iter().map(|(v, _)| v).sum::<f64>() / values.len() as f64
+        };
+        // sum of squared deviations from the average
+        let squares = |values: &[&(f64, bool)], avg: f64| {
+            values
+                .iter()
+                .map(|(v, _)| (v - avg) * (v - avg))
+                .sum::<f64>()
+        };
+
+        let (n1, n0, n) = (won.len() as f64, lost.len() as f64, samples.len() as f64);
+        let win_avg = avg(&won);
+        let lose_avg = avg(&lost);
+        let total_avg = (win_avg * n1 + lose_avg * n0) / n;
+        let total_sd = ((squares(&won, total_avg) + squares(&lost, total_avg)) / n).sqrt();
+        let pooled_sd = ((squares(&won, win_avg) + squares(&lost, lose_avg)) / (n - 2.0)).sqrt();
+        if total_sd == 0.0 || pooled_sd == 0.0 {
+            return None;
+        }
+
+        Some(OutcomeCorrelation {
+            wins: won.len() as u64,
+            loses: lost.len() as u64,
+            win_avg,
+            lose_avg,
+            correlation: (win_avg - lose_avg) / total_sd * (n1 * n0 / (n * n)).sqrt(),
+            effect_size: (win_avg - lose_avg) / pooled_sd,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn correlation_and_effect_size() {
+        let samples = [
+            (5.0, true),
+            (7.0, true),
+            (6.0, true),
+            (1.0, false),
+            (3.0, false),
+            (2.0, false),
+        ];
+        let res = OutcomeCorrelation::compute(&samples).unwrap();
+        assert_eq!((res.wins, res.loses), (3, 3));
+        assert_eq!((res.win_avg, res.lose_avg), (6.0, 2.0));
+        // pooled standard deviation is 1
+        assert!((res.effect_size - 4.0).abs() < 1e-9);
+        // same as the pearson correlation with the 0/1 outcome
+        assert!((res.correlation - 0.9258).abs() < 1e-4);
+
+        let reversed = samples.map(|(v, won)| (v, !won));
+        let res = OutcomeCorrelation::compute(&reversed).unwrap();
+        assert!((res.effect_size + 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn not_enough_data() {
+        assert!(OutcomeCorrelation::compute(&[(1.0, true), (2.0, true), (3.0, false)]).is_none());
+        let same = [(1.0, true), (1.0, true), (1.0, false), (1.0, false)];
+        assert!(OutcomeCorrelation::compute(&same).is_none());
+    }
+}
@@ -4,11 +4,17 @@ pub struct Rating {
     pub mu: f64,
     /// uncertainty value
     pub sigma: f64,
+    /// performance volatility, only tracked by some algorithms (e.g. Glicko-2)
+    pub volatility: Option<f64>,
 }
 
 impl Rating {
     pub fn new(mu: f64, sigma: f64) -> Rating {
-        Self { mu, sigma }
+        Self {
+            mu,
+            sigma,
+            volatility: None,
+        }
     }
 
     pub fn score(&self, uncertainty_coefficient: f64) -> f64 {
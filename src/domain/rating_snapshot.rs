@@ -0,0 +1,14 @@
+use chrono::{DateTime, Utc};
+
+use crate::domain::{BotId, Rating};
+
+/// Bot rating and rank on a leaderboard at some point in time
+#[derive(Clone)]
+pub struct RatingSnapshot {
+    pub bot_id: BotId,
+    pub rating: Rating,
+    pub rank: usize,
+    /// amount of leaderboard matches at the moment of snapshot
+    pub total_matches: u64,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,494 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use rand::seq::SliceRandom;
+use rand::{random, rng};
+
+use crate::domain::{BotId, TournamentGameId, TournamentId, TournamentName};
+
+pub struct Tournament {
+    pub id: TournamentId,
+    pub name: TournamentName,
+    pub format: TournamentFormat,
+    pub games_per_pair: u32,
+    pub bot_ids: Vec<BotId>,
+    pub games: Vec<TournamentGame>,
+    pub finished: bool,
+    pub created_at: DateTime<Utc>,
+    /// deleted bots, they stay in the standings but are not paired anymore
+    pub withdrawn: HashSet<BotId>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TournamentFormat {
+    /// every pair of bots plays `games_per_pair` games
+    RoundRobin,
+    /// each round bots with similar points are paired against each other
+    Swiss { rounds: u32 },
+}
+
+#[derive(Clone)]
+pub struct TournamentGame {
+    pub id: TournamentGameId,
+    pub round: u32,
+    pub seed: i64,
+    /// participants in seat order, a single participant means a bye
+    pub bot_ids: Vec<BotId>,
+    /// `None` until the game is finished
+    pub ranks: Option<Vec<u8>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TournamentStanding {
+    pub bot_id: BotId,
+    pub points: f64,
+    /// sum of the points of all the opponents the bot was paired with
+    pub buchholz: f64,
+    pub wins: u64,
+    pub draws: u64,
+    pub loses: u64,
+    pub byes: u64,
+}
+
+impl TournamentGame {
+    fn new(round: u32, seed: i64, bot_ids: Vec<BotId>) -> Self {
+        // byes are finished right away
+        let ranks = if bot_ids.len() == 1 {
+            Some(vec![0])
+        } else {
+            None
+        };
+        Self {
+            id: TournamentGameId::UNINITIALIZED,
+            round,
+            seed,
+            bot_ids,
+            ranks,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.ranks.is_some()
+    }
+
+    pub fn is_bye(&self) -> bool {
+        self.bot_ids.len() == 1
+    }
+}
+
+impl Tournament {
+    pub fn new(
+        name: TournamentName,
+        format: TournamentFormat,
+        games_per_pair: u32,
+        bot_ids: Vec<BotId>,
+    ) -> Self {
+        Self {
+            id: TournamentId::UNINITIALIZED,
+            name,
+            format,
+            games_per_pair,
+            bot_ids,
+            games: vec![],
+            finished: false,
+            created_at: Utc::now(),
+            withdrawn: HashSet::new(),
+        }
+    }
+
+    pub fn total_rounds(&self) -> u32 {
+        match self.format {
+            TournamentFormat::RoundRobin => 1,
+            TournamentFormat::Swiss { rounds } => rounds,
+        }
+    }
+
+    pub fn current_round(&self) -> u32 {
+        self.games.iter().map(|g| g.round).max().unwrap_or(0)
+    }
+
+    pub fn is_current_round_finished(&self) -> bool {
+        let round = self.current_round();
+        self.games
+            .iter()
+            .filter(|g| g.round == round)
+            .all(|g| g.is_finished())
+    }
+
+    pub fn finished_games(&self) -> usize {
+        self.games.iter().filter(|g| g.is_finished()).count()
+    }
+
+    /// Plans the games of the next round.
+    /// For non-symmetric games every seed is played once per seat order.
+    pub fn plan_next_round(&self, symmetric: bool) -> Vec<TournamentGame> {
+        let round = self.current_round() + 1;
+        let pairs = match self.format {
+            TournamentFormat::RoundRobin => self
+                .bot_ids
+                .iter()
+                .copied()
+                .filter(|id| !self.withdrawn.contains(id))
+                .tuple_combinations()
+                .map(|(a, b)| (a, Some(b)))
+                .collect_vec(),
+            TournamentFormat::Swiss { .. } => self.swiss_pairings(),
+        };
+
+        let mut rng = rng();
+        let mut games = vec![];
+        for (a, b) in pairs {
+            for _ in 0..self.games_per_pair {
+                let seed: i64 = random();
+                match b {
+                    None => {
+                        // a bye is worth as many points as winning every game of the pairing
+                        let cnt = if symmetric { 1 } else { 2 };
+                        for _ in 0..cnt {
+                            games.push(TournamentGame::new(round, seed, vec![a]));
+                        }
+                    }
+                    Some(b) if symmetric => {
+                        let mut bot_ids = vec![a, b];
+                        bot_ids.shuffle(&mut rng);
+                        games.push(TournamentGame::new(round, seed, bot_ids));
+                    }
+                    Some(b) => {
+                        games.push(TournamentGame::new(round, seed, vec![a, b]));
+                        games.push(TournamentGame::new(round, seed, vec![b, a]));
+                    }
+                }
+            }
+        }
+        games
+    }
+
+    fn swiss_pairings(&self) -> Vec<(BotId, Option<BotId>)> {
+        let mut order = if self.games.is_empty() {
+            let mut ids = self.bot_ids.clone();
+            ids.shuffle(&mut rng());
+            ids
+        } else {
+            self.standings().into_iter().map(|s| s.bot_id).collect_vec()
+        };
+        order.retain(|id| !self.withdrawn.contains(id));
+        if order.is_empty() {
+            return vec![];
+        }
+
+        let played: HashSet<(BotId, BotId)> = self
+            .games
+            .iter()
+            .filter(|g| !g.is_bye())
+            .flat_map(|g| [(g.bot_ids[0], g.bot_ids[1]), (g.bot_ids[1], g.bot_ids[0])])
+            .collect();
+
+        let mut pairs = Vec::with_capacity(order.len() / 2 + 1);
+
+        if order.len() % 2 == 1 {
+            // the lowest ranked bot which didn't have a bye yet gets one
+            let had_bye: HashSet<BotId> = self
+                .games
+                .iter()
+                .filter(|g| g.is_bye())
+                .map(|g| g.bot_ids[0])
+                .collect();
+            let index = order
+                .iter()
+                .rposition(|id| !had_bye.contains(id))
+                .unwrap_or(order.len() - 1);
+            pairs.push((order.remove(index), None));
+        }
+
+        while !order.is_empty() {
+            let a = order.remove(0);
+            // closest in standings opponent which was not played yet, rematch if there is no such
+            let index = order
+                .iter()
+                .position(|&b| !played.contains(&(a, b)))
+                .unwrap_or(0);
+            let b = order.remove(index);
+            pairs.push((a, Some(b)));
+        }
+
+        pairs
+    }
+
+    /// Returns false if there is no such game or the ranks don't match its participants.
+    pub fn record_result(&mut self, game_id: TournamentGameId, ranks: Vec<u8>) -> bool {
+        let Some(game) = self.games.iter_mut().find(|g| g.id == game_id) else {
+            return false;
+        };
+        if game.bot_ids.len() != ranks.len() {
+            return false;
+        }
+        game.ranks = Some(ranks);
+        true
+    }
+
+    /// Makes the bot lose all its unfinished games and excludes it from the next rounds,
+    /// returns ids of the affected games.
+    pub fn withdraw(&mut self, bot_id: BotId) -> Vec<TournamentGameId> {
+        self.withdrawn.insert(bot_id);
+        let mut affected = vec![];
+        for game in &mut self.games {
+            if game.is_finished() || !game.bot_ids.contains(&bot_id) {
+                continue;
+            }
+            game.ranks = Some(
+                game.bot_ids
+                    .iter()
+                    .map(|&id| if id == bot_id { 1 } else { 0 })
+                    .collect(),
+            );
+            affected.push(game.id);
+        }
+        affected
+    }
+
+    /// Standings sorted by points and Buchholz tiebreak, best bot first.
+    pub fn standings(&self) -> Vec<TournamentStanding> {
+        let mut standings: HashMap<BotId, TournamentStanding> = HashMap::new();
+        let mut opponents: HashMap<BotId, HashSet<(u32, BotId)>> = HashMap::new();
+
+        let entry = |standings: &mut HashMap<BotId, TournamentStanding>, bot_id: BotId| {
+            standings.entry(bot_id).or_insert(TournamentStanding {
+                bot_id,
+                points: 0.0,
+                buchholz: 0.0,
+                wins: 0,
+                draws: 0,
+                loses: 0,
+                byes: 0,
+            });
+        };
+
+        for &bot_id in &self.bot_ids {
+            entry(&mut standings, bot_id);
+        }
+
+        for game in &self.games {
+            let Some(ranks) = &game.ranks else {
+                continue;
+            };
+            for &bot_id in &game.bot_ids {
+                entry(&mut standings, bot_id);
+            }
+
+            if game.is_bye() {
+                let s = standings.get_mut(&game.bot_ids[0]).unwrap();
+                s.points += 1.0;
+                s.byes += 1;
+                continue;
+            }
+
+            let (a, b) = (game.bot_ids[0], game.bot_ids[1]);
+            let (a_points, b_points) = match ranks[0].cmp(&ranks[1]) {
+                Ordering::Less => (1.0, 0.0),
+                Ordering::Equal => (0.5, 0.5),
+                Ordering::Greater => (0.0, 1.0),
+            };
+            for (bot_id, points) in [(a, a_points), (b, b_points)] {
+                let s = standings.get_mut(&bot_id).unwrap();
+                s.points += points;
+                if points == 1.0 {
+                    s.wins += 1;
+                } else if points == 0.5 {
+                    s.draws += 1;
+                } else {
+                    s.loses += 1;
+                }
+            }
+            opponents.entry(a).or_default().insert((game.round, b));
+            opponents.entry(b).or_default().insert((game.round, a));
+        }
+
+        let points: HashMap<BotId, f64> = standings.iter().map(|(k, v)| (*k, v.points)).collect();
+        for (bot_id, opps) in opponents {
+            standings.get_mut(&bot_id).unwrap().buchholz =
+                opps.iter().map(|(_, opp)| points[opp]).sum();
+        }
+
+        // keeping the initial bot order for full ties
+        let order: HashMap<BotId, usize> = self
+            .bot_ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (*id, i))
+            .collect();
+
+        standings
+            .into_values()
+            .sorted_by(|a, b| {
+                b.points
+                    .total_cmp(&a.points)
+                    .then(b.buchholz.total_cmp(&a.buchholz))
+                    .then(
+                        order
+                            .get(&a.bot_id)
+                            .unwrap_or(&usize::MAX)
+                            .cmp(order.get(&b.bot_id).unwrap_or(&usize::MAX)),
+                    )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bot_ids(ids: &[i64]) -> Vec<BotId> {
+        ids.iter().map(|&id| id.into()).collect()
+    }
+
+    fn tournament(format: TournamentFormat, games_per_pair: u32, ids: &[i64]) -> Tournament {
+        Tournament::new(
+            "test".to_string().try_into().unwrap(),
+            format,
+            games_per_pair,
+            bot_ids(ids),
+        )
+    }
+
+    fn add_round(t: &mut Tournament, symmetric: bool) {
+        let first_id = t.games.len() as i64 + 1;
+        for (id, mut game) in (first_id..).zip(t.plan_next_round(symmetric)) {
+            game.id = id.into();
+            t.games.push(game);
+        }
+    }
+
+    /// lower bot id always wins
+    fn finish_round(t: &mut Tournament) {
+        for game in &mut t.games {
+            if game.is_finished() {
+                continue;
+            }
+            let a: i64 = game.bot_ids[0].into();
+            let b: i64 = game.bot_ids[1].into();
+            game.ranks = Some(if a < b { vec![0, 1] } else { vec![1, 0] });
+        }
+    }
+
+    #[test]
+    fn round_robin_plans_every_pair() {
+        let mut t = tournament(TournamentFormat::RoundRobin, 3, &[1, 2, 3, 4]);
+        add_round(&mut t, true);
+        assert_eq!(t.games.len(), 6 * 3);
+        assert!(t.games.iter().all(|g| g.round == 1 && !g.is_finished()));
+
+        let mut t = tournament(TournamentFormat::RoundRobin, 3, &[1, 2, 3, 4]);
+        add_round(&mut t, false);
+        assert_eq!(t.games.len(), 6 * 3 * 2);
+    }
+
+    #[test]
+    fn round_robin_standings() {
+        let mut t = tournament(TournamentFormat::RoundRobin, 2, &[3, 2, 1]);
+        add_round(&mut t, true);
+        finish_round(&mut t);
+        assert!(t.is_current_round_finished());
+
+        let standings = t.standings();
+        let ids: Vec<i64> = standings.iter().map(|s| s.bot_id.into()).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert_eq!(standings[0].points, 4.0);
+        assert_eq!(standings[0].wins, 4);
+        assert_eq!(standings[1].points, 2.0);
+        assert_eq!(standings[2].points, 0.0);
+        assert_eq!(standings[2].loses, 4);
+        // bot 3 faced bot 1 (4 points) and bot 2 (2 points)
+        assert_eq!(standings[2].buchholz, 6.0);
+    }
+
+    #[test]
+    fn swiss_gives_bye_for_odd_bot_count() {
+        let mut t = tournament(TournamentFormat::Swiss { rounds: 3 }, 1, &[1, 2, 3]);
+        add_round(&mut t, true);
+        assert_eq!(t.games.len(), 2);
+        assert_eq!(t.games.iter().filter(|g| g.is_bye()).count(), 1);
+        finish_round(&mut t);
+
+        add_round(&mut t, true);
+        let byes = t
+            .games
+            .iter()
+            .filter(|g| g.is_bye())
+            .map(|g| g.bot_ids[0])
+            .collect_vec();
+        assert_eq!(byes.len(), 2);
+        assert_ne!(byes[0], byes[1], "same bot should not get a bye twice");
+    }
+
+    #[test]
+    fn swiss_avoids_rematches() {
+        let mut t = tournament(TournamentFormat::Swiss { rounds: 3 }, 1, &[1, 2, 3, 4]);
+        for _ in 0..3 {
+            add_round(&mut t, true);
+            finish_round(&mut t);
+        }
+        assert_eq!(t.current_round(), 3);
+
+        let pairs = t
+            .games
+            .iter()
+            .map(|g| {
+                let mut ids: Vec<i64> = g.bot_ids.iter().map(|&id| id.into()).collect();
+                ids.sort();
+                ids
+            })
+            .collect_vec();
+        assert_eq!(pairs.len(), 6);
+        assert!(pairs.iter().all_unique());
+
+        let standings = t.standings();
+        assert_eq!(i64::from(standings[0].bot_id), 1);
+        assert_eq!(standings[0].points, 3.0);
+    }
+
+    #[test]
+    fn withdraw_finishes_games_of_the_bot() {
+        let mut t = tournament(TournamentFormat::RoundRobin, 1, &[1, 2, 3]);
+        add_round(&mut t, true);
+        let affected = t.withdraw(2.into());
+        assert_eq!(affected.len(), 2);
+        let standings = t.standings();
+        assert_eq!(i64::from(standings[2].bot_id), 2);
+        assert_eq!(standings[2].loses, 2);
+    }
+
+    #[test]
+    fn swiss_does_not_pair_withdrawn_bot() {
+        let mut t = tournament(TournamentFormat::Swiss { rounds: 3 }, 1, &[1, 2, 3, 4]);
+        add_round(&mut t, true);
+        finish_round(&mut t);
+        t.withdraw(3.into());
+
+        add_round(&mut t, true);
+        let round = t.games.iter().filter(|g| g.round == 2).collect_vec();
+        assert!(round.iter().all(|g| !g.bot_ids.contains(&3.into())));
+        // the three remaining bots, one of them gets a bye
+        assert_eq!(round.len(), 2);
+        finish_round(&mut t);
+        assert!(t.is_current_round_finished());
+
+        add_round(&mut t, true);
+        finish_round(&mut t);
+        assert!(t.is_current_round_finished());
+        assert_eq!(t.standings().len(), 4);
+    }
+
+    #[test]
+    fn record_result_rejects_wrong_ranks() {
+        let mut t = tournament(TournamentFormat::RoundRobin, 1, &[1, 2]);
+        add_round(&mut t, true);
+        let game_id = t.games[0].id;
+        assert!(!t.record_result(game_id, vec![0, 1, 2]));
+        assert!(!t.games[0].is_finished());
+        assert!(t.record_result(game_id, vec![0, 1]));
+        assert!(t.games[0].is_finished());
+    }
+}
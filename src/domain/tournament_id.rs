@@ -0,0 +1,39 @@
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct TournamentId(i64);
+
+impl TournamentId {
+    pub const UNINITIALIZED: TournamentId = TournamentId(0);
+}
+
+impl From<i64> for TournamentId {
+    fn from(id: i64) -> Self {
+        assert_ne!(id, Self::UNINITIALIZED.0);
+        Self(id)
+    }
+}
+
+impl From<TournamentId> for i64 {
+    fn from(id: TournamentId) -> i64 {
+        id.0
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct TournamentGameId(i64);
+
+impl TournamentGameId {
+    pub const UNINITIALIZED: TournamentGameId = TournamentGameId(0);
+}
+
+impl From<i64> for TournamentGameId {
+    fn from(id: i64) -> Self {
+        assert_ne!(id, Self::UNINITIALIZED.0);
+        Self(id)
+    }
+}
+
+impl From<TournamentGameId> for i64 {
+    fn from(id: TournamentGameId) -> i64 {
+        id.0
+    }
+}
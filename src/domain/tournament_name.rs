@@ -0,0 +1,38 @@
+use anyhow::bail;
+use std::ops::Deref;
+
+#[derive(Eq, PartialEq, Hash, Clone, Debug)]
+pub struct TournamentName(String);
+
+impl TryFrom<String> for TournamentName {
+    type Error = anyhow::Error;
+
+    fn try_from(src: String) -> Result<Self, Self::Error> {
+        if src.is_empty() {
+            bail!("TournamentName cannot be empty");
+        }
+        if src.len() >= LEN_LIMIT {
+            bail!(
+                "TournamentName should be less than {} characters",
+                LEN_LIMIT
+            );
+        }
+        Ok(Self(src))
+    }
+}
+
+impl From<TournamentName> for String {
+    fn from(value: TournamentName) -> Self {
+        value.0
+    }
+}
+
+impl Deref for TournamentName {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+const LEN_LIMIT: usize = 64;
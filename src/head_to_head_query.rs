@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use itertools::Itertools;
+use sqlx::SqlitePool;
+
+use crate::{
+    db,
+    domain::{BotId, BotTags, HeadToHead, MatchFilter},
+    ranking::ErrorPolicy,
+};
+
+/// Compares the bots over the leaderboard matches both of them played in
+pub async fn compare(
+    filter: MatchFilter,
+    a: BotId,
+    b: BotId,
+    error_policy: ErrorPolicy,
+    bot_tags: Arc<BotTags>,
+    pool: SqlitePool,
+) -> anyhow::Result<HeadToHead> {
+    let condition = filter.sql_condition();
+    let needed_attrs = filter.needed_attributes();
+
+    let (matches, _) = db::fetch_matches_paged(
+        &pool,
+        condition.as_deref(),
+        &[a, b],
+        None,
+        None,
+        &needed_attrs,
+        |m| filter.matches(m, &bot_tags),
+    )
+    .await?;
+
+    // pages are fetched newest first
+    let matches = matches.iter().rev().collect_vec();
+    Ok(HeadToHead::compute(a, b, &matches, error_policy))
+}
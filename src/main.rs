@@ -10,6 +10,8 @@ mod chart;
 mod config;
 mod db;
 mod domain;
+mod head_to_head_query;
+mod match_browser;
 mod matchmaking;
 mod ranking;
 mod worker;
@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use sqlx::SqlitePool;
+
+use crate::{
+    arena_commands::MatchesPage,
+    db,
+    domain::{BotId, BotTags, MatchFilter, MatchId},
+};
+
+pub async fn browse(
+    filter: MatchFilter,
+    bot_id: Option<BotId>,
+    limit: usize,
+    cursor: Option<MatchId>,
+    bot_tags: Arc<BotTags>,
+    pool: SqlitePool,
+) -> anyhow::Result<MatchesPage> {
+    let condition = filter.sql_condition();
+    let needed_attrs = filter.needed_attributes();
+
+    let (matches, limit_reached) = db::fetch_matches_paged(
+        &pool,
+        condition.as_deref(),
+        bot_id.as_slice(),
+        cursor,
+        Some(limit),
+        &needed_attrs,
+        |m| filter.matches(m, &bot_tags),
+    )
+    .await?;
+
+    let next_cursor = if limit_reached {
+        matches.last().map(|m| m.id)
+    } else {
+        None
+    };
+    Ok(MatchesPage {
+        matches,
+        next_cursor,
+    })
+}
@@ -2,7 +2,10 @@ use std::collections::HashMap;
 
 use crate::config::{GameConfig, MatchmakingConfig};
 use crate::domain::BotId;
+use chrono::{DateTime, TimeDelta, Utc};
 use itertools::Itertools;
+use rand::distr::weighted::WeightedIndex;
+use rand::distr::Distribution;
 use rand::prelude::SliceRandom;
 use rand::{random, rng, Rng};
 use serde::{Deserialize, Serialize};
@@ -31,10 +34,37 @@ pub struct MatchmakingAlgorithmV2Config {
     pub max_matches: Option<u64>,
 }
 
+/// Reserves a share of the matches for the recently submitted bots,
+/// so their rank becomes known quickly.
+#[derive(Serialize, Deserialize)]
+pub struct NewcomersConfig {
+    /// bots created less than this amount of minutes ago are newcomers
+    pub max_age_minutes: Option<u64>,
+    /// bots with less than this amount of matches are newcomers
+    pub max_matches: Option<u64>,
+    /// share of the matches (0.0 - 1.0) dedicated to newcomers
+    pub share: f64,
+    /// newcomers are matched against a random bot among the top-K by rating
+    pub top_k: Option<usize>,
+}
+
+impl NewcomersConfig {
+    fn is_newcomer(&self, candidate: &Candidate, now: DateTime<Utc>) -> bool {
+        let young = self
+            .max_age_minutes
+            .is_some_and(|minutes| now - candidate.created_at < TimeDelta::minutes(minutes as i64));
+        let few_matches = self
+            .max_matches
+            .is_some_and(|max_matches| candidate.matches_total < max_matches);
+        young || few_matches
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Candidate {
     pub id: BotId,
     pub rating: f64,
+    pub created_at: DateTime<Utc>,
     // the numbers below should include queued matches
     pub matches_total: u64,
     pub matches_vs: HashMap<BotId, u64>,
@@ -54,9 +84,17 @@ pub fn create_match(
         return vec![];
     }
 
-    let n_players = rng().random_range(game_config.min_players..=game_config.max_players) as usize;
+    // there could be less ready bots than the picked player count
+    let n_players = pick_player_count(game_config).min(candidates.len());
+
+    // newcomers take their share of the matches before the regular algorithm
+    let newcomers_bot_ids = matchmaking_config
+        .newcomers
+        .as_ref()
+        .filter(|config| rng().random::<f64>() < config.share)
+        .and_then(|config| pick_participants_newcomers(n_players, config, candidates, Utc::now()));
 
-    let bot_ids = match &matchmaking_config.algorithm {
+    let bot_ids = newcomers_bot_ids.or_else(|| match &matchmaking_config.algorithm {
         MatchmakingAlgorithmConfig::V1(matchmaking_algorithm_v1_config) => {
             pick_participants_v1(n_players, matchmaking_algorithm_v1_config, candidates)
         }
@@ -66,7 +104,7 @@ pub fn create_match(
         MatchmakingAlgorithmConfig::Legacy(matchmaking_algorithm_v1_config) => {
             pick_participants_v1(n_players, matchmaking_algorithm_v1_config, candidates)
         }
-    };
+    });
 
     let Some(bot_ids) = bot_ids else {
         return vec![];
@@ -89,6 +127,20 @@ pub fn create_match(
     }
 }
 
+fn pick_player_count(game_config: &GameConfig) -> usize {
+    let mut rng = rng();
+    let n_players = match &game_config.player_count_weights {
+        Some(weights) => {
+            let index = WeightedIndex::new(weights)
+                .expect("player count weights should be validated")
+                .sample(&mut rng);
+            game_config.min_players + index as u32
+        }
+        None => rng.random_range(game_config.min_players..=game_config.max_players),
+    };
+    n_players as usize
+}
+
 fn pick_participants_v1(
     n_players: usize,
     matchmaking_config: &MatchmakingAlgorithmV1Config,
@@ -197,6 +249,50 @@ fn pick_participants_v2(
     Some(players)
 }
 
+fn pick_participants_newcomers(
+    n_players: usize,
+    config: &NewcomersConfig,
+    candidates: &[Candidate],
+    now: DateTime<Utc>,
+) -> Option<Vec<BotId>> {
+    let mut rng = rng();
+
+    let (newcomers, others): (Vec<_>, Vec<_>) =
+        candidates.iter().partition(|c| config.is_newcomer(c, now));
+
+    // the newcomer with the least matches goes first
+    let newcomer = newcomers.iter().min_by_key(|c| c.matches_total)?;
+    if n_players < 2 {
+        return Some(vec![newcomer.id]);
+    }
+
+    // prefer established bots as opponents, but fall back to other newcomers
+    let opponents = if others.is_empty() {
+        newcomers
+            .iter()
+            .filter(|c| c.id != newcomer.id)
+            .collect_vec()
+    } else {
+        others.iter().collect_vec()
+    };
+    let top_k = opponents
+        .into_iter()
+        .sorted_by(|a, b| b.rating.total_cmp(&a.rating))
+        .take(config.top_k.unwrap_or(5).max(1))
+        .collect_vec();
+    if top_k.is_empty() {
+        return None;
+    }
+    let opponent = top_k[rng.random_range(0..top_k.len())];
+
+    let mut players = Vec::with_capacity(n_players);
+    players.push(newcomer.id);
+    players.push(opponent.id);
+    backfill_random(n_players, &mut players, candidates);
+    players.shuffle(&mut rng);
+    Some(players)
+}
+
 fn backfill_random(n_players: usize, players: &mut Vec<BotId>, candidates: &[Candidate]) {
     let mut rng = rng();
     while players.len() < n_players {
@@ -227,18 +323,21 @@ mod test {
             Candidate {
                 id: 1.into(),
                 rating: 2.0,
+                created_at: Default::default(),
                 matches_total: 5,
                 matches_vs: [(2.into(), 3), (3.into(), 2)].into(),
             },
             Candidate {
                 id: 2.into(),
                 rating: 1.0,
+                created_at: Default::default(),
                 matches_total: 3,
                 matches_vs: [(1.into(), 3), (3.into(), 0)].into(),
             },
             Candidate {
                 id: 3.into(),
                 rating: 1.0,
+                created_at: Default::default(),
                 matches_total: 2,
                 matches_vs: [(1.into(), 2), (2.into(), 0)].into(),
             },
@@ -266,18 +365,21 @@ mod test {
             Candidate {
                 id: 1.into(),
                 rating: 2.0,
+                created_at: Default::default(),
                 matches_total: 5,
                 matches_vs: [(2.into(), 3), (3.into(), 2)].into(),
             },
             Candidate {
                 id: 2.into(),
                 rating: 1.0,
+                created_at: Default::default(),
                 matches_total: 3,
                 matches_vs: [(1.into(), 3), (3.into(), 0)].into(),
             },
             Candidate {
                 id: 3.into(),
                 rating: 1.0,
+                created_at: Default::default(),
                 matches_total: 2,
                 matches_vs: [(1.into(), 2), (2.into(), 0)].into(),
             },
@@ -305,18 +407,21 @@ mod test {
             Candidate {
                 id: 1.into(),
                 rating: 3.0,
+                created_at: Default::default(),
                 matches_total: 3,
                 matches_vs: [(2.into(), 3), (3.into(), 2)].into(),
             },
             Candidate {
                 id: 2.into(),
                 rating: 2.0,
+                created_at: Default::default(),
                 matches_total: 3,
                 matches_vs: [(1.into(), 3), (3.into(), 1)].into(),
             },
             Candidate {
                 id: 3.into(),
                 rating: 1.0,
+                created_at: Default::default(),
                 matches_total: 2,
                 matches_vs: [(1.into(), 2), (2.into(), 1)].into(),
             },
@@ -331,4 +436,118 @@ mod test {
 
         assert_eq!(&bot_ids, &[1, 3]);
     }
+
+    fn newcomers_candidates(now: DateTime<Utc>) -> Vec<Candidate> {
+        let old = now - TimeDelta::days(1);
+        vec![
+            Candidate {
+                id: 1.into(),
+                rating: 3.0,
+                created_at: old,
+                matches_total: 100,
+                matches_vs: [(2.into(), 50), (3.into(), 50), (4.into(), 0)].into(),
+            },
+            Candidate {
+                id: 2.into(),
+                rating: 2.0,
+                created_at: old,
+                matches_total: 100,
+                matches_vs: [(1.into(), 50), (3.into(), 50), (4.into(), 0)].into(),
+            },
+            Candidate {
+                id: 3.into(),
+                rating: 1.0,
+                created_at: old,
+                matches_total: 100,
+                matches_vs: [(1.into(), 50), (2.into(), 50), (4.into(), 0)].into(),
+            },
+            Candidate {
+                id: 4.into(),
+                rating: 0.0,
+                created_at: now - TimeDelta::minutes(1),
+                matches_total: 0,
+                matches_vs: [(1.into(), 0), (2.into(), 0), (3.into(), 0)].into(),
+            },
+        ]
+    }
+
+    #[test]
+    fn newcomer_plays_against_top_k() {
+        let config = NewcomersConfig {
+            max_age_minutes: Some(10),
+            max_matches: None,
+            share: 1.0,
+            top_k: Some(2),
+        };
+        let now = Utc::now();
+        let candidates = newcomers_candidates(now);
+
+        for _ in 0..20 {
+            let bot_ids: Vec<i64> = pick_participants_newcomers(2, &config, &candidates, now)
+                .unwrap()
+                .into_iter()
+                .map(|id| id.into())
+                .sorted()
+                .collect();
+
+            assert!(bot_ids == [1, 4] || bot_ids == [2, 4]);
+        }
+    }
+
+    #[test]
+    fn newcomer_by_match_count() {
+        let config = NewcomersConfig {
+            max_age_minutes: None,
+            max_matches: Some(10),
+            share: 1.0,
+            top_k: Some(1),
+        };
+        let now = Utc::now();
+        let mut candidates = newcomers_candidates(now);
+        candidates[3].created_at = now - TimeDelta::days(1);
+
+        let bot_ids: Vec<i64> = pick_participants_newcomers(2, &config, &candidates, now)
+            .unwrap()
+            .into_iter()
+            .map(|id| id.into())
+            .sorted()
+            .collect();
+        assert_eq!(&bot_ids, &[1, 4]);
+
+        candidates[3].matches_total = 10;
+        assert!(pick_participants_newcomers(2, &config, &candidates, now).is_none());
+    }
+
+    #[test]
+    fn newcomer_plays_alone_in_single_player_match() {
+        let config = NewcomersConfig {
+            max_age_minutes: Some(10),
+            max_matches: None,
+            share: 1.0,
+            top_k: Some(2),
+        };
+        let now = Utc::now();
+        let candidates = newcomers_candidates(now);
+
+        let bot_ids: Vec<i64> = pick_participants_newcomers(1, &config, &candidates, now)
+            .unwrap()
+            .into_iter()
+            .map(|id| id.into())
+            .collect();
+        assert_eq!(&bot_ids, &[4]);
+    }
+
+    #[test]
+    fn player_count_weights() {
+        let game_config = GameConfig {
+            min_players: 2,
+            max_players: 4,
+            symmetric: true,
+            player_count_weights: Some(vec![0.0, 1.0, 0.0]),
+        };
+
+        for _ in 0..20 {
+            assert_eq!(pick_player_count(&game_config), 3);
+        }
+    }
 }
@@ -1,18 +1,40 @@
-use crate::domain::{BotId, Rating, WinrateStats};
+use crate::domain::{BotId, DecayedWinrateStats, Rating, WinrateStats};
+use crate::ranking::PerformanceNoise;
 use std::collections::HashMap;
 
 pub trait Algorithm {
     fn supports_multi_team(&self) -> bool;
     fn default_rating(&self) -> Rating;
+    /// Match outcome model the ratings are fitted to
+    fn performance_noise(&self) -> PerformanceNoise;
 }
 
 pub trait OnlineAlgorithm: Algorithm {
     fn recalc_ratings(&self, input: &[(Rating, u8)]) -> Vec<Rating>;
 }
 
+pub struct BatchInput<'a> {
+    pub winrate_stats: &'a HashMap<(BotId, BotId), WinrateStats>,
+    /// (bot, rank, no gain) of every match participant, one slice per match.
+    /// No gain is set for the errored participants under `ErrorPolicy::ExcludeOpponentGain`,
+    /// they lose as usual but nobody gains from beating them.
+    /// Empty unless the algorithm `needs_placements`.
+    pub placements: &'a [&'a [(BotId, u8, bool)]],
+    /// Set when the ranking decays older matches, to be used instead of `winrate_stats`
+    pub decayed_winrate_stats: Option<&'a HashMap<(BotId, BotId), DecayedWinrateStats>>,
+    /// Weight of every placement, set when the ranking decays older matches
+    pub placement_weights: Option<&'a [f64]>,
+    /// Bots with fixed rating mu
+    pub anchors: &'a HashMap<BotId, f64>,
+    /// Ratings of the previous fit, used as the starting point
+    pub previous: &'a HashMap<BotId, Rating>,
+}
+
 pub trait BatchAlgorithm: Algorithm {
-    fn recalc_batch(
-        &self,
-        winrate_stats: &HashMap<(BotId, BotId), WinrateStats>,
-    ) -> HashMap<BotId, Rating>;
+    /// Whether the algorithm fits on per-match placements rather than on winrate stats only
+    fn needs_placements(&self) -> bool {
+        false
+    }
+
+    fn recalc_batch(&self, input: &BatchInput) -> HashMap<BotId, Rating>;
 }
@@ -1,10 +1,10 @@
 use crate::domain::{BotId, Rating, WinrateStats};
-use crate::ranking::{Algorithm, BatchAlgorithm};
+use crate::ranking::{Algorithm, BatchAlgorithm, BatchInput, PerformanceNoise, ELO_SCALE};
 use nalgebra::{DMatrix, DVector};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Config {
     max_iter: Option<usize>,
 }
@@ -27,14 +27,33 @@ impl Algorithm for BradleyTerry {
     fn default_rating(&self) -> Rating {
         Rating::new(0f64, 0f64)
     }
+
+    fn performance_noise(&self) -> PerformanceNoise {
+        PerformanceNoise::Gumbel(ELO_SCALE)
+    }
 }
 
 impl BatchAlgorithm for BradleyTerry {
-    fn recalc_batch(
-        &self,
-        winrate_stats: &HashMap<(BotId, BotId), WinrateStats>,
-    ) -> HashMap<BotId, Rating> {
-        bradley_terry_bayesian(winrate_stats, self.config.max_iter.unwrap_or(50))
+    fn recalc_batch(&self, input: &BatchInput) -> HashMap<BotId, Rating> {
+        let scores = match input.decayed_winrate_stats {
+            Some(decayed) => decayed
+                .iter()
+                .map(|(&key, stats)| {
+                    (
+                        key,
+                        stats.wins + 0.5 * stats.draws,
+                        stats.loses + 0.5 * stats.draws,
+                    )
+                })
+                .collect(),
+            None => pair_scores(input.winrate_stats),
+        };
+        bradley_terry_bayesian(
+            &scores,
+            input.anchors,
+            input.previous,
+            self.config.max_iter.unwrap_or(50),
+        )
     }
 }
 
@@ -46,6 +65,12 @@ struct Pair {
     wins_ji: f64,
 }
 
+/// Anchored bots are pinned to their ratings, the others are pulled towards `mean`
+struct Prior {
+    mean: f64,
+    fixed: Vec<Option<f64>>,
+}
+
 fn sigmoid(x: f64) -> f64 {
     1.0 / (1.0 + (-x).exp())
 }
@@ -58,6 +83,7 @@ fn compute_grad_hess(
     pairs: &[Pair],
     s: &DVector<f64>,
     tau: f64, // prior stddev in natural scale
+    prior: &Prior,
 ) -> (DVector<f64>, DMatrix<f64>) {
     let mut grad = DVector::<f64>::zeros(n);
     let mut hess = DMatrix::<f64>::zeros(n, n);
@@ -87,28 +113,47 @@ fn compute_grad_hess(
         hess[(j, i)] += weight;
     }
 
-    // Gaussian prior: s_i ~ N(0, tau²)
+    // Gaussian prior: s_i ~ N(mean, tau²)
     let prior_prec = 1.0 / (tau * tau);
 
     for i in 0..n {
-        grad[i] -= s[i] * prior_prec;
+        grad[i] -= (s[i] - prior.mean) * prior_prec;
         hess[(i, i)] -= prior_prec;
     }
 
+    // Anchors: zero step, unit diagonal keeps the Hessian invertible
+    for (i, fixed) in prior.fixed.iter().enumerate() {
+        if fixed.is_some() {
+            grad[i] = 0.0;
+            hess.row_mut(i).fill(0.0);
+            hess.column_mut(i).fill(0.0);
+            hess[(i, i)] = -1.0;
+        }
+    }
+
     (grad, hess)
 }
 
 // ------------------------------------------------------------
-// Fit MAP using Newton method
+// Fit MAP using Newton method,
+// `None` if the Hessian is singular or the step is not finite
 // ------------------------------------------------------------
-fn fit_map(n: usize, pairs: &[Pair], tau: f64, max_iter: usize) -> (DVector<f64>, DMatrix<f64>) {
-    let mut s = DVector::<f64>::zeros(n);
-
+fn fit_map(
+    n: usize,
+    pairs: &[Pair],
+    tau: f64,
+    prior: &Prior,
+    mut s: DVector<f64>,
+    max_iter: usize,
+) -> Option<(DVector<f64>, DMatrix<f64>)> {
     for _ in 0..max_iter {
-        let (grad, hess) = compute_grad_hess(n, pairs, &s, tau);
+        let (grad, hess) = compute_grad_hess(n, pairs, &s, tau, prior);
 
         let fisher = -&hess;
-        let step = fisher.lu().solve(&grad).expect("Newton step failed");
+        let step = fisher.lu().solve(&grad)?;
+        if !step.iter().all(|x| x.is_finite()) {
+            return None;
+        }
 
         s += &step;
 
@@ -117,33 +162,50 @@ fn fit_map(n: usize, pairs: &[Pair], tau: f64, max_iter: usize) -> (DVector<f64>
         }
     }
 
-    let (_, hess) = compute_grad_hess(n, pairs, &s, tau);
+    let (_, hess) = compute_grad_hess(n, pairs, &s, tau, prior);
     let fisher = -hess;
 
-    let covariance = fisher.try_inverse().expect("Hessian inversion failed");
+    let covariance = fisher.try_inverse()?;
+
+    Some((s, covariance))
+}
 
-    (s, covariance)
+/// (bots, wins of the first bot, wins of the second bot), draws count as half a win
+type PairScore = ((BotId, BotId), f64, f64);
+
+fn pair_scores(winrate_stats: &HashMap<(BotId, BotId), WinrateStats>) -> Vec<PairScore> {
+    winrate_stats
+        .iter()
+        .map(|(&key, stats)| {
+            let wins_ij = stats.wins as f64 + 0.5 * stats.draws as f64;
+            let wins_ji = stats.loses as f64 + 0.5 * stats.draws as f64;
+            (key, wins_ij, wins_ji)
+        })
+        .collect()
 }
 
 // ------------------------------------------------------------
 // Public API
 // ------------------------------------------------------------
 fn bradley_terry_bayesian(
-    winrate_stats: &HashMap<(BotId, BotId), WinrateStats>,
+    scores: &[PairScore],
+    anchors: &HashMap<BotId, f64>,
+    previous: &HashMap<BotId, Rating>,
     max_iter: usize,
 ) -> HashMap<BotId, Rating> {
     // 🚨 Handle empty dataset
-    if winrate_stats.is_empty() {
+    if scores.is_empty() {
         return HashMap::new();
     }
 
-    let scale = 400.0 / std::f64::consts::LN_10;
+    let scale = ELO_SCALE;
+    let base = 1500.0;
 
     // --------------------------------------------------------
     // Build index
     // --------------------------------------------------------
     let mut bots = HashMap::<BotId, usize>::new();
-    for (a, b) in winrate_stats.keys() {
+    for ((a, b), _, _) in scores {
         if !bots.contains_key(a) {
             let idx = bots.len();
             bots.insert(*a, idx);
@@ -162,12 +224,9 @@ fn bradley_terry_bayesian(
     // --------------------------------------------------------
     let mut pairs = Vec::<Pair>::new();
 
-    for ((a, b), stats) in winrate_stats {
-        let i = bots[a];
-        let j = bots[b];
-
-        let wins_ij = stats.wins as f64 + 0.5 * stats.draws as f64;
-        let wins_ji = stats.loses as f64 + 0.5 * stats.draws as f64;
+    for &((a, b), wins_ij, wins_ji) in scores {
+        let i = bots[&a];
+        let j = bots[&b];
 
         pairs.push(Pair {
             i,
@@ -177,6 +236,29 @@ fn bradley_terry_bayesian(
         });
     }
 
+    // --------------------------------------------------------
+    // Anchors define the location of the scale
+    // --------------------------------------------------------
+    let mut fixed = vec![None; n];
+    for (bot, &idx) in &bots {
+        fixed[idx] = anchors.get(bot).map(|mu| (mu - base) / scale);
+    }
+    let anchored = fixed.iter().flatten().copied().collect::<Vec<_>>();
+    let prior = Prior {
+        mean: anchored.iter().sum::<f64>() / anchored.len().max(1) as f64,
+        fixed,
+    };
+
+    // --------------------------------------------------------
+    // Warm start from the previous fit, new bots start at the prior mean
+    // --------------------------------------------------------
+    let mut start = DVector::<f64>::from_element(n, prior.mean);
+    for (bot, &idx) in &bots {
+        start[idx] = prior.fixed[idx]
+            .or_else(|| previous.get(bot).map(|r| (r.mu - base) / scale))
+            .unwrap_or(prior.mean);
+    }
+
     // --------------------------------------------------------
     // 1️⃣ Initial tau (weak prior)
     // --------------------------------------------------------
@@ -186,7 +268,10 @@ fn bradley_terry_bayesian(
     // --------------------------------------------------------
     // 2️⃣ First fit
     // --------------------------------------------------------
-    let (mut s, mut covariance) = fit_map(n, &pairs, tau, max_iter);
+    // a failed fit keeps the previous ratings instead of publishing broken ones
+    let Some((mut s, mut covariance)) = fit_map(n, &pairs, tau, &prior, start, max_iter) else {
+        return previous.clone();
+    };
 
     // --------------------------------------------------------
     // 3️⃣ Empirical Bayes auto-tuning of tau
@@ -213,7 +298,9 @@ fn bradley_terry_bayesian(
     // --------------------------------------------------------
     // 4️⃣ Refit with tuned tau
     // --------------------------------------------------------
-    let (s2, covariance2) = fit_map(n, &pairs, tau, max_iter);
+    let Some((s2, covariance2)) = fit_map(n, &pairs, tau, &prior, s, max_iter) else {
+        return previous.clone();
+    };
 
     s = s2;
     covariance = covariance2;
@@ -221,21 +308,26 @@ fn bradley_terry_bayesian(
     // --------------------------------------------------------
     // 5️⃣ Convert to Elo
     // --------------------------------------------------------
-    let base = 1500.0;
     let mut result = HashMap::new();
     let total_mean_covariance = covariance.iter().sum::<f64>() / (n * n) as f64;
 
     for (bot, idx) in bots {
-        let mu = base + s[idx] * scale;
+        let mu = anchors.get(&bot).copied().unwrap_or(base + s[idx] * scale);
         let row_mean_covariance = covariance.row(idx).iter().sum::<f64>() / n as f64;
-        // Bradley-Terry only identifies relative skill. Project uncertainty onto the
-        // zero-mean subspace so sigma reflects evidence about this bot relative to the field
-        // instead of the largely constant global-offset mode.
-        let variance =
-            (covariance[(idx, idx)] - 2.0 * row_mean_covariance + total_mean_covariance).max(0.0);
+        let variance = if prior.fixed[idx].is_some() {
+            0.0
+        } else if !anchored.is_empty() {
+            // anchors are known exactly, so the uncertainty is relative to them
+            covariance[(idx, idx)]
+        } else {
+            // Bradley-Terry only identifies relative skill. Project uncertainty onto the
+            // zero-mean subspace so sigma reflects evidence about this bot relative to the field
+            // instead of the largely constant global-offset mode.
+            (covariance[(idx, idx)] - 2.0 * row_mean_covariance + total_mean_covariance).max(0.0)
+        };
         let sigma = variance.sqrt() * scale;
 
-        result.insert(bot, Rating { mu, sigma });
+        result.insert(bot, Rating::new(mu, sigma));
     }
 
     result
@@ -259,11 +351,11 @@ mod tests {
             .into_iter()
             .map(|(key, value)| ((key.0.into(), key.1.into()), value))
             .collect();
-        let res = bradley_terry_bayesian(&stats, 50);
-        return res
-            .into_iter()
+        let res =
+            bradley_terry_bayesian(&pair_scores(&stats), &HashMap::new(), &HashMap::new(), 50);
+        res.into_iter()
             .map(|(key, value)| (key.into(), value))
-            .collect();
+            .collect()
     }
 
     #[test]
@@ -438,6 +530,58 @@ mod tests {
         }
     }
 
+    #[test]
+    fn anchors_keep_their_rating() {
+        let mut stats = HashMap::new();
+        stats.insert((1.into(), 2.into()), create_stats(30, 10, 0));
+        stats.insert((2.into(), 3.into()), create_stats(30, 10, 0));
+        let anchors = HashMap::from([(2.into(), 0.0)]);
+
+        let result: HashMap<i64, Rating> =
+            bradley_terry_bayesian(&pair_scores(&stats), &anchors, &HashMap::new(), 50)
+                .into_iter()
+                .map(|(key, value)| (key.into(), value))
+                .collect();
+
+        assert_eq!(result[&2].mu, 0.0);
+        assert_eq!(result[&2].sigma, 0.0);
+        assert!(result[&1].mu > 0.0);
+        assert!(result[&3].mu < 0.0);
+        assert!(result[&1].sigma > 0.0);
+    }
+
+    #[test]
+    fn failed_fit_keeps_previous_ratings() {
+        let scores = vec![((1.into(), 2.into()), f64::NAN, 1.0)];
+        let previous = HashMap::from([(1.into(), Rating::new(1600.0, 50.0))]);
+
+        let result = bradley_terry_bayesian(&scores, &HashMap::new(), &previous, 50);
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[&1.into()].mu, 1600.0);
+    }
+
+    #[test]
+    fn warm_start_converges_to_the_same_ratings() {
+        let mut stats = HashMap::new();
+        stats.insert((1.into(), 2.into()), create_stats(30, 10, 0));
+        stats.insert((2.into(), 3.into()), create_stats(25, 15, 5));
+        stats.insert((1.into(), 3.into()), create_stats(20, 5, 0));
+        let scores = pair_scores(&stats);
+
+        let cold = bradley_terry_bayesian(&scores, &HashMap::new(), &HashMap::new(), 50);
+        let previous = cold
+            .iter()
+            .map(|(&id, r)| (id, Rating::new(r.mu + 100.0, r.sigma)))
+            .collect();
+        let warm = bradley_terry_bayesian(&scores, &HashMap::new(), &previous, 50);
+
+        for (id, rating) in cold {
+            assert!((warm[&id].mu - rating.mu).abs() < 1e-6);
+            assert!((warm[&id].sigma - rating.sigma).abs() < 1e-6);
+        }
+    }
+
     #[test]
     fn uncertainty_shrinks_with_more_matches() {
         let mut few = HashMap::new();
@@ -1,9 +1,9 @@
 use crate::domain::Rating;
-use crate::ranking::{Algorithm, OnlineAlgorithm};
+use crate::ranking::{Algorithm, OnlineAlgorithm, PerformanceNoise, ELO_SCALE};
 use serde::{Deserialize, Serialize};
 use skillratings::{elo::*, Outcomes};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Config {
     pub k: Option<f64>,
 }
@@ -25,10 +25,7 @@ impl From<Rating> for EloRating {
 
 impl From<EloRating> for Rating {
     fn from(rating: EloRating) -> Self {
-        Rating {
-            mu: rating.rating,
-            sigma: 0.0,
-        }
+        Rating::new(rating.rating, 0.0)
     }
 }
 
@@ -46,26 +43,78 @@ impl Elo {
 
 impl Algorithm for Elo {
     fn supports_multi_team(&self) -> bool {
-        false
+        true
     }
 
     fn default_rating(&self) -> Rating {
         EloRating::default().into()
     }
+
+    fn performance_noise(&self) -> PerformanceNoise {
+        PerformanceNoise::Gumbel(ELO_SCALE)
+    }
 }
 
 impl OnlineAlgorithm for Elo {
+    /// Multi-player match is decomposed into all the pairwise outcomes.
+    /// K-factor is divided by the amount of opponents, so the max rating change
+    /// from a single match does not depend on the player count.
     fn recalc_ratings(&self, input: &[(Rating, u8)]) -> Vec<Rating> {
-        assert_eq!(input.len(), 2);
-        let p1 = input[0].0.into();
-        let p2 = input[1].0.into();
-        let outcome = match input[0].1.cmp(&input[1].1) {
-            std::cmp::Ordering::Less => Outcomes::WIN,
-            std::cmp::Ordering::Equal => Outcomes::DRAW,
-            std::cmp::Ordering::Greater => Outcomes::LOSS,
+        let opponents = input.len().saturating_sub(1).max(1);
+        let config = EloConfig {
+            k: self.config.k / opponents as f64,
         };
 
-        let (new_p1, new_p2) = elo(&p1, &p2, &outcome, &self.config);
-        vec![new_p1.into(), new_p2.into()]
+        input
+            .iter()
+            .enumerate()
+            .map(|(i, &(rating, rank))| {
+                let player: EloRating = rating.into();
+                let delta: f64 = input
+                    .iter()
+                    .enumerate()
+                    .filter(|&(j, _)| i != j)
+                    .map(|(_, &(opp_rating, opp_rank))| {
+                        let outcome = match rank.cmp(&opp_rank) {
+                            std::cmp::Ordering::Less => Outcomes::WIN,
+                            std::cmp::Ordering::Equal => Outcomes::DRAW,
+                            std::cmp::Ordering::Greater => Outcomes::LOSS,
+                        };
+                        let (new_player, _) = elo(&player, &opp_rating.into(), &outcome, &config);
+                        new_player.rating - player.rating
+                    })
+                    .sum();
+                Rating::new(player.rating + delta, 0.0)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_players_match_classic_elo() {
+        let algorithm = Elo::new(Config { k: Some(32.0) });
+        let default = algorithm.default_rating();
+
+        let res = algorithm.recalc_ratings(&[(default, 0), (default, 1)]);
+
+        assert_eq!(res[0].mu, default.mu + 16.0);
+        assert_eq!(res[1].mu, default.mu - 16.0);
+    }
+
+    #[test]
+    fn multi_player_pairwise() {
+        let algorithm = Elo::new(Config { k: Some(30.0) });
+        let default = algorithm.default_rating();
+
+        let res = algorithm.recalc_ratings(&[(default, 1), (default, 0), (default, 2)]);
+
+        // winner beats both opponents with k = 30 / 2
+        assert!((res[1].mu - (default.mu + 15.0)).abs() < 1e-9);
+        assert!((res[0].mu - default.mu).abs() < 1e-9);
+        assert!((res[2].mu - (default.mu - 15.0)).abs() < 1e-9);
     }
 }
@@ -0,0 +1,136 @@
+use crate::domain::Rating;
+use crate::ranking::{Algorithm, OnlineAlgorithm, PerformanceNoise, ELO_SCALE};
+use serde::{Deserialize, Serialize};
+use skillratings::{glicko2::*, Outcomes};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Config {
+    pub tau: Option<f64>,
+    pub default_deviation: Option<f64>,
+}
+
+impl From<Rating> for Glicko2Rating {
+    fn from(rating: Rating) -> Self {
+        Self {
+            rating: rating.mu,
+            deviation: rating.sigma,
+            volatility: rating
+                .volatility
+                .unwrap_or(Glicko2Rating::default().volatility),
+        }
+    }
+}
+
+impl From<Glicko2Rating> for Rating {
+    fn from(rating: Glicko2Rating) -> Self {
+        Rating {
+            mu: rating.rating,
+            sigma: rating.deviation,
+            volatility: Some(rating.volatility),
+        }
+    }
+}
+
+pub struct Glicko2 {
+    config: Glicko2Config,
+    default_deviation: f64,
+}
+
+impl Glicko2 {
+    pub fn new(config: Config) -> Self {
+        let default = Glicko2Config::default();
+        Self {
+            config: Glicko2Config {
+                tau: config.tau.unwrap_or(default.tau),
+                ..default
+            },
+            default_deviation: config
+                .default_deviation
+                .unwrap_or(Glicko2Rating::default().deviation),
+        }
+    }
+}
+
+impl Algorithm for Glicko2 {
+    fn supports_multi_team(&self) -> bool {
+        true
+    }
+
+    fn default_rating(&self) -> Rating {
+        Glicko2Rating {
+            deviation: self.default_deviation,
+            ..Default::default()
+        }
+        .into()
+    }
+
+    fn performance_noise(&self) -> PerformanceNoise {
+        PerformanceNoise::Gumbel(ELO_SCALE)
+    }
+}
+
+impl OnlineAlgorithm for Glicko2 {
+    /// Multi-player match is treated as a rating period in which
+    /// every participant played against each of the others.
+    fn recalc_ratings(&self, input: &[(Rating, u8)]) -> Vec<Rating> {
+        input
+            .iter()
+            .enumerate()
+            .map(|(i, &(rating, rank))| {
+                let results = input
+                    .iter()
+                    .enumerate()
+                    .filter(|&(j, _)| i != j)
+                    .map(|(_, &(opp_rating, opp_rank))| {
+                        let outcome = match rank.cmp(&opp_rank) {
+                            std::cmp::Ordering::Less => Outcomes::WIN,
+                            std::cmp::Ordering::Equal => Outcomes::DRAW,
+                            std::cmp::Ordering::Greater => Outcomes::LOSS,
+                        };
+                        (opp_rating.into(), outcome)
+                    })
+                    .collect::<Vec<_>>();
+
+                glicko2_rating_period(&rating.into(), &results, &self.config).into()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn winner_gains_rating() {
+        let algorithm = Glicko2::new(Config {
+            tau: None,
+            default_deviation: None,
+        });
+        let default = algorithm.default_rating();
+
+        let res = algorithm.recalc_ratings(&[(default, 1), (default, 0)]);
+
+        assert!(res[1].mu > default.mu);
+        assert!(res[0].mu < default.mu);
+        assert!(res[0].sigma < default.sigma);
+        assert!(res[0].volatility.is_some());
+    }
+
+    #[test]
+    fn multi_player_ordering() {
+        let algorithm = Glicko2::new(Config {
+            tau: Some(0.3),
+            default_deviation: Some(200.0),
+        });
+        let default = algorithm.default_rating();
+        assert_eq!(default.sigma, 200.0);
+
+        let res =
+            algorithm.recalc_ratings(&[(default, 2), (default, 0), (default, 1), (default, 1)]);
+
+        assert!(res[1].mu > res[2].mu);
+        assert_eq!(res[2].mu, res[3].mu);
+        assert!(res[2].mu > res[0].mu);
+    }
+}
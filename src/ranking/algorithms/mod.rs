@@ -1,4 +1,6 @@
 pub mod bradley_terry;
 pub mod elo;
+pub mod glicko2;
 pub mod openskill;
+pub mod plackett_luce;
 pub mod trueskill;
@@ -4,7 +4,7 @@ use serde::Deserialize;
 use serde::Serialize;
 use skillratings::MultiTeamOutcome;
 
-use crate::ranking::{Algorithm, OnlineAlgorithm};
+use crate::ranking::{Algorithm, OnlineAlgorithm, PerformanceNoise};
 use skillratings::weng_lin::*;
 
 impl From<Rating> for WengLinRating {
@@ -18,14 +18,11 @@ impl From<Rating> for WengLinRating {
 
 impl From<WengLinRating> for Rating {
     fn from(rating: WengLinRating) -> Self {
-        Rating {
-            mu: rating.rating,
-            sigma: rating.uncertainty,
-        }
+        Rating::new(rating.rating, rating.uncertainty)
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Config {
     pub beta: Option<f64>,
     pub uncertainty_tolerance: Option<f64>,
@@ -63,6 +60,10 @@ impl Algorithm for OpenSkill {
     fn default_rating(&self) -> Rating {
         WengLinRating::default().into()
     }
+
+    fn performance_noise(&self) -> PerformanceNoise {
+        PerformanceNoise::Gaussian(self.config.beta)
+    }
 }
 
 impl OnlineAlgorithm for OpenSkill {
@@ -0,0 +1,478 @@
+use crate::domain::{BotId, Rating};
+use crate::ranking::{Algorithm, BatchAlgorithm, BatchInput, PerformanceNoise, ELO_SCALE};
+use itertools::Itertools;
+use nalgebra::{DMatrix, DVector};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Config {
+    max_iter: Option<usize>,
+}
+
+pub struct PlackettLuce {
+    config: Config,
+}
+
+impl PlackettLuce {
+    pub fn new(config: Config) -> Self {
+        Self { config }
+    }
+}
+
+impl Algorithm for PlackettLuce {
+    fn supports_multi_team(&self) -> bool {
+        true
+    }
+
+    fn default_rating(&self) -> Rating {
+        Rating::new(0f64, 0f64)
+    }
+
+    fn performance_noise(&self) -> PerformanceNoise {
+        PerformanceNoise::Gumbel(ELO_SCALE)
+    }
+}
+
+impl BatchAlgorithm for PlackettLuce {
+    fn needs_placements(&self) -> bool {
+        true
+    }
+
+    fn recalc_batch(&self, input: &BatchInput) -> HashMap<BotId, Rating> {
+        plackett_luce_bayesian(
+            input.placements,
+            input.placement_weights,
+            input.anchors,
+            input.previous,
+            self.config.max_iter.unwrap_or(50),
+        )
+    }
+}
+
+/// One stage of a match ranking: `winners` take the next place(s) among `contenders`.
+/// Tied bots form a single stage (Breslow approximation).
+struct Stage {
+    winners: Vec<usize>,
+    contenders: Vec<usize>,
+    weight: f64,
+    /// when set, only these bots are updated by the stage, the others are treated as fixed
+    only: Option<Vec<usize>>,
+}
+
+impl Stage {
+    fn updates(&self, i: usize) -> bool {
+        self.only.as_ref().is_none_or(|only| only.contains(&i))
+    }
+}
+
+/// Anchored bots are pinned to their ratings, the others are pulled towards `mean`
+struct Prior {
+    mean: f64,
+    fixed: Vec<Option<f64>>,
+}
+
+/// `ranking` is (bot, rank, no gain), see `BatchInput::placements`
+fn build_stages(ranking: &[(usize, u8, bool)], weight: f64) -> Vec<Stage> {
+    let sorted = ranking
+        .iter()
+        .sorted_by_key(|(_, rank, _)| *rank)
+        .collect_vec();
+
+    let mut stages = vec![];
+    let mut start = 0;
+    while start < sorted.len() {
+        let rank = sorted[start].1;
+        let end = sorted[start..]
+            .iter()
+            .position(|(_, r, _)| *r != rank)
+            .map_or(sorted.len(), |len| start + len);
+
+        let winners = sorted[start..end].iter().map(|(i, _, _)| *i).collect_vec();
+        let contenders = sorted[start..].iter().map(|(i, _, _)| *i).collect_vec();
+        let no_gain_losers = sorted[end..]
+            .iter()
+            .filter(|(_, _, no_gain)| *no_gain)
+            .map(|(i, _, _)| *i)
+            .collect_vec();
+        let gaining_winner = sorted[start..end].iter().any(|(_, _, no_gain)| !no_gain);
+
+        if gaining_winner && !no_gain_losers.is_empty() {
+            // the winners only gain over the others, while the errored losers
+            // still lose to the winners as usual
+            stages.push(Stage {
+                winners: winners.clone(),
+                contenders: contenders
+                    .iter()
+                    .copied()
+                    .filter(|i| !no_gain_losers.contains(i))
+                    .collect(),
+                weight,
+                only: None,
+            });
+            stages.push(Stage {
+                winners,
+                contenders,
+                weight,
+                only: Some(no_gain_losers),
+            });
+        } else {
+            stages.push(Stage {
+                winners,
+                contenders,
+                weight,
+                only: None,
+            });
+        }
+        start = end;
+    }
+
+    // the last stage of a match without ties carries no information
+    stages.retain(|stage| stage.contenders.len() > 1);
+    stages
+}
+
+// ------------------------------------------------------------
+// Gradient & Hessian (Bayesian Plackett–Luce)
+// ------------------------------------------------------------
+fn compute_grad_hess(
+    n: usize,
+    stages: &[Stage],
+    s: &DVector<f64>,
+    tau: f64, // prior stddev in natural scale
+    prior: &Prior,
+) -> (DVector<f64>, DMatrix<f64>) {
+    let mut grad = DVector::<f64>::zeros(n);
+    let mut hess = DMatrix::<f64>::zeros(n, n);
+
+    for stage in stages {
+        let k = stage.winners.len() as f64;
+        let w = stage.weight;
+
+        // softmax over contenders, shifted for numerical stability
+        let max_s = stage
+            .contenders
+            .iter()
+            .map(|&i| s[i])
+            .fold(f64::NEG_INFINITY, f64::max);
+        let weights = stage
+            .contenders
+            .iter()
+            .map(|&i| (s[i] - max_s).exp())
+            .collect_vec();
+        let total: f64 = weights.iter().sum();
+        let probs = weights.iter().map(|w| w / total).collect_vec();
+
+        // Likelihood gradient
+        for &i in stage.winners.iter().filter(|&&i| stage.updates(i)) {
+            grad[i] += w;
+        }
+        for (&i, &p) in stage.contenders.iter().zip(&probs) {
+            if stage.updates(i) {
+                grad[i] -= w * k * p;
+            }
+        }
+
+        // Likelihood Hessian
+        for (a, (&i, &p_i)) in stage.contenders.iter().zip(&probs).enumerate() {
+            if !stage.updates(i) {
+                continue;
+            }
+            hess[(i, i)] -= w * k * p_i;
+            for (&j, &p_j) in stage.contenders.iter().zip(&probs).skip(a) {
+                if !stage.updates(j) {
+                    continue;
+                }
+                let h = w * k * p_i * p_j;
+                hess[(i, j)] += h;
+                if i != j {
+                    hess[(j, i)] += h;
+                }
+            }
+        }
+    }
+
+    // Gaussian prior: s_i ~ N(mean, tau²)
+    let prior_prec = 1.0 / (tau * tau);
+
+    for i in 0..n {
+        grad[i] -= (s[i] - prior.mean) * prior_prec;
+        hess[(i, i)] -= prior_prec;
+    }
+
+    // Anchors: zero step, unit diagonal keeps the Hessian invertible
+    for (i, fixed) in prior.fixed.iter().enumerate() {
+        if fixed.is_some() {
+            grad[i] = 0.0;
+            hess.row_mut(i).fill(0.0);
+            hess.column_mut(i).fill(0.0);
+            hess[(i, i)] = -1.0;
+        }
+    }
+
+    (grad, hess)
+}
+
+// ------------------------------------------------------------
+// Fit MAP using Newton method,
+// `None` if the Hessian is singular or the step is not finite
+// ------------------------------------------------------------
+fn fit_map(
+    n: usize,
+    stages: &[Stage],
+    tau: f64,
+    prior: &Prior,
+    mut s: DVector<f64>,
+    max_iter: usize,
+) -> Option<(DVector<f64>, DMatrix<f64>)> {
+    for _ in 0..max_iter {
+        let (grad, hess) = compute_grad_hess(n, stages, &s, tau, prior);
+
+        let fisher = -&hess;
+        let step = fisher.lu().solve(&grad)?;
+        if !step.iter().all(|x| x.is_finite()) {
+            return None;
+        }
+
+        s += &step;
+
+        if step.amax() < 1e-8 {
+            break;
+        }
+    }
+
+    let (_, hess) = compute_grad_hess(n, stages, &s, tau, prior);
+    let fisher = -hess;
+
+    let covariance = fisher.try_inverse()?;
+
+    Some((s, covariance))
+}
+
+// ------------------------------------------------------------
+// Public API
+// ------------------------------------------------------------
+fn plackett_luce_bayesian(
+    placements: &[&[(BotId, u8, bool)]],
+    weights: Option<&[f64]>,
+    anchors: &HashMap<BotId, f64>,
+    previous: &HashMap<BotId, Rating>,
+    max_iter: usize,
+) -> HashMap<BotId, Rating> {
+    if placements.is_empty() {
+        return HashMap::new();
+    }
+
+    let scale = ELO_SCALE;
+    let base = 1500.0;
+
+    // --------------------------------------------------------
+    // Build index & stages
+    // --------------------------------------------------------
+    let mut bots = HashMap::<BotId, usize>::new();
+    let mut stages = Vec::<Stage>::new();
+
+    for (i, placement) in placements.iter().enumerate() {
+        let weight = weights.map_or(1.0, |weights| weights[i]);
+        let ranking = placement
+            .iter()
+            .map(|(bot_id, rank, no_gain)| {
+                let idx = bots.len();
+                (*bots.entry(*bot_id).or_insert(idx), *rank, *no_gain)
+            })
+            .collect_vec();
+        stages.extend(build_stages(&ranking, weight));
+    }
+
+    let n = bots.len();
+
+    // --------------------------------------------------------
+    // Anchors define the location of the scale
+    // --------------------------------------------------------
+    let mut fixed = vec![None; n];
+    for (bot, &idx) in &bots {
+        fixed[idx] = anchors.get(bot).map(|mu| (mu - base) / scale);
+    }
+    let anchored = fixed.iter().flatten().copied().collect_vec();
+    let prior = Prior {
+        mean: anchored.iter().sum::<f64>() / anchored.len().max(1) as f64,
+        fixed,
+    };
+
+    // warm start from the previous fit, new bots start at the prior mean
+    let mut start = DVector::<f64>::from_element(n, prior.mean);
+    for (bot, &idx) in &bots {
+        start[idx] = prior.fixed[idx]
+            .or_else(|| previous.get(bot).map(|r| (r.mu - base) / scale))
+            .unwrap_or(prior.mean);
+    }
+
+    // --------------------------------------------------------
+    // First fit with weak prior, then empirical Bayes refit
+    // --------------------------------------------------------
+    let tau_elo = 400.0;
+    let tau = tau_elo / scale;
+
+    // a failed fit keeps the previous ratings instead of publishing broken ones
+    let Some((s, covariance)) = fit_map(n, &stages, tau, &prior, start, max_iter) else {
+        return previous.clone();
+    };
+
+    let mean_s = s.iter().sum::<f64>() / n as f64;
+    let var_s = s.iter().map(|x| (x - mean_s).powi(2)).sum::<f64>() / n as f64;
+    let avg_post_var = covariance.diagonal().sum() / n as f64;
+
+    // Avoid collapse
+    let tau = (var_s + avg_post_var).sqrt().max(1e-6);
+
+    let Some((s, covariance)) = fit_map(n, &stages, tau, &prior, s, max_iter) else {
+        return previous.clone();
+    };
+
+    // --------------------------------------------------------
+    // Convert to Elo
+    // --------------------------------------------------------
+    let total_mean_covariance = covariance.iter().sum::<f64>() / (n * n) as f64;
+
+    bots.into_iter()
+        .map(|(bot, idx)| {
+            let mu = anchors.get(&bot).copied().unwrap_or(base + s[idx] * scale);
+            let row_mean_covariance = covariance.row(idx).iter().sum::<f64>() / n as f64;
+            let variance = if prior.fixed[idx].is_some() {
+                0.0
+            } else if !anchored.is_empty() {
+                covariance[(idx, idx)]
+            } else {
+                // same as Bradley-Terry, only relative skill is identified
+                (covariance[(idx, idx)] - 2.0 * row_mean_covariance + total_mean_covariance)
+                    .max(0.0)
+            };
+            (bot, Rating::new(mu, variance.sqrt() * scale))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(matches: &[Vec<(i64, u8)>]) -> HashMap<i64, Rating> {
+        let placements = matches
+            .iter()
+            .map(|m| {
+                m.iter()
+                    .map(|&(id, rank)| (id.into(), rank, false))
+                    .collect_vec()
+            })
+            .collect_vec();
+        let placements = placements.iter().map(Vec::as_slice).collect_vec();
+        plackett_luce_bayesian(&placements, None, &HashMap::new(), &HashMap::new(), 50)
+            .into_iter()
+            .map(|(key, value)| (key.into(), value))
+            .collect()
+    }
+
+    #[test]
+    fn empty_dataset_returns_empty() {
+        assert!(run(&[]).is_empty());
+    }
+
+    #[test]
+    fn full_ranking_is_preserved() {
+        let matches = vec![vec![(1, 0), (2, 1), (3, 2), (4, 3)]; 20];
+
+        let result = run(&matches);
+
+        assert_eq!(result.len(), 4);
+        assert!(result[&1].mu > result[&2].mu);
+        assert!(result[&2].mu > result[&3].mu);
+        assert!(result[&3].mu > result[&4].mu);
+    }
+
+    #[test]
+    fn tied_bots_get_equal_rating() {
+        let matches = vec![vec![(1, 0), (2, 1), (3, 1)]; 10];
+
+        let result = run(&matches);
+
+        assert!(result[&1].mu > result[&2].mu);
+        assert!((result[&2].mu - result[&3].mu).abs() < 1e-6);
+    }
+
+    #[test]
+    fn uses_placements_beyond_winner() {
+        // bot 2 is always second, bot 3 always last, bot 1 and 4 share the wins
+        let mut matches = vec![vec![(1, 0), (2, 1), (3, 2), (4, 3)]; 10];
+        matches.extend(vec![vec![(4, 0), (2, 1), (3, 2), (1, 3)]; 10]);
+
+        let result = run(&matches);
+
+        assert!(result[&2].mu > result[&3].mu);
+    }
+
+    #[test]
+    fn weights_favor_heavier_placements() {
+        let old = vec![(1.into(), 0, false), (2.into(), 1, false)];
+        let recent = vec![(2.into(), 0, false), (1.into(), 1, false)];
+        let mut placements = vec![old.as_slice(); 10];
+        placements.extend(vec![recent.as_slice(); 5]);
+        let mut weights = vec![0.1; 10];
+        weights.extend(vec![1.0; 5]);
+
+        let result = plackett_luce_bayesian(
+            &placements,
+            Some(&weights),
+            &HashMap::new(),
+            &HashMap::new(),
+            50,
+        );
+
+        assert!(result[&2.into()].mu > result[&1.into()].mu);
+    }
+
+    #[test]
+    fn failed_fit_keeps_previous_ratings() {
+        let placement = vec![(1.into(), 0, false), (2.into(), 1, false)];
+        let placements = vec![placement.as_slice(); 5];
+        let weights = vec![f64::NAN; 5];
+        let previous = HashMap::from([(1.into(), Rating::new(1600.0, 50.0))]);
+
+        let result =
+            plackett_luce_bayesian(&placements, Some(&weights), &HashMap::new(), &previous, 50);
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[&1.into()].mu, 1600.0);
+    }
+
+    #[test]
+    fn anchors_keep_their_rating() {
+        let placement = vec![
+            (1.into(), 0, false),
+            (2.into(), 1, false),
+            (3.into(), 2, false),
+        ];
+        let placements = vec![placement.as_slice(); 10];
+        let anchors = HashMap::from([(3.into(), 1000.0)]);
+
+        let result = plackett_luce_bayesian(&placements, None, &anchors, &HashMap::new(), 50);
+
+        assert_eq!(result[&3.into()].mu, 1000.0);
+        assert_eq!(result[&3.into()].sigma, 0.0);
+        assert!(result[&2.into()].mu > 1000.0);
+        assert!(result[&1.into()].mu > result[&2.into()].mu);
+    }
+
+    #[test]
+    fn more_matches_reduce_uncertainty() {
+        let few = run(&vec![vec![(1, 0), (2, 1), (3, 2)]; 5]);
+
+        let mut matches = vec![];
+        for _ in 0..50 {
+            matches.push(vec![(1, 0), (2, 1), (3, 2)]);
+            matches.push(vec![(2, 0), (3, 1), (1, 2)]);
+        }
+        let many = run(&matches);
+
+        assert!(many[&1].sigma < few[&1].sigma);
+    }
+}
@@ -1,10 +1,10 @@
 use crate::domain::Rating;
-use crate::ranking::{Algorithm, OnlineAlgorithm};
+use crate::ranking::{Algorithm, OnlineAlgorithm, PerformanceNoise};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use skillratings::{trueskill::*, MultiTeamOutcome};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Config {
     pub draw_probability: Option<f64>,
     pub beta: Option<f64>,
@@ -33,10 +33,7 @@ impl From<Rating> for TrueSkillRating {
 
 impl From<TrueSkillRating> for Rating {
     fn from(rating: TrueSkillRating) -> Self {
-        Rating {
-            mu: rating.rating,
-            sigma: rating.uncertainty,
-        }
+        Rating::new(rating.rating, rating.uncertainty)
     }
 }
 
@@ -60,6 +57,10 @@ impl Algorithm for Trueskill {
     fn default_rating(&self) -> Rating {
         TrueSkillRating::default().into()
     }
+
+    fn performance_noise(&self) -> PerformanceNoise {
+        PerformanceNoise::Gaussian(self.config.beta)
+    }
 }
 
 impl OnlineAlgorithm for Trueskill {
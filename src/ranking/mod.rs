@@ -1,6 +1,8 @@
 mod algorithm;
 pub mod algorithms;
+mod prediction;
 mod ranker;
 
 pub use algorithm::*;
+pub use prediction::*;
 pub use ranker::*;
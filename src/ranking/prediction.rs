@@ -0,0 +1,140 @@
+use crate::domain::Rating;
+use itertools::Itertools;
+use rand::Rng;
+use std::f64::consts::PI;
+
+/// Random deviation of a bot's performance in a single match from its skill.
+/// The bot with the best performance wins, so it defines the outcome probabilities
+/// assumed by a ranking algorithm.
+#[derive(Copy, Clone, Debug)]
+pub enum PerformanceNoise {
+    /// normal distribution with the given standard deviation (TrueSkill, OpenSkill)
+    Gaussian(f64),
+    /// Gumbel distribution with the given scale, pairwise outcomes follow the logistic curve
+    /// (Elo, Glicko-2, Bradley-Terry, Plackett-Luce)
+    Gumbel(f64),
+}
+
+/// Logistic scale of the Elo-like rating systems, 400 points difference means 10:1 odds
+pub const ELO_SCALE: f64 = 400.0 / std::f64::consts::LN_10;
+
+impl PerformanceNoise {
+    /// Probability of `a` finishing ahead of `b`, skill uncertainty included
+    pub fn win_probability(&self, a: &Rating, b: &Rating) -> f64 {
+        let diff = a.mu - b.mu;
+        let skill_variance = a.sigma.powi(2) + b.sigma.powi(2);
+        match *self {
+            PerformanceNoise::Gaussian(beta) => {
+                normal_cdf(diff / (2.0 * beta.powi(2) + skill_variance).sqrt())
+            }
+            PerformanceNoise::Gumbel(scale) => {
+                // logistic-normal convolution approximated by a wider logistic
+                let scale = (scale.powi(2) + 3.0 * skill_variance / PI.powi(2)).sqrt();
+                1.0 / (1.0 + (-diff / scale).exp())
+            }
+        }
+    }
+
+    fn sample_performance<R: Rng>(&self, rating: &Rating, rng: &mut R) -> f64 {
+        let skill = rating.mu + rating.sigma * standard_normal(rng);
+        match *self {
+            PerformanceNoise::Gaussian(beta) => skill + beta * standard_normal(rng),
+            PerformanceNoise::Gumbel(scale) => {
+                let u: f64 = rng.random_range(f64::EPSILON..1.0);
+                skill - scale * (-u.ln()).ln()
+            }
+        }
+    }
+
+    /// Monte Carlo estimate of the match outcome, i-th row holds the probabilities
+    /// of the i-th bot finishing at each place
+    pub fn placement_probabilities<R: Rng>(
+        &self,
+        ratings: &[Rating],
+        samples: usize,
+        rng: &mut R,
+    ) -> Vec<Vec<f64>> {
+        let n = ratings.len();
+        let mut counts = vec![vec![0u64; n]; n];
+        for _ in 0..samples {
+            let performances = ratings
+                .iter()
+                .map(|r| self.sample_performance(r, rng))
+                .collect_vec();
+            let order = (0..n)
+                .sorted_by(|&i, &j| performances[j].total_cmp(&performances[i]))
+                .collect_vec();
+            for (place, i) in order.into_iter().enumerate() {
+                counts[i][place] += 1;
+            }
+        }
+        counts
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|c| c as f64 / samples.max(1) as f64)
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+// Box-Muller transform
+fn standard_normal<R: Rng>(rng: &mut R) -> f64 {
+    let u1: f64 = rng.random_range(f64::EPSILON..1.0);
+    let u2: f64 = rng.random();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+}
+
+// Abramowitz and Stegun 7.1.26, absolute error below 1.5e-7
+fn normal_cdf(x: f64) -> f64 {
+    let z = x.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * z);
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-z * z).exp();
+    if x >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn normal_cdf_values() {
+        assert!((normal_cdf(0.0) - 0.5).abs() < 1e-6);
+        assert!((normal_cdf(1.96) - 0.975).abs() < 1e-3);
+        assert!((normal_cdf(-1.0) - 0.1587).abs() < 1e-3);
+    }
+
+    #[test]
+    fn gumbel_matches_elo_expectation() {
+        let noise = PerformanceNoise::Gumbel(ELO_SCALE);
+        let p = noise.win_probability(&Rating::new(1900.0, 0.0), &Rating::new(1500.0, 0.0));
+        assert!((p - 10.0 / 11.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn monte_carlo_agrees_with_pairwise_probability() {
+        let ratings = [Rating::new(30.0, 1.0), Rating::new(25.0, 2.0)];
+        let mut rng = StdRng::seed_from_u64(42);
+
+        for noise in [
+            PerformanceNoise::Gaussian(25.0 / 6.0),
+            PerformanceNoise::Gumbel(5.0),
+        ] {
+            let placements = noise.placement_probabilities(&ratings, 20000, &mut rng);
+            let expected = noise.win_probability(&ratings[0], &ratings[1]);
+            assert!((placements[0][0] - expected).abs() < 0.02);
+            assert!((placements[0][0] + placements[0][1] - 1.0).abs() < 1e-9);
+            assert!((placements[0][0] + placements[1][0] - 1.0).abs() < 1e-9);
+        }
+    }
+}
@@ -1,14 +1,65 @@
-use crate::config::RankingConfig;
-use crate::domain::{BotId, Match, Rating, WinrateStats};
-use crate::ranking::algorithms::{bradley_terry, elo, openskill, trueskill};
-use crate::ranking::{BatchAlgorithm, OnlineAlgorithm};
+use crate::config::{DecayConfig, RankingAlgorithmConfig, RankingConfig};
+use crate::domain::{BotId, Match, Rating};
+use crate::ranking::algorithms::{
+    bradley_terry, elo, glicko2, openskill, plackett_luce, trueskill,
+};
+use crate::ranking::{BatchAlgorithm, BatchInput, OnlineAlgorithm, PerformanceNoise};
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 pub struct Ranker {
     strategy: RankingStrategy,
+    per_player_count: bool,
+    error_policy: ErrorPolicy,
+    decay: Option<Decay>,
+    permutations: usize,
+    anchors: HashMap<BotId, f64>,
 }
 
+/// How the matches with errored participants affect the ratings
+#[derive(Serialize, Deserialize, Copy, Clone, Default, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorPolicy {
+    /// match result is used as-is
+    #[default]
+    Count,
+    /// match is ignored if any participant errored
+    ExcludeMatch,
+    /// errored participant loses as usual, but the others can't gain from it
+    ExcludeOpponentGain,
+}
+
+/// How fast older matches lose their weight
+#[derive(Copy, Clone)]
+pub enum Decay {
+    HalfLifeMatches(f64),
+    HalfLifeDays(f64),
+}
+
+impl Decay {
+    fn new(config: DecayConfig) -> Option<Decay> {
+        match (config.half_life_matches, config.half_life_days) {
+            (Some(half_life), _) => Some(Decay::HalfLifeMatches(half_life)),
+            (None, Some(half_life)) => Some(Decay::HalfLifeDays(half_life)),
+            (None, None) => None,
+        }
+    }
+
+    /// Position of the match on the time axis measured in half-lives,
+    /// `index` is the match number within the leaderboard
+    pub fn clock(&self, index: u64, m: &Match) -> f64 {
+        match *self {
+            Decay::HalfLifeMatches(half_life) => index as f64 / half_life,
+            Decay::HalfLifeDays(half_life) => {
+                m.created_at.timestamp() as f64 / SECONDS_PER_DAY / half_life
+            }
+        }
+    }
+}
+
+const SECONDS_PER_DAY: f64 = 24.0 * 60.0 * 60.0;
+
 enum RankingStrategy {
     Online(Box<dyn OnlineAlgorithm + Sync + Send>),
     Batch(Box<dyn BatchAlgorithm + Sync + Send>),
@@ -22,19 +73,76 @@ pub enum RankingStrategyKind {
 
 impl Ranker {
     pub fn new(config: RankingConfig) -> Ranker {
-        let strategy = match config {
-            RankingConfig::OpenSkill(c) => {
+        let strategy = match config.algorithm {
+            RankingAlgorithmConfig::OpenSkill(c) => {
                 RankingStrategy::Online(Box::new(openskill::OpenSkill::new(c)))
             }
-            RankingConfig::TrueSkill(c) => {
+            RankingAlgorithmConfig::TrueSkill(c) => {
                 RankingStrategy::Online(Box::new(trueskill::Trueskill::new(c)))
             }
-            RankingConfig::Elo(c) => RankingStrategy::Online(Box::new(elo::Elo::new(c))),
-            RankingConfig::BradleyTerry(c) => {
+            RankingAlgorithmConfig::Elo(c) => RankingStrategy::Online(Box::new(elo::Elo::new(c))),
+            RankingAlgorithmConfig::Glicko2(c) => {
+                RankingStrategy::Online(Box::new(glicko2::Glicko2::new(c)))
+            }
+            RankingAlgorithmConfig::BradleyTerry(c) => {
                 RankingStrategy::Batch(Box::new(bradley_terry::BradleyTerry::new(c)))
             }
+            RankingAlgorithmConfig::PlackettLuce(c) => {
+                RankingStrategy::Batch(Box::new(plackett_luce::PlackettLuce::new(c)))
+            }
         };
-        Self { strategy }
+        Self {
+            strategy,
+            per_player_count: config.per_player_count.unwrap_or(false),
+            error_policy: config.error_policy.unwrap_or_default(),
+            decay: config.decay.and_then(Decay::new),
+            permutations: config.permutations.unwrap_or(1),
+            anchors: config
+                .anchors
+                .unwrap_or_default()
+                .into_iter()
+                .map(|a| (a.bot_id.into(), a.rating))
+                .collect(),
+        }
+    }
+
+    /// Whether separate ratings should be kept for each player count
+    pub fn per_player_count(&self) -> bool {
+        self.per_player_count
+    }
+
+    pub fn error_policy(&self) -> ErrorPolicy {
+        self.error_policy
+    }
+
+    pub fn decay(&self) -> Option<Decay> {
+        self.decay
+    }
+
+    pub fn anchors(&self) -> &HashMap<BotId, f64> {
+        &self.anchors
+    }
+
+    /// Fixed rating of the bot if it's an anchor
+    pub fn anchor(&self, id: BotId) -> Option<Rating> {
+        self.anchors.get(&id).map(|&mu| Rating::new(mu, 0.0))
+    }
+
+    /// Amount of random match orders the online ratings are averaged over
+    pub fn permutations(&self) -> usize {
+        self.permutations
+    }
+
+    /// Moves the rating uncertainty back towards the default one as the bot's
+    /// last match gets older, `elapsed` is measured in half-lives
+    pub fn inflate_sigma(&self, rating: Rating, elapsed: f64) -> Rating {
+        let default_sigma = self.default_rating().sigma;
+        let remaining = 0.5f64.powf(elapsed.max(0.0));
+        let variance = remaining * rating.sigma.powi(2) + (1.0 - remaining) * default_sigma.powi(2);
+        Rating {
+            sigma: variance.sqrt().max(rating.sigma),
+            ..rating
+        }
     }
 
     pub fn support_multi_team(&self) -> bool {
@@ -51,6 +159,13 @@ impl Ranker {
         }
     }
 
+    pub fn performance_noise(&self) -> PerformanceNoise {
+        match &self.strategy {
+            RankingStrategy::Online(algorithm) => algorithm.performance_noise(),
+            RankingStrategy::Batch(algorithm) => algorithm.performance_noise(),
+        }
+    }
+
     pub fn strategy_kind(&self) -> RankingStrategyKind {
         match &self.strategy {
             RankingStrategy::Online(_) => RankingStrategyKind::Online,
@@ -58,15 +173,24 @@ impl Ranker {
         }
     }
 
-    pub fn recalc_rating_batch(
-        &self,
-        winrate_stats: &HashMap<(BotId, BotId), WinrateStats>,
-    ) -> HashMap<BotId, Rating> {
+    /// Whether match placements should be collected for `recalc_rating_batch`
+    pub fn needs_placements(&self) -> bool {
+        match &self.strategy {
+            RankingStrategy::Online(_) => false,
+            RankingStrategy::Batch(algorithm) => algorithm.needs_placements(),
+        }
+    }
+
+    pub fn recalc_rating_batch(&self, input: &BatchInput) -> HashMap<BotId, Rating> {
         let RankingStrategy::Batch(algorithm) = &self.strategy else {
             panic!("recalc_rating_batch called on non-batch strategy")
         };
 
-        algorithm.recalc_batch(winrate_stats)
+        let mut ratings = algorithm.recalc_batch(input);
+        for (&id, &mu) in &self.anchors {
+            ratings.insert(id, Rating::new(mu, 0.0));
+        }
+        ratings
     }
 
     pub fn recalc_rating(&self, ratings: &mut HashMap<BotId, Rating>, m: &Match) {
@@ -80,20 +204,31 @@ impl Ranker {
             .participants
             .iter()
             .map(|p| {
-                (
-                    ratings.get(&p.bot_id).copied().unwrap_or(default_rating),
-                    p.rank,
-                )
+                let rating = self
+                    .anchor(p.bot_id)
+                    .unwrap_or_else(|| ratings.get(&p.bot_id).copied().unwrap_or(default_rating));
+                (rating, p.rank)
             })
             .collect_vec();
 
         let new_ratings = algorithm.recalc_ratings(&ps);
 
+        let exclude_gain = self.error_policy == ErrorPolicy::ExcludeOpponentGain
+            && m.participants.iter().any(|p| p.error);
+
         m.participants
             .iter()
+            .zip_eq(ps)
             .zip_eq(new_ratings)
-            .for_each(|(p, new_rating)| {
-                ratings.insert(p.bot_id, new_rating);
+            .for_each(|((p, (old_rating, _)), new_rating)| {
+                let rating = if let Some(anchor) = self.anchor(p.bot_id) {
+                    anchor
+                } else if exclude_gain && !p.error && new_rating.mu > old_rating.mu {
+                    old_rating
+                } else {
+                    new_rating
+                };
+                ratings.insert(p.bot_id, rating);
             });
     }
 }
@@ -1,6 +1,7 @@
 use crate::config::EmbeddedWorkerConfig;
 use crate::domain::{
-    BotId, BuildResult, Language, MatchAttribute, Participant, SourceCode, WorkerName,
+    BotId, BuildResult, Language, MatchAttribute, Participant, SourceCode, TournamentGameId,
+    WorkerName,
 };
 use anyhow::{bail, Context};
 use itertools::Itertools;
@@ -267,6 +268,7 @@ async fn spawn_play_match_command(
 
     let output = PlayMatchOutput {
         seed: input.seed,
+        tournament_game_id: input.tournament_game_id,
         participants: input
             .bots
             .iter()
@@ -306,6 +308,7 @@ pub struct BuildBotOutput {
 pub struct PlayMatchInput {
     pub bots: Vec<PlayMatchBot>,
     pub seed: i64,
+    pub tournament_game_id: Option<TournamentGameId>,
 }
 
 #[derive(Clone)]
@@ -316,6 +319,7 @@ pub struct PlayMatchBot {
 
 pub struct PlayMatchOutput {
     pub seed: i64,
+    pub tournament_game_id: Option<TournamentGameId>,
     pub participants: Vec<Participant>,
     pub attributes: Vec<MatchAttribute>,
 }